@@ -0,0 +1,68 @@
+//! Linear scan vs `ObstacleGrid` on a 10k-obstacle scene
+//!
+//! Run with `cargo bench --bench obstacle_grid`.
+
+use nav_lambda_core::*;
+use std::time::Instant;
+
+const OBSTACLES: usize = 10_000;
+const QUERIES: usize = 2_000;
+
+fn main() {
+    rust_core_init();
+
+    // Deterministic scene spread over a 1 km square
+    let mut seed: u32 = 42;
+    let mut next = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 8) as f32 / (1u32 << 24) as f32
+    };
+    let obstacles: Vec<f32> = (0..OBSTACLES)
+        .flat_map(|_| [next() * 1000.0 - 500.0, next() * 1000.0 - 500.0, 0.0])
+        .collect();
+    let states: Vec<State7D> = (0..QUERIES)
+        .map(|i| State7D {
+            position: [next() * 1000.0 - 500.0, next() * 1000.0 - 500.0, 0.0],
+            velocity: [next() * 4.0 - 2.0, next() * 4.0 - 2.0, 0.0],
+            heading: 0.0,
+            timestamp: i as u64,
+            certainty: 0.9,
+            fatigue: 0.9,
+        })
+        .collect();
+    let params = RigorParams {
+        min_margin: 0.5,
+        horizon_secs: 2.0,
+        planar_mode: 1,
+        ..Default::default()
+    };
+
+    let run = |score: &dyn Fn(&State7D, &mut VerificationResult) -> NavStatus| {
+        let start = Instant::now();
+        for state in &states {
+            let mut result = VerificationResult::default();
+            assert_eq!(score(state, &mut result), NavStatus::Ok);
+            unsafe {
+                free_c_string(result.breach_reason);
+                free_c_string(result.evidence_hash);
+            }
+        }
+        start.elapsed()
+    };
+
+    let linear = run(&|state, result| unsafe {
+        calculate_p_score(state, &params, obstacles.as_ptr(), OBSTACLES, result)
+    });
+
+    let build_start = Instant::now();
+    let grid = unsafe { nav_build_obstacle_grid(obstacles.as_ptr(), OBSTACLES, 10.0) };
+    let build = build_start.elapsed();
+    assert!(!grid.is_null());
+    let indexed = run(&|state, result| unsafe { calculate_p_score_grid(state, &params, grid, result) });
+    unsafe { nav_free_obstacle_grid(grid) };
+
+    println!("{OBSTACLES} obstacles, {QUERIES} queries");
+    println!("  linear: {:>10.2?} total, {:>8.2?}/query", linear, linear / QUERIES as u32);
+    println!("  grid:   {:>10.2?} total, {:>8.2?}/query (build {:.2?})", indexed, indexed / QUERIES as u32, build);
+    println!("  speedup: {:.1}x", linear.as_secs_f64() / indexed.as_secs_f64());
+}
@@ -0,0 +1,51 @@
+//! `calculate_p_score` on 10k obstacles, scalar vs `simd` feature
+//!
+//! Run once per path and compare:
+//!
+//! ```text
+//! cargo bench --bench obstacle_margins
+//! cargo bench --bench obstacle_margins --features simd
+//! ```
+
+use nav_lambda_core::*;
+use std::time::Instant;
+
+const OBSTACLES: usize = 10_000;
+const QUERIES: usize = 2_000;
+
+fn main() {
+    rust_core_init();
+
+    let mut seed: u32 = 42;
+    let mut next = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 8) as f32 / (1u32 << 24) as f32 * 1000.0 - 500.0
+    };
+    let obstacles: Vec<f32> = (0..OBSTACLES).flat_map(|_| [next(), next(), next()]).collect();
+    // Well clear of every obstacle, so each query scans the whole array
+    let state = State7D {
+        position: [5000.0, 5000.0, 0.0],
+        velocity: [0.0; 3],
+        heading: 0.0,
+        timestamp: 0,
+        certainty: 0.9,
+        fatigue: 0.9,
+    };
+    let params = RigorParams { min_margin: 0.5, ..Default::default() };
+
+    let start = Instant::now();
+    for _ in 0..QUERIES {
+        let mut result = VerificationResult::default();
+        let status = unsafe { calculate_p_score(&state, &params, obstacles.as_ptr(), OBSTACLES, &mut result) };
+        assert_eq!(status, NavStatus::Ok);
+        unsafe {
+            free_c_string(result.breach_reason);
+            free_c_string(result.evidence_hash);
+        }
+    }
+    let elapsed = start.elapsed();
+
+    let path = if cfg!(feature = "simd") { "simd" } else { "scalar" };
+    println!("{OBSTACLES} obstacles, {QUERIES} queries ({path})");
+    println!("  {:>10.2?} total, {:>8.2?}/query", elapsed, elapsed / QUERIES as u32);
+}
@@ -0,0 +1,62 @@
+//! `compute_risk_grid` vs a `calculate_min_margin` call per cell
+//!
+//! Run with `cargo bench --bench risk_grid`.
+
+use nav_lambda_core::*;
+use std::time::Instant;
+
+const OBSTACLES: usize = 1_000;
+const SIDE: usize = 256; // Cells per grid side
+const CELL_SIZE: f32 = 4.0; // Grid spans ~1 km
+
+fn main() {
+    rust_core_init();
+
+    // Deterministic scene spread over a 1 km square
+    let mut seed: u32 = 42;
+    let mut next = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 8) as f32 / (1u32 << 24) as f32
+    };
+    let obstacles: Vec<f32> = (0..OBSTACLES)
+        .flat_map(|_| [next() * 1000.0 - 500.0, next() * 1000.0 - 500.0, 0.0])
+        .collect();
+    let params = RigorParams {
+        min_margin: 0.5,
+        planar_mode: 1,
+        ..Default::default()
+    };
+    let origin = -(SIDE as f32) * CELL_SIZE / 2.0;
+
+    let mut grid = vec![0.0; SIDE * SIDE];
+    let start = Instant::now();
+    let status = unsafe {
+        compute_risk_grid(&params, obstacles.as_ptr(), OBSTACLES, origin, origin, CELL_SIZE, SIDE, SIDE, grid.as_mut_ptr())
+    };
+    let batched = start.elapsed();
+    assert_eq!(status, NavStatus::Ok);
+
+    let start = Instant::now();
+    let mut breaches = 0;
+    for row in 0..SIDE {
+        for col in 0..SIDE {
+            let state = State7D {
+                position: [origin + (col as f32 + 0.5) * CELL_SIZE, origin + (row as f32 + 0.5) * CELL_SIZE, 0.0],
+                velocity: [0.0; 3],
+                heading: 0.0,
+                timestamp: 0,
+                certainty: 1.0,
+                fatigue: 1.0,
+            };
+            let margin = unsafe { calculate_min_margin(&state, &params, obstacles.as_ptr(), OBSTACLES) };
+            breaches += usize::from(margin < 0.0);
+        }
+    }
+    let per_cell = start.elapsed();
+    assert_eq!(breaches, grid.iter().filter(|&&m| m < 0.0).count());
+
+    let cells = (SIDE * SIDE) as u32;
+    println!("{OBSTACLES} obstacles, {SIDE}x{SIDE} cells");
+    println!("  compute_risk_grid:    {:>10.2?} total, {:>8.2?}/cell", batched, batched / cells);
+    println!("  calculate_min_margin: {:>10.2?} total, {:>8.2?}/cell", per_cell, per_cell / cells);
+}
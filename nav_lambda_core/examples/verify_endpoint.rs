@@ -0,0 +1,136 @@
+//! A `/verify` endpoint that runs batch verification off the async executor
+//!
+//! Run with `cargo run --example verify_endpoint --features async`, then:
+//!
+//! ```text
+//! curl -X POST localhost:8090/verify \
+//!     -d '{"positions": [[0, 0, 0], [4.6, 0, 0]], "obstacles": [[5, 0, 0]]}'
+//! ```
+//!
+//! The HTTP handling is deliberately minimal (one request per connection,
+//! `Content-Length` bodies only); the point is the `async_api` call, which
+//! keeps the accept loop responsive however large the batch.
+
+use nav_lambda_core::async_api;
+use nav_lambda_core::{RigorParams, State7D};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const ADDR: &str = "127.0.0.1:8090";
+const MAX_BODY: usize = 1024 * 1024;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind(ADDR).await?;
+    println!("POST http://{}/verify", ADDR);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream).await {
+                eprintln!("connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    // Read the head, then the rest of the body by Content-Length
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let content_length = head
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or(0)
+        .min(MAX_BODY);
+    while buf.len() < head_end + content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let (status, body) = if head.starts_with("POST /verify ") {
+        let end = buf.len().min(head_end + content_length);
+        verify(&buf[head_end..end]).await
+    } else {
+        ("404 Not Found", json!({ "error": "POST /verify" }))
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+/// `{"positions": [[x, y, z], ...], "obstacles": [[x, y, z], ...]}` ->
+/// one verdict per position
+async fn verify(body: &[u8]) -> (&'static str, Value) {
+    let Ok(request) = serde_json::from_slice::<Value>(body) else {
+        return ("400 Bad Request", json!({ "error": "body must be JSON" }));
+    };
+    let points = |key: &str| -> Option<Vec<[f32; 3]>> {
+        request[key]
+            .as_array()?
+            .iter()
+            .map(|p| {
+                let p = p.as_array().filter(|p| p.len() == 3)?;
+                Some([p[0].as_f64()? as f32, p[1].as_f64()? as f32, p[2].as_f64()? as f32])
+            })
+            .collect()
+    };
+    let (Some(positions), Some(obstacles)) = (points("positions"), points("obstacles")) else {
+        return ("400 Bad Request", json!({ "error": "positions and obstacles must be [[x, y, z], ...]" }));
+    };
+
+    let states = positions
+        .into_iter()
+        .map(|position| State7D {
+            position,
+            velocity: [0.0; 3],
+            heading: 0.0,
+            timestamp: 0,
+            certainty: 0.9,
+            fatigue: 0.9,
+        })
+        .collect();
+    let params = RigorParams { min_margin: 0.5, ..Default::default() };
+
+    // Runs on tokio's blocking pool; this task just awaits the owned reports
+    match async_api::verify_batch(states, params, obstacles).await {
+        Ok(reports) => {
+            let verdicts: Vec<Value> = reports
+                .iter()
+                .map(|r| {
+                    json!({
+                        "is_safe": r.is_safe,
+                        "p_score": r.p_score,
+                        "margin": r.margin,
+                        "breach_reason": r.breach_reason,
+                        "evidence_hash": r.evidence_hash,
+                    })
+                })
+                .collect();
+            ("200 OK", json!({ "results": verdicts }))
+        }
+        Err(status) => ("422 Unprocessable Entity", json!({ "error": status.to_string() })),
+    }
+}
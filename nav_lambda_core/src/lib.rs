@@ -5,10 +5,13 @@
 //! Exposes C-friendly FFI for Unity integration.
 
 use std::ffi::{CStr, CString};
-use std::ffi::CString;
 use std::os::raw::{c_char, c_float, c_int, c_ulonglong, c_void};
 use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256, Sha512};
 
 // --- 7D State Space (The Ironclad Math) ---
 #[repr(C)]
@@ -16,12 +19,147 @@ use std::sync::atomic::{AtomicBool, Ordering};
 pub struct State7D {
     pub position: [c_float; 3], // x, y, z
     pub velocity: [c_float; 3],  // vx, vy, vz
-    pub heading: c_float,        // Theta
+    pub heading: c_float,        // Theta (radians, 0 = facing straight up the slope)
     pub timestamp: c_ulonglong,
     pub certainty: c_float,      // 'i' (Model Confidence)
     pub fatigue: c_float,        // 'c' (Consciousness/Fatigue)
 }
 
+impl State7D {
+    /// True when every float field is neither NaN nor infinite
+    fn is_finite(&self) -> bool {
+        self.position.iter().chain(self.velocity.iter()).all(|v| v.is_finite())
+            && self.heading.is_finite()
+            && self.certainty.is_finite()
+            && self.fatigue.is_finite()
+    }
+
+    /// The state a fraction `t` of the way from `self` to `other`
+    ///
+    /// Position, velocity, certainty, fatigue and timestamp (rounded to the
+    /// nearest tick) are linear. Heading turns the short way round, so
+    /// 3.0 -> -3.0 passes through ±π rather than 0, and the result is wrapped
+    /// to `[-π, π)`; exactly opposite headings turn through -π. `t` is
+    /// clamped to `[0, 1]`, so this never extrapolates.
+    pub fn lerp(&self, other: &State7D, t: c_float) -> State7D {
+        use std::f32::consts::{PI, TAU};
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: c_float, b: c_float| a + (b - a) * t;
+        let delta = (other.heading - self.heading + PI).rem_euclid(TAU) - PI;
+        let ticks = (other.timestamp as i128 - self.timestamp as i128) as f64 * t as f64;
+
+        State7D {
+            position: [0, 1, 2].map(|i| mix(self.position[i], other.position[i])),
+            velocity: [0, 1, 2].map(|i| mix(self.velocity[i], other.velocity[i])),
+            heading: (self.heading + delta * t + PI).rem_euclid(TAU) - PI,
+            timestamp: (self.timestamp as i128 + ticks.round() as i128) as c_ulonglong,
+            certainty: mix(self.certainty, other.certainty),
+            fatigue: mix(self.fatigue, other.fatigue),
+        }
+    }
+}
+
+/// Interpolate between two samples, e.g. to verify the state at a control
+/// tick that falls between two sensor readings (see `State7D::lerp`)
+///
+/// `t` is clamped to `[0, 1]` (0 gives `a`, 1 gives `b`, up to heading
+/// wrapping). Returns `NullPointer` for a null argument and `NonFinite` when
+/// `t` or any float in `a` or `b` is NaN/infinite; `out` is untouched on
+/// error.
+///
+/// # Safety
+///
+/// `a`, `b` and `out` must be null or valid pointers; `out` may alias `a`
+/// or `b`.
+#[no_mangle]
+pub unsafe extern "C" fn state7d_lerp(
+    a: *const State7D,
+    b: *const State7D,
+    t: c_float,
+    out: *mut State7D,
+) -> NavStatus {
+    with_last_error("state7d_lerp", || {
+        if a.is_null() || b.is_null() || out.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let (a, b) = (*a, *b);
+        if !a.is_finite() || !b.is_finite() || !t.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        *out = a.lerp(&b, t);
+        NavStatus::Ok
+    })
+}
+
+// --- Quaternion State (aerial / articulated agents) ---
+/// `State7D` with a full 3D orientation in place of the scalar heading
+///
+/// Quaternion convention: Hamilton, stored `[w, x, y, z]` (scalar first),
+/// rotating body coordinates into world coordinates. The world is Y-up, as
+/// everywhere in the crate, and the body's forward axis is +X. It need not
+/// be unit length (it is normalized before use) but must not be zero.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct State7DQuat {
+    pub position: [c_float; 3],
+    pub velocity: [c_float; 3],
+    pub orientation: [c_float; 4], // [w, x, y, z]
+    pub timestamp: c_ulonglong,
+    pub certainty: c_float,
+    pub fatigue: c_float,
+}
+
+impl State7DQuat {
+    fn is_finite(&self) -> bool {
+        self.position
+            .iter()
+            .chain(self.velocity.iter())
+            .chain(self.orientation.iter())
+            .all(|v| v.is_finite())
+            && self.certainty.is_finite()
+            && self.fatigue.is_finite()
+    }
+
+    /// Equivalent `State7D` on `terrain`, or None for a zero-length quaternion
+    ///
+    /// `heading` is measured from the terrain's uphill direction in the
+    /// ground (x/z) plane, Y being up, so the derived heading is the angle
+    /// between the forward axis projected onto x/z and the uphill direction
+    /// at the agent: the heightmap gradient, or +Z where there is none (no
+    /// heightmap, whose linear fallback has no horizontal direction, or a
+    /// flat spot). A forward axis pointing straight up or down has no ground
+    /// direction and gets `π/2`, i.e. no slope contribution.
+    fn to_state7d(self, terrain: Option<&Heightmap>) -> Option<State7D> {
+        let norm = self.orientation.iter().map(|v| v * v).sum::<c_float>().sqrt();
+        if norm <= c_float::EPSILON {
+            return None;
+        }
+        let [w, x, y, z] = self.orientation.map(|v| v / norm);
+        // X and Z components of R * [1, 0, 0] (first column of the rotation matrix)
+        let forward = [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * z - w * y)];
+        let uphill = terrain
+            .map(|terrain| terrain.gradient(self.position[0], self.position[2]))
+            .filter(|g| g[0] * g[0] + g[1] * g[1] > c_float::EPSILON * c_float::EPSILON)
+            .unwrap_or([0.0, 1.0]);
+        let heading = if forward[0] * forward[0] + forward[1] * forward[1] <= c_float::EPSILON * c_float::EPSILON {
+            std::f32::consts::FRAC_PI_2
+        } else {
+            let dot = forward[0] * uphill[0] + forward[1] * uphill[1];
+            let cross = uphill[0] * forward[1] - uphill[1] * forward[0];
+            cross.atan2(dot)
+        };
+
+        Some(State7D {
+            position: self.position,
+            velocity: self.velocity,
+            heading,
+            timestamp: self.timestamp,
+            certainty: self.certainty,
+            fatigue: self.fatigue,
+        })
+    }
+}
+
 // --- Verification Result ---
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -30,8 +168,134 @@ pub struct VerificationResult {
     pub is_safe: c_int,          // bool as int (0 = false, 1 = true)
     pub margin: c_float,
     pub sigma: c_float,          // Uncertainty (from SIM2VAL)
-    pub breach_reason: *mut c_char, // String pointer (caller must free)
-    pub evidence_hash: *mut c_char, // SHA-256 hash string
+    pub breach_reason: *mut c_char, // String pointer (caller must free, see free_verification_result)
+    pub evidence_hash: *mut c_char, // "<algo>:<hex digest>" (see NAV_HASH_*)
+    pub time_to_collision: c_float, // Seconds until predicted breach (c_float::MAX if none)
+    pub closest_obstacle_index: c_int, // Obstacle that produced `margin` (-1 if none)
+    pub closest_obstacle_pos: [c_float; 3],
+    pub breach_flags: c_ulonglong, // Every active violation (BREACH_FLAG_* bits, 0 = none; see soft_cognitive_breach)
+    pub severity: c_int,           // Graduated level (SEVERITY_SAFE .. SEVERITY_CRITICAL)
+    pub terms: [c_float; 5],       // Weighted contributions, same order as RigorParams::weights; sum to p_score
+}
+
+impl Default for VerificationResult {
+    fn default() -> Self {
+        VerificationResult {
+            p_score: 0.0,
+            is_safe: 0,
+            margin: 0.0,
+            sigma: 0.0,
+            breach_reason: ptr::null_mut(),
+            evidence_hash: ptr::null_mut(),
+            time_to_collision: c_float::MAX,
+            closest_obstacle_index: -1,
+            closest_obstacle_pos: [0.0; 3],
+            breach_flags: 0,
+            severity: SEVERITY_SAFE,
+            terms: [0.0; 5],
+        }
+    }
+}
+
+// --- Breach Flags ---
+// Bit positions for `VerificationResult::breach_flags`. Exported as symbols so
+// the C# side can read them instead of hard-coding the values.
+#[no_mangle]
+pub static BREACH_FLAG_VNC_VIOLATION: c_ulonglong = 1 << 0;
+#[no_mangle]
+pub static BREACH_FLAG_PREDICTED_COLLISION: c_ulonglong = 1 << 1;
+#[no_mangle]
+pub static BREACH_FLAG_FATIGUE: c_ulonglong = 1 << 2;
+#[no_mangle]
+pub static BREACH_FLAG_LOW_CERTAINTY: c_ulonglong = 1 << 3;
+#[no_mangle]
+pub static BREACH_FLAG_DYNAMIC_COLLISION: c_ulonglong = 1 << 4;
+#[no_mangle]
+pub static BREACH_FLAG_STOPPING_DISTANCE: c_ulonglong = 1 << 5;
+//
+// Flags always report every violation. Normally any flag makes the verdict
+// unsafe (`is_safe = 0`, logged, callback fired). With
+// `RigorParams::soft_cognitive_breach` set, FATIGUE and LOW_CERTAINTY are
+// warnings: they stay in `breach_flags` (and `breach_reason`) but only the
+// obstacle flags clear `is_safe`, so `breach_flags != 0` with
+// `is_safe = 1` means "degraded but operating".
+
+// --- Severity Levels ---
+// Values of `VerificationResult::severity`, ordered so callers can compare
+// with `>=`. Any unsafe breach is CRITICAL; otherwise the level comes from
+// the closest approach to a limit, measured as `value / limit` (1.0 = at the
+// limit, so a soft cognitive breach is WARNING):
+//   clearance:  (dist - radius) / min_margin   (skipped when min_margin <= 0)
+//   certainty:  certainty / CERTAINTY_LIMIT
+//   fatigue:    fatigue / FATIGUE_LIMIT
+// A ratio below SEVERITY_WARNING_RATIO is WARNING, below
+// SEVERITY_CAUTION_RATIO is CAUTION, otherwise SAFE.
+#[no_mangle]
+pub static SEVERITY_SAFE: c_int = 0;
+#[no_mangle]
+pub static SEVERITY_CAUTION: c_int = 1;
+#[no_mangle]
+pub static SEVERITY_WARNING: c_int = 2;
+#[no_mangle]
+pub static SEVERITY_CRITICAL: c_int = 3;
+
+/// Ratio to a limit below which the level is WARNING (within 25% of the limit)
+pub const SEVERITY_WARNING_RATIO: c_float = 1.25;
+/// Ratio to a limit below which the level is CAUTION (within 50% of the limit)
+pub const SEVERITY_CAUTION_RATIO: c_float = 1.5;
+
+/// Certainty below this raises BREACH_FLAG_LOW_CERTAINTY
+pub const CERTAINTY_LIMIT: c_float = 0.5;
+/// Fatigue (alertness) below this raises BREACH_FLAG_FATIGUE
+pub const FATIGUE_LIMIT: c_float = 0.3;
+
+/// Graduated severity for a verdict; `margin` is the clearance beyond `min_margin`
+fn severity_for(
+    breach_flags: c_ulonglong,
+    margin: c_float,
+    certainty: c_float,
+    fatigue: c_float,
+    params: &RigorParams,
+) -> c_int {
+    if params.hard_breach_flags(breach_flags) != 0 {
+        return SEVERITY_CRITICAL;
+    }
+
+    let mut closest = (certainty / CERTAINTY_LIMIT).min(fatigue / FATIGUE_LIMIT);
+    if params.min_margin > 0.0 && margin < c_float::MAX {
+        closest = closest.min((margin + params.min_margin) / params.min_margin);
+    }
+
+    if closest < SEVERITY_WARNING_RATIO {
+        SEVERITY_WARNING
+    } else if closest < SEVERITY_CAUTION_RATIO {
+        SEVERITY_CAUTION
+    } else {
+        SEVERITY_SAFE
+    }
+}
+
+/// Highest-priority human-readable reason for a set of breach flags
+///
+/// Precedence (highest first):
+/// VNC_VIOLATION > PREDICTED_COLLISION > DYNAMIC_COLLISION >
+/// INSUFFICIENT_STOPPING_DISTANCE > FATIGUE > LOW_CERTAINTY
+fn breach_reason_for(flags: c_ulonglong) -> &'static str {
+    if flags & BREACH_FLAG_VNC_VIOLATION != 0 {
+        "VNC_VIOLATION"
+    } else if flags & BREACH_FLAG_PREDICTED_COLLISION != 0 {
+        "PREDICTED_COLLISION"
+    } else if flags & BREACH_FLAG_DYNAMIC_COLLISION != 0 {
+        "DYNAMIC_COLLISION"
+    } else if flags & BREACH_FLAG_STOPPING_DISTANCE != 0 {
+        "INSUFFICIENT_STOPPING_DISTANCE"
+    } else if flags & BREACH_FLAG_FATIGUE != 0 {
+        "FATIGUE"
+    } else if flags & BREACH_FLAG_LOW_CERTAINTY != 0 {
+        "LOW_CERTAINTY"
+    } else {
+        "SAFE"
+    }
 }
 
 // --- Ironclad Equation Parameters ---
@@ -40,259 +304,6644 @@ pub struct VerificationResult {
 pub struct RigorParams {
     pub alpha: c_float,      // Class-K (Rigorousness)
     pub min_margin: c_float,
+    pub weights: [c_float; 5], // Term weights: [pos_norm, t_phase, g_gradient, i_intent, c_consciousness]
+    pub horizon_secs: c_float, // Collision lookahead (0 = static check only)
+    pub planar_mode: c_int,    // Non-zero: ignore Z in all distances, zero the gradient
+    pub reference_origin: [c_float; 3], // Point pos_norm is measured from (world origin by default)
+    pub clamp_cognitive: c_int, // Non-zero: saturate certainty/fatigue to [0, 1]; zero: reject with OutOfRange
+    pub time_period: c_ulonglong, // t_phase period in timestamp ticks (0 = DEFAULT_TIME_PERIOD)
+    pub speed_confidence_decay: c_float, // Certainty discount per m/s of speed (0 = none)
+    pub soft_cognitive_breach: c_int, // Non-zero: fatigue/certainty breaches are flagged but keep is_safe = 1
+    pub max_decel: c_float,    // Braking deceleration (m/s^2); clearance must cover the stopping distance (0 = off)
+    pub distance_metric: c_int, // NAV_METRIC_*: norm for obstacle clearance and pos_norm (default Euclidean)
+    pub hash_algo: c_int,       // NAV_HASH_*: evidence hash algorithm (default SHA-256)
 }
 
-// Global state for robustness checking
-static RUST_CORE_INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// t_phase period used when `RigorParams::time_period` is 0
+pub const DEFAULT_TIME_PERIOD: c_ulonglong = 10_000;
 
-/// Initialize the Rust core library
-/// Returns 1 if successful, 0 if failed
-#[no_mangle]
-pub extern "C" fn rust_core_init() -> c_int {
-    RUST_CORE_INITIALIZED.store(true, Ordering::Release);
-    1
-}
+// --- Distance Metrics (RigorParams::distance_metric) ---
+// Each measures the offset (dx, dy, dz) from the agent to an obstacle or to
+// `reference_origin`; dz is 0 in planar mode. Collision prediction and speed
+// (for the certainty discount and stopping distance) stay Euclidean, since
+// they model motion rather than grid clearance. Any other value is treated
+// as Euclidean and rejected by `validate_rigor_params`.
+/// L2: `sqrt(dx^2 + dy^2 + dz^2)`
+pub const NAV_METRIC_EUCLIDEAN: c_int = 0;
+/// L1: `|dx| + |dy| + |dz|` (4-connected grid steps)
+pub const NAV_METRIC_MANHATTAN: c_int = 1;
+/// L-infinity: `max(|dx|, |dy|, |dz|)` (8-connected grid steps)
+pub const NAV_METRIC_CHEBYSHEV: c_int = 2;
 
-/// Check system robustness
-/// Returns 1 if robust, 0 if failed
-#[no_mangle]
-pub extern "C" fn check_system_robustness() -> c_int {
-    if RUST_CORE_INITIALIZED.load(Ordering::Acquire) {
-        1
-    } else {
-        0
+// --- Evidence Hash Algorithms (RigorParams::hash_algo) ---
+// The evidence hash string is `<name>:<lowercase hex digest>`, so a verifier
+// knows which algorithm to recompute with. Any other value is treated as
+// SHA-256 and rejected by `validate_rigor_params`.
+/// `sha256:` + 64 hex chars (default)
+pub const NAV_HASH_SHA256: c_int = 0;
+/// `sha512:` + 128 hex chars
+pub const NAV_HASH_SHA512: c_int = 1;
+/// `blake3:` + 64 hex chars (32-byte BLAKE3 output)
+pub const NAV_HASH_BLAKE3: c_int = 2;
+/// Longest evidence hash string (`sha512:` + 128 hex chars), excluding the NUL
+pub const NAV_EVIDENCE_HASH_MAX_LEN: usize = 135;
+
+impl Default for RigorParams {
+    fn default() -> Self {
+        RigorParams {
+            alpha: 1.0,
+            min_margin: 0.0,
+            weights: [1.0; 5], // All 1.0 reproduces the unweighted sum
+            horizon_secs: 0.0,
+            planar_mode: 0,
+            reference_origin: [0.0; 3],
+            clamp_cognitive: 0, // Reject, so bad data surfaces loudly
+            time_period: DEFAULT_TIME_PERIOD,
+            speed_confidence_decay: 0.0,
+            soft_cognitive_breach: 0, // Strict: any breach is unsafe
+            max_decel: 0.0,           // No stopping-distance requirement
+            distance_metric: NAV_METRIC_EUCLIDEAN,
+            hash_algo: NAV_HASH_SHA256,
+        }
     }
 }
 
-/// Validate Unity memory allocation (simulated)
-/// In production, this would check heap integrity
-#[no_mangle]
-pub extern "C" fn validate_unity_alloc(ptr: *mut c_void, size: usize) -> c_int {
-    if ptr.is_null() {
-        return 0;
+impl RigorParams {
+    /// True when every float field is neither NaN nor infinite
+    fn is_finite(&self) -> bool {
+        self.alpha.is_finite()
+            && self.min_margin.is_finite()
+            && self.weights.iter().all(|w| w.is_finite())
+            && self.horizon_secs.is_finite()
+            && self.reference_origin.iter().all(|v| v.is_finite())
+            && self.speed_confidence_decay.is_finite()
+            && self.max_decel.is_finite()
     }
-    if size == 0 {
-        return 0;
+
+    /// Length of the offset `d` under `distance_metric`
+    fn metric_norm(&self, d: [c_float; 3]) -> c_float {
+        match self.distance_metric {
+            NAV_METRIC_MANHATTAN => d[0].abs() + d[1].abs() + d[2].abs(),
+            NAV_METRIC_CHEBYSHEV => d[0].abs().max(d[1].abs()).max(d[2].abs()),
+            _ => (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt(),
+        }
     }
-    // In production, add actual memory validation
-    // For now, just check pointer is not null and size is reasonable
-    if size > 1024 * 1024 * 1024 {
-        // Reject allocations > 1GB (suspicious)
-        return 0;
+
+    /// Stopping distance at `speed` (see `stopping_distance`)
+    fn stopping_distance(&self, speed: c_float) -> c_float {
+        stopping_distance(speed, self.max_decel)
+    }
+
+    /// The breach flags that make a verdict unsafe: all of them, or with
+    /// `soft_cognitive_breach` only the physical (obstacle) ones
+    fn hard_breach_flags(&self, breach_flags: c_ulonglong) -> c_ulonglong {
+        if self.soft_cognitive_breach != 0 {
+            breach_flags & !(BREACH_FLAG_FATIGUE | BREACH_FLAG_LOW_CERTAINTY)
+        } else {
+            breach_flags
+        }
+    }
+
+    /// Certainty after the speed discount: `certainty * exp(-decay * |v|)`
+    ///
+    /// Sensor models degrade with speed, so a fast agent trusts its model
+    /// less. A zero (or negative) decay returns `certainty` unchanged.
+    fn effective_certainty(&self, certainty: c_float, velocity: [c_float; 3]) -> c_float {
+        if self.speed_confidence_decay <= 0.0 {
+            return certainty;
+        }
+        let speed = (velocity[0].powi(2) + velocity[1].powi(2) + velocity[2].powi(2)).sqrt();
+        certainty * (-self.speed_confidence_decay * speed).exp()
+    }
+
+    /// Apply the certainty/fatigue range policy to a (finite) state
+    ///
+    /// Both are probabilities in `[0, 1]`. Out-of-range values either fail
+    /// with `OutOfRange` (default) or, with `clamp_cognitive`, saturate to
+    /// the nearest bound before any breach check sees them.
+    fn check_cognitive(&self, state: &mut State7D) -> Result<(), NavStatus> {
+        let in_range = |v: c_float| (0.0..=1.0).contains(&v);
+        if in_range(state.certainty) && in_range(state.fatigue) {
+            Ok(())
+        } else if self.clamp_cognitive != 0 {
+            state.certainty = state.certainty.clamp(0.0, 1.0);
+            state.fatigue = state.fatigue.clamp(0.0, 1.0);
+            Ok(())
+        } else {
+            Err(NavStatus::OutOfRange)
+        }
     }
-    1
 }
 
-/// Calculate P-score using Ironclad 7D Math
-/// 
+/// Check RigorParams for sane values before the first verification
+///
+/// Valid ranges (NaN and infinity are invalid everywhere):
+/// - `alpha`: > 0
+/// - `min_margin`: >= 0
+/// - `weights`: each >= 0
+/// - `horizon_secs`: >= 0 (0 disables the lookahead)
+/// - `reference_origin`: any finite point
+/// - `speed_confidence_decay`: >= 0 (0 disables the discount)
+/// - `max_decel`: >= 0 (0 disables the stopping-distance check)
+/// - `distance_metric`: one of the `NAV_METRIC_*` constants
+/// - `hash_algo`: one of the `NAV_HASH_*` constants
+/// - `planar_mode`, `clamp_cognitive`, `soft_cognitive_breach`: any value
+///   (zero / non-zero)
+/// - `time_period`: any value (0 selects `DEFAULT_TIME_PERIOD`)
+///
+/// Returns `Ok`, `NullPointer`, or the `Invalid*` code of the first bad
+/// field in declaration order. The verification functions don't call this:
+/// they accept any finite params, so run it once when loading a
+/// configuration to surface mistakes early.
+///
 /// # Safety
-/// 
-/// This function is unsafe because it dereferences raw pointers.
-/// Caller must ensure:
-/// - `obstacles` points to a valid array of at least `obstacle_count * 3` floats
-/// - `result` is a valid pointer to a VerificationResult struct
+///
+/// `params` must be null or a valid pointer.
 #[no_mangle]
-pub unsafe extern "C" fn calculate_p_score(
-    state: *const State7D,
-    params: *const RigorParams,
-    obstacles: *const c_float,
-    obstacle_count: usize,
-    result: *mut VerificationResult,
-) -> c_int {
-    // Validate inputs
-    if state.is_null() || params.is_null() || result.is_null() {
-        return 0; // Failure
-    }
+pub unsafe extern "C" fn validate_rigor_params(params: *const RigorParams) -> NavStatus {
+    with_last_error("validate_rigor_params", || {
+        if params.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let params = &*params;
+        let non_negative = |v: c_float| v.is_finite() && v >= 0.0;
 
-    let state = *state;
-    let params = *params;
+        if !(params.alpha.is_finite() && params.alpha > 0.0) {
+            NavStatus::InvalidAlpha
+        } else if !non_negative(params.min_margin) {
+            NavStatus::InvalidMinMargin
+        } else if !params.weights.iter().all(|&w| non_negative(w)) {
+            NavStatus::InvalidWeights
+        } else if !non_negative(params.horizon_secs) {
+            NavStatus::InvalidHorizon
+        } else if !params.reference_origin.iter().all(|v| v.is_finite()) {
+            NavStatus::InvalidReferenceOrigin
+        } else if !non_negative(params.speed_confidence_decay) {
+            NavStatus::InvalidSpeedDecay
+        } else if !non_negative(params.max_decel) {
+            NavStatus::InvalidMaxDecel
+        } else if !matches!(params.distance_metric, NAV_METRIC_EUCLIDEAN | NAV_METRIC_MANHATTAN | NAV_METRIC_CHEBYSHEV) {
+            NavStatus::InvalidDistanceMetric
+        } else if !matches!(params.hash_algo, NAV_HASH_SHA256 | NAV_HASH_SHA512 | NAV_HASH_BLAKE3) {
+            NavStatus::InvalidHashAlgo
+        } else {
+            NavStatus::Ok
+        }
+    })
+}
 
-    let mut p_score: c_float = 0.0;
+// --- Version & ABI ---
+// Keep in sync with Cargo.toml (checked by test_version_matches_manifest)
+pub const NAV_CORE_VERSION_MAJOR: u32 = 0;
+pub const NAV_CORE_VERSION_MINOR: u32 = 2;
+pub const NAV_CORE_VERSION_PATCH: u32 = 0;
 
-    // 1. Calculate "x" (Position Norm) - Euclidean distance to origin
-    let pos_norm = (state.position[0].powi(2) 
-                  + state.position[1].powi(2) 
-                  + state.position[2].powi(2)).sqrt();
+/// Library version packed as `(major << 16) | (minor << 8) | patch`
+/// e.g. 0.2.0 -> 0x000200. Minor and patch must stay below 256.
+#[no_mangle]
+pub extern "C" fn nav_core_version() -> u32 {
+    (NAV_CORE_VERSION_MAJOR << 16) | (NAV_CORE_VERSION_MINOR << 8) | NAV_CORE_VERSION_PATCH
+}
 
-    // 2. Calculate "t" (Time Phase) - Sine wave system sync (0.0 to 1.0)
-    let t_phase = ((state.timestamp % 10000) as c_float) / 10000.0;
-    
-    // 3. Calculate "g" (Gradient) - Slope simulation
-    let g_gradient = state.position[1] * 0.1;
+/// Describe a repr(C) struct as `Name(size,align){field@offset;...}`
+macro_rules! layout_descriptor {
+    ($ty:ident { $($field:ident),* $(,)? }) => {{
+        let mut desc = format!(
+            "{}({},{}){{",
+            stringify!($ty),
+            std::mem::size_of::<$ty>(),
+            std::mem::align_of::<$ty>()
+        );
+        $(
+            desc.push_str(&format!("{}@{};", stringify!($field), std::mem::offset_of!($ty, $field)));
+        )*
+        desc.push('}');
+        desc
+    }};
+}
 
-    // 4. Calculate "i" (Intent) - Model Confidence
-    let i_intent = state.certainty;
+/// 64-bit FNV-1a hash of the repr(C) layouts shared with the managed side
+///
+/// Covers the size, alignment and every field offset of State7D,
+/// State7DQuat, VerificationResult, BreachRecord, VerificationResultFixed,
+/// Obstacle and RigorParams. Any field added, removed, reordered or
+/// retyped changes the hash, so the C# wrapper can compare it against the
+/// value it was built for and refuse to run on mismatch.
+#[no_mangle]
+pub extern "C" fn nav_core_abi_hash() -> u64 {
+    let layout = [
+        layout_descriptor!(State7D {
+            position, velocity, heading, timestamp, certainty, fatigue,
+        }),
+        layout_descriptor!(State7DQuat {
+            position, velocity, orientation, timestamp, certainty, fatigue,
+        }),
+        layout_descriptor!(VerificationResult {
+            p_score, is_safe, margin, sigma, breach_reason, evidence_hash,
+            time_to_collision, closest_obstacle_index, closest_obstacle_pos, breach_flags,
+            severity, terms,
+        }),
+        layout_descriptor!(BreachRecord {
+            timestamp, breach_flags, p_score, margin, evidence_hash, sigma,
+        }),
+        layout_descriptor!(VerificationResultFixed {
+            p_score, is_safe, margin, time_to_collision, closest_obstacle_index, breach_flags,
+        }),
+        layout_descriptor!(Obstacle {
+            pos, radius,
+        }),
+        layout_descriptor!(RigorParams {
+            alpha, min_margin, weights, horizon_secs, planar_mode, reference_origin,
+            clamp_cognitive, time_period, speed_confidence_decay, soft_cognitive_breach, max_decel,
+            distance_metric, hash_algo,
+        }),
+    ]
+    .concat();
 
-    // 5. Calculate "c" (Consciousness/Fatigue)
-    let c_consciousness = state.fatigue;
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    layout
+        .bytes()
+        .fold(FNV_OFFSET, |hash, b| (hash ^ b as u64).wrapping_mul(FNV_PRIME))
+}
 
-    // 6. Safety Check (The "Ironclad" Constraint)
-    let mut constraint_violated = false;
-    let mut min_margin_dist = c_float::MAX;
-    let mut breach_reason_str = CString::new("SAFE").unwrap();
-
-    if !obstacles.is_null() && obstacle_count > 0 {
-        for i in 0..obstacle_count {
-            let obs_idx = i * 3;
-            let obs_x = *obstacles.add(obs_idx);
-            let obs_y = *obstacles.add(obs_idx + 1);
-            let obs_z = *obstacles.add(obs_idx + 2);
-
-            let dx = state.position[0] - obs_x;
-            let dy = state.position[1] - obs_y;
-            let dz = state.position[2] - obs_z;
-            
-            let dist_sq = dx * dx + dy * dy + dz * dz;
-            let dist = dist_sq.sqrt();
-
-            let margin = dist - params.min_margin;
-            if margin < min_margin_dist {
-                min_margin_dist = margin;
-            }
-            
-            // Check Breach (If Margin < 0)
-            if margin < 0.0 {
-                constraint_violated = true;
-                breach_reason_str = CString::new("VNC_VIOLATION").unwrap();
-                break;
-            }
-        }
-    }
+// --- Status Codes ---
+/// Status returned by the fallible FFI functions
+///
+/// Migration: these functions used to return `1` on success and `0` on any
+/// failure. Callers checking `== 1` must now check `== NavStatus::Ok` (0);
+/// any non-zero value is an error whose text is `nav_status_message(code)`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavStatus {
+    Ok = 0,
+    NullPointer = 1,         // A required pointer argument was null
+    EmptyInput = 2,          // A required array or size was zero
+    NonFinite = 3,           // A float input was NaN or infinite
+    Uninitialized = 4,       // Context is null or not initialized
+    InsufficientSamples = 5, // Too few samples for the estimator (e.g. n - 1 == 0)
+    OutOfRange = 6,          // A value is outside its accepted range
+    UnknownPointer = 7,      // Pointer is not a registered allocation
+    SizeMismatch = 8,        // Size differs from the registered allocation
+    InvalidAlpha = 9,        // RigorParams::alpha is not a positive finite number
+    InvalidMinMargin = 10,   // RigorParams::min_margin is negative or not finite
+    InvalidWeights = 11,     // A RigorParams::weights entry is negative or not finite
+    InvalidHorizon = 12,     // RigorParams::horizon_secs is negative or not finite
+    InvalidReferenceOrigin = 13, // A RigorParams::reference_origin coordinate is not finite
+    InvalidSpeedDecay = 14,  // RigorParams::speed_confidence_decay is negative or not finite
+    InvalidMaxDecel = 15,    // RigorParams::max_decel is negative or not finite
+    IoError = 16,            // A file could not be opened
+    InvalidDistanceMetric = 17, // RigorParams::distance_metric is not a NAV_METRIC_* value
+    InvalidHashAlgo = 18,    // RigorParams::hash_algo is not a NAV_HASH_* value
+    TooManyObstacles = 19,   // obstacle_count exceeds the context's max_obstacles
+    StateSizeMismatch = 20,  // Caller's sizeof(State7D) differs from the library's
+    ResultSizeMismatch = 21, // Caller's sizeof(VerificationResult) differs from the library's
+    ParamsSizeMismatch = 22, // Caller's sizeof(RigorParams) differs from the library's
+}
 
-    // Check fatigue breach
-    if state.fatigue < 0.3 {
-        constraint_violated = true;
-        breach_reason_str = CString::new("FATIGUE").unwrap();
+/// Static, human-readable description of a status code
+/// The returned pointer is never null and must NOT be freed
+#[no_mangle]
+pub extern "C" fn nav_status_message(code: c_int) -> *const c_char {
+    let msg: &'static [u8] = match code {
+        0 => b"OK\0",
+        1 => b"Null pointer argument\0",
+        2 => b"Empty input\0",
+        3 => b"Non-finite float input (NaN or infinity)\0",
+        4 => b"Context not initialized\0",
+        5 => b"Insufficient samples\0",
+        6 => b"Value out of range\0",
+        7 => b"Pointer is not a registered allocation\0",
+        8 => b"Size does not match the registered allocation\0",
+        9 => b"RigorParams.alpha must be positive and finite\0",
+        10 => b"RigorParams.min_margin must be non-negative and finite\0",
+        11 => b"RigorParams.weights must be non-negative and finite\0",
+        12 => b"RigorParams.horizon_secs must be non-negative and finite\0",
+        13 => b"RigorParams.reference_origin must be finite\0",
+        14 => b"RigorParams.speed_confidence_decay must be non-negative and finite\0",
+        15 => b"RigorParams.max_decel must be non-negative and finite\0",
+        16 => b"File could not be opened\0",
+        17 => b"RigorParams.distance_metric must be a NAV_METRIC_* value\0",
+        18 => b"RigorParams.hash_algo must be a NAV_HASH_* value\0",
+        19 => b"Obstacle count exceeds the context's max_obstacles\0",
+        20 => b"State7D size differs between caller and library\0",
+        21 => b"VerificationResult size differs between caller and library\0",
+        22 => b"RigorParams size differs between caller and library\0",
+        _ => b"Unknown status code\0",
+    };
+    msg.as_ptr() as *const c_char
+}
+
+impl std::fmt::Display for NavStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = unsafe { CStr::from_ptr(nav_status_message(*self as c_int)) };
+        f.write_str(&msg.to_string_lossy())
     }
+}
 
-    // Check certainty breach
-    if state.certainty < 0.5 {
-        constraint_violated = true;
-        if !constraint_violated {
-            breach_reason_str = CString::new("LOW_CERTAINTY").unwrap();
-        }
+impl std::error::Error for NavStatus {}
+
+thread_local! {
+    // Message of the last non-Ok status returned on this thread (see `nav_last_error`)
+    static LAST_ERROR: std::cell::RefCell<CString> = std::cell::RefCell::new(CString::default());
+}
+
+/// Run the body of a `NavStatus`-returning FFI function, recording a non-Ok
+/// result as the thread's last error (`"<function>: <status message>"`)
+fn with_last_error(function: &str, body: impl FnOnce() -> NavStatus) -> NavStatus {
+    let status = body();
+    if status != NavStatus::Ok {
+        let message = CString::new(format!("{}: {}", function, status)).unwrap_or_default();
+        LAST_ERROR.with(|last| *last.borrow_mut() = message);
     }
+    status
+}
 
-    // --- SUM IT UP (The Formula: P = x + y + z + t + g + i + c) ---
-    // Note: x, y, z are combined into pos_norm
-    p_score = pos_norm + t_phase + g_gradient + i_intent + c_consciousness;
+/// Human-readable description of the last failed call on the calling thread
+///
+/// errno-style companion to the status codes: every FFI function returning
+/// a `NavStatus` records a non-Ok result here as `"<function>: <message>"`,
+/// e.g. `"calculate_p_score: Null pointer argument"`, so a C# caller can
+/// log why a call failed without its own code-to-text table.
+///
+/// Thread-local: each thread sees only the failures of calls it made
+/// itself, so read it on the same thread, right after the call that
+/// failed. As with errno, successful calls don't clear it (and may even
+/// set it, when they recover from a failed inner call), so it is only
+/// meaningful after a non-Ok return. Functions signalling errors by other
+/// means (NaN, null, sentinel counts) don't set it.
+///
+/// The returned pointer is never null (an empty string before the first
+/// failure) and must NOT be freed. It stays valid until the next FFI call
+/// on the same thread; copy the string out before making another call.
+#[no_mangle]
+pub extern "C" fn nav_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
 
-    // Create result
-    let breach_reason_ptr = breach_reason_str.into_raw();
-    let evidence_hash_str = CString::new("PENDING_HASH").unwrap();
-    let evidence_hash_ptr = evidence_hash_str.into_raw();
+// --- Session Context ---
+/// Opaque per-session handle
+///
+/// Each Unity subsystem or test harness can own its own context so init
+/// state and session parameters don't collide. Create with
+/// `nav_context_create`, free with `nav_context_destroy`.
+pub struct NavContext {
+    initialized: AtomicBool,
+    session: Mutex<Session>,
+}
 
-    *result = VerificationResult {
-        p_score,
-        is_safe: if constraint_violated { 0 } else { 1 },
-        margin: min_margin_dist,
-        sigma: 0.0, // Would be filled by SIM2VAL
-        breach_reason: breach_reason_ptr,
-        evidence_hash: evidence_hash_ptr,
-    };
+/// Mutable per-session state guarded by the context mutex
+struct Session {
+    params: Option<RigorParams>, // None until nav_context_set_params
+    ema_sigma: Option<c_float>,  // Smoothed sigma; None until the first sim2val_ema_update
+    rng: SplitMix64,             // Sampling RNG; reseed with nav_set_rng_seed
+    breach_log: BreachLog,       // Most recent breaches, oldest first
+    heightmap: Option<Arc<Heightmap>>, // Terrain for the gradient term; None uses the linear approximation
+    hysteresis_band: c_float,    // Extra clearance needed to leave a margin breach (0 = off)
+    latched_breach: bool,        // Last verdict in this context had a margin breach
+    max_obstacles: usize,        // Obstacle counts above this are rejected unread (TooManyObstacles)
+}
+
+/// Seed every context's RNG starts from
+///
+/// Fixed rather than drawn from OS entropy, so sampling-based results are
+/// reproducible by default (tests and certification replays alike).
+pub const NAV_DEFAULT_RNG_SEED: u64 = 0x4E41_5641_2D41_4921;
+
+/// SplitMix64 PRNG: tiny, fast, and fully determined by its seed
+#[derive(Debug, Clone, Copy)]
+struct SplitMix64(u64);
 
-    1 // Success
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in `0..n` via multiply-shift (bias is negligible for
+    /// sample-sized `n`)
+    fn next_index(&mut self, n: usize) -> usize {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as usize
+    }
 }
 
-/// Free C string allocated by Rust
-/// Caller must call this to prevent memory leaks
-#[no_mangle]
-pub unsafe extern "C" fn free_c_string(ptr: *mut c_char) {
-    if !ptr.is_null() {
-        let _ = CString::from_raw(ptr);
+impl Session {
+    const fn new() -> Self {
+        Session {
+            params: None,
+            ema_sigma: None,
+            rng: SplitMix64(NAV_DEFAULT_RNG_SEED),
+            breach_log: BreachLog::new(),
+            heightmap: None,
+            hysteresis_band: 0.0,
+            latched_breach: false,
+            max_obstacles: NAV_DEFAULT_MAX_OBSTACLES,
+        }
     }
 }
 
-/// Calculate SIM2VAL++ uncertainty estimate
-/// 
-/// # Safety
-/// 
-/// This function is unsafe because it dereferences raw pointers.
-#[no_mangle]
-pub unsafe extern "C" fn calculate_sim2val_uncertainty(
-    control_variates: *const c_float,
-    variate_count: usize,
-    result_sigma: *mut c_float,
-) -> c_int {
-    if control_variates.is_null() || result_sigma.is_null() || variate_count == 0 {
-        return 0;
+impl NavContext {
+    const fn new() -> Self {
+        NavContext {
+            initialized: AtomicBool::new(false),
+            session: Mutex::new(Session::new()),
+        }
     }
 
-    // Calculate mean
-    let mut sum = 0.0;
-    for i in 0..variate_count {
-        sum += *control_variates.add(i);
+    /// Lock the session, recovering from a poisoned mutex (the data is plain values)
+    fn session(&self) -> std::sync::MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
     }
-    let mean = sum / variate_count as c_float;
 
-    // Calculate variance
-    let mut variance_sum = 0.0;
-    for i in 0..variate_count {
-        let diff = *control_variates.add(i) - mean;
-        variance_sum += diff * diff;
+    /// Session parameters, or the defaults if none were set
+    fn params(&self) -> RigorParams {
+        self.session().params.unwrap_or_default()
     }
-    let variance = variance_sum / variate_count as c_float;
 
-    // Standard deviation (sigma)
-    let sigma = variance.sqrt();
+    /// Obstacle count cap (see `nav_context_set_max_obstacles`)
+    fn max_obstacles(&self) -> usize {
+        self.session().max_obstacles
+    }
+
+    /// Registered terrain, shared so evaluation runs without the session lock
+    fn heightmap(&self) -> Option<Arc<Heightmap>> {
+        self.session().heightmap.clone()
+    }
 
-    *result_sigma = sigma;
-    1
+    /// Hold a margin breach until the clearance exceeds the hysteresis band
+    /// (see `nav_context_set_hysteresis`); `update` then records the verdict
+    /// as the one the next call is held against
+    fn apply_hysteresis(&self, eval: &mut Evaluation, update: bool) {
+        let mut session = self.session();
+        if session.latched_breach && eval.margin < session.hysteresis_band {
+            eval.breach_flags |= BREACH_FLAG_VNC_VIOLATION;
+            eval.is_safe = 0;
+            eval.severity = SEVERITY_CRITICAL;
+        }
+        if update {
+            session.latched_breach = eval.breach_flags & BREACH_FLAG_VNC_VIOLATION != 0;
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// Default context backing the legacy global functions
+static DEFAULT_CONTEXT: NavContext = NavContext::new();
 
-    #[test]
-    fn test_rust_core_init() {
-        assert_eq!(rust_core_init(), 1);
-        assert_eq!(check_system_robustness(), 1);
+/// Create a new, initialized context
+/// Caller must release it with `nav_context_destroy`
+#[no_mangle]
+pub extern "C" fn nav_context_create() -> *mut NavContext {
+    let ctx = NavContext::new();
+    ctx.initialized.store(true, Ordering::Release);
+    Box::into_raw(Box::new(ctx))
+}
+
+/// Destroy a context created by `nav_context_create`
+///
+/// # Safety
+///
+/// `ctx` must be null or a pointer returned by `nav_context_create` that has
+/// not already been destroyed. The handle must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_destroy(ctx: *mut NavContext) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
     }
+}
 
-    #[test]
-    fn test_calculate_p_score() {
-        rust_core_init();
-        
-        let state = State7D {
-            position: [1.0, 2.0, 3.0],
-            velocity: [0.1, 0.2, 0.3],
-            heading: 45.0,
-            timestamp: 1000,
-            certainty: 0.8,
-            fatigue: 0.9,
-        };
+/// Store session RigorParams used when `nav_calculate_p_score` gets null params
+///
+/// # Safety
+///
+/// `ctx` must be a live context handle and `params` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_set_params(
+    ctx: *mut NavContext,
+    params: *const RigorParams,
+) -> NavStatus {
+    with_last_error("nav_context_set_params", || {
+        if ctx.is_null() || params.is_null() {
+            return NavStatus::NullPointer;
+        }
+        (*ctx).session().params = Some(*params);
+        NavStatus::Ok
+    })
+}
 
-        let params = RigorParams {
-            alpha: 5.0,
-            min_margin: 0.5,
-        };
+/// Reseed the default context's sampling RNG
+///
+/// Sampling-based functions (`calculate_sim2val_bootstrap`) draw from this
+/// RNG, so the same seed followed by the same calls reproduces the same
+/// results exactly. Without a call the RNG starts from
+/// `NAV_DEFAULT_RNG_SEED`, never OS entropy.
+#[no_mangle]
+pub extern "C" fn nav_set_rng_seed(seed: u64) {
+    DEFAULT_CONTEXT.session().rng = SplitMix64(seed);
+}
 
-        let obstacles = [0.0, 0.0, 0.0, 10.0, 10.0, 10.0];
-        let mut result = VerificationResult {
-            p_score: 0.0,
-            is_safe: 0,
-            margin: 0.0,
-            sigma: 0.0,
-            breach_reason: ptr::null_mut(),
-            evidence_hash: ptr::null_mut(),
-        };
+/// Reseed a context's sampling RNG (see `nav_set_rng_seed`)
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_set_rng_seed(ctx: *mut NavContext, seed: u64) -> NavStatus {
+    with_last_error("nav_context_set_rng_seed", || {
+        if ctx.is_null() {
+            return NavStatus::NullPointer;
+        }
+        (*ctx).session().rng = SplitMix64(seed);
+        NavStatus::Ok
+    })
+}
 
-        unsafe {
-            let success = calculate_p_score(
-                &state,
-                &params,
-                obstacles.as_ptr(),
-                2,
-                &mut result,
-            );
+/// Obstacle count cap every context starts with
+///
+/// Far above any real scene (the grid and SIMD paths are tuned for tens of
+/// thousands) but finite, so a corrupted count can't walk off the buffer.
+pub const NAV_DEFAULT_MAX_OBSTACLES: usize = 1_000_000;
 
-            assert_eq!(success, 1);
-            assert!(result.p_score > 0.0);
-            
-            // Free allocated strings
-            free_c_string(result.breach_reason);
-            free_c_string(result.evidence_hash);
+/// Set the largest obstacle count a context's functions will read
+///
+/// Defense in depth for the raw pointer path: every function taking an
+/// obstacle array trusts `obstacle_count` to describe the buffer, so a bogus
+/// huge count (e.g. an integer overflow on the managed side) would read far
+/// out of bounds. Counts above the cap are rejected before the pointer is
+/// touched: `TooManyObstacles` from functions returning a `NavStatus`,
+/// otherwise the function's invalid-input value (NaN, null,
+/// `NAV_FLAGS_INVALID_INPUT`, ...). The default is
+/// `NAV_DEFAULT_MAX_OBSTACLES`.
+///
+/// A null `ctx` sets the default context's cap, which applies to the global
+/// functions (`calculate_p_score*`, `calculate_min_margin`,
+/// `verify_flags_only`, `nav_build_obstacle_grid`, `nav_merge_obstacles`
+/// per input array, ...); the `nav_*` context functions use their own
+/// context's cap. `rust_core_shutdown` restores the default. Returns
+/// `OutOfRange` for 0 (pass `usize::MAX` to disable the guard).
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_set_max_obstacles(ctx: *mut NavContext, max_obstacles: usize) -> NavStatus {
+    with_last_error("nav_context_set_max_obstacles", || {
+        if max_obstacles == 0 {
+            return NavStatus::OutOfRange;
+        }
+        log_context(ctx).session().max_obstacles = max_obstacles;
+        NavStatus::Ok
+    })
+}
+
+/// Set the hysteresis band of a context's safety verdict (0 = off, the default)
+///
+/// Without hysteresis, a margin hovering at `min_margin` flips `is_safe`
+/// every frame. With a band, once a verification reports a margin breach
+/// (`BREACH_FLAG_VNC_VIOLATION`), later verifications in the same context
+/// keep reporting it until the margin reaches `band`, i.e. the clearance
+/// exceeds `min_margin + band`. The breach is only entered at margin < 0,
+/// as before. Other flags are unaffected.
+///
+/// The last verdict is stored per context, so verify one agent per context
+/// (as with `sim2val_ema_update`). Single-state verifications apply it; the
+/// batch functions, which run a whole fleet through the default context,
+/// don't. A null `ctx` sets the default context's band, used by the global
+/// `calculate_p_score*` functions. Setting the band forgets the last verdict.
+///
+/// Returns `NonFinite` for NaN/inf and `OutOfRange` for a negative band.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_set_hysteresis(ctx: *mut NavContext, band: c_float) -> NavStatus {
+    with_last_error("nav_context_set_hysteresis", || {
+        if !band.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        if band < 0.0 {
+            return NavStatus::OutOfRange;
+        }
+        let mut session = log_context(ctx).session();
+        session.hysteresis_band = band;
+        session.latched_breach = false;
+        NavStatus::Ok
+    })
+}
+
+/// Check robustness of a single context
+/// Returns 1 if robust, 0 if failed
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_context_check_robustness(ctx: *const NavContext) -> c_int {
+    if ctx.is_null() {
+        return 0;
+    }
+    if (*ctx).initialized.load(Ordering::Acquire) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Initialize the Rust core library
+/// Returns 1 if successful, 0 if failed
+///
+/// Lifecycle: `rust_core_init` -> verifications -> `rust_core_shutdown`,
+/// repeatable. Init is idempotent: calling it on an initialized core is a
+/// no-op that keeps the current session state. After a shutdown it starts
+/// again from a fresh default context.
+///
+/// Deprecated: operates on the shared default context. Prefer
+/// `nav_context_create` for independent sessions, and
+/// `rust_core_init_checked` to catch struct layout drift.
+#[no_mangle]
+pub extern "C" fn rust_core_init() -> c_int {
+    let status = rust_core_init_checked(
+        std::mem::size_of::<State7D>(),
+        std::mem::size_of::<VerificationResult>(),
+        std::mem::size_of::<RigorParams>(),
+    );
+    (status == NavStatus::Ok) as c_int
+}
+
+/// Initialize the Rust core library after checking the caller's struct sizes
+///
+/// The caller passes its own `sizeof` (`Marshal.SizeOf` in C#) of its
+/// `State7D`, `VerificationResult` and `RigorParams` mirrors. A binding
+/// compiled against another version of a struct would otherwise read and
+/// write past or short of it silently; here it fails at startup instead.
+/// Returns the first mismatch, in argument order, as
+/// `StateSizeMismatch`, `ResultSizeMismatch` or `ParamsSizeMismatch`, and
+/// leaves the core uninitialized. Otherwise same as `rust_core_init`.
+///
+/// Size only catches added, removed or resized fields; `nav_core_abi_hash`
+/// also covers alignment and field offsets.
+#[no_mangle]
+pub extern "C" fn rust_core_init_checked(state_size: usize, result_size: usize, params_size: usize) -> NavStatus {
+    with_last_error("rust_core_init_checked", || {
+        if state_size != std::mem::size_of::<State7D>() {
+            return NavStatus::StateSizeMismatch;
+        }
+        if result_size != std::mem::size_of::<VerificationResult>() {
+            return NavStatus::ResultSizeMismatch;
+        }
+        if params_size != std::mem::size_of::<RigorParams>() {
+            return NavStatus::ParamsSizeMismatch;
+        }
+        DEFAULT_CONTEXT.initialized.store(true, Ordering::Release);
+        NavStatus::Ok
+    })
+}
+
+/// Tear down the state behind the legacy global functions
+///
+/// Clears the initialized flag (`check_system_robustness` returns 0 until
+/// the next `rust_core_init`), resets the default context's session
+/// (params, EMA, RNG seed, heightmap, hysteresis and breach log, including
+/// its capacity), clears the breach callback, closes the breach log file
+/// and forgets every registered allocation. Contexts from
+/// `nav_context_create` are untouched. Safe to call repeatedly or before
+/// init.
+#[no_mangle]
+pub extern "C" fn rust_core_shutdown() {
+    DEFAULT_CONTEXT.initialized.store(false, Ordering::Release);
+    *DEFAULT_CONTEXT.session() = Session::new();
+    nav_set_breach_callback(None);
+    *BREACH_LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    ALLOC_TABLE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Check system robustness
+/// Returns 1 if robust, 0 if failed
+///
+/// Deprecated: reads the shared default context. Prefer
+/// `nav_context_check_robustness`.
+#[no_mangle]
+pub extern "C" fn check_system_robustness() -> c_int {
+    unsafe { nav_context_check_robustness(&DEFAULT_CONTEXT) }
+}
+
+// Live allocations registered by Unity: base address -> size in bytes
+static ALLOC_TABLE: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+// Reject allocations > 1GB (suspicious)
+const MAX_ALLOC_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Record a live Unity allocation so `validate_unity_alloc` can check it
+/// Re-registering the same pointer replaces its recorded size
+#[no_mangle]
+pub extern "C" fn nav_register_alloc(ptr: *mut c_void, size: usize) -> NavStatus {
+    with_last_error("nav_register_alloc", || {
+        if ptr.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if size == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if size > MAX_ALLOC_SIZE {
+            return NavStatus::OutOfRange;
+        }
+        ALLOC_TABLE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ptr as usize, size);
+        NavStatus::Ok
+    })
+}
+
+/// Forget an allocation previously recorded with `nav_register_alloc`
+/// Returns `UnknownPointer` if it was never registered
+#[no_mangle]
+pub extern "C" fn nav_unregister_alloc(ptr: *mut c_void) -> NavStatus {
+    with_last_error("nav_unregister_alloc", || {
+        if ptr.is_null() {
+            return NavStatus::NullPointer;
+        }
+        match ALLOC_TABLE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(ptr as usize))
+        {
+            Some(_) => NavStatus::Ok,
+            None => NavStatus::UnknownPointer,
+        }
+    })
+}
+
+/// Validate Unity memory allocation against the live allocation table
+///
+/// Returns `NullPointer` for a null `ptr`, `EmptyInput` for `size == 0`,
+/// `OutOfRange` for sizes above 1GB, `UnknownPointer` if `ptr` was never
+/// registered with `nav_register_alloc`, and `SizeMismatch` if `size` differs
+/// from the recorded size (e.g. a buffer overrun on the Unity side).
+#[no_mangle]
+pub extern "C" fn validate_unity_alloc(ptr: *mut c_void, size: usize) -> NavStatus {
+    with_last_error("validate_unity_alloc", || {
+        if ptr.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if size == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if size > MAX_ALLOC_SIZE {
+            return NavStatus::OutOfRange;
+        }
+        match ALLOC_TABLE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(ptr as usize))
+        {
+            Some(&recorded) if recorded == size => NavStatus::Ok,
+            Some(_) => NavStatus::SizeMismatch,
+            None => NavStatus::UnknownPointer,
+        }
+    })
+}
+
+/// Calculate P-score using Ironclad 7D Math
+/// 
+/// # Safety
+/// 
+/// This function is unsafe because it dereferences raw pointers.
+/// Caller must ensure:
+/// - `obstacles` points to a valid array of at least `obstacle_count * 3` floats
+/// - `result` is a valid pointer to a VerificationResult struct
+///
+/// Returns `NullPointer` if `state`, `params` or `result` is null and
+/// `NonFinite` if any float in `state`, `params` or the obstacle array is NaN
+/// or infinite. On error `result` is left untouched.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score", || {
+        calculate_p_score_strided(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT, 0.0, result)
+    })
+}
+
+/// Calculate P-score within a session context
+///
+/// Same as `calculate_p_score`, but returns `Uninitialized` if `ctx` is null
+/// or not initialized. When `params` is null the context's session parameters
+/// (set via `nav_context_set_params`, defaulting to `RigorParams::default()`)
+/// are used.
+///
+/// # Safety
+///
+/// Same requirements as `calculate_p_score`, plus `ctx` must be null or a
+/// live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_calculate_p_score(
+    ctx: *const NavContext,
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("nav_calculate_p_score", || {
+        if nav_context_check_robustness(ctx) != 1 {
+            return NavStatus::Uninitialized;
+        }
+
+        let session_params;
+        let params = if params.is_null() {
+            session_params = (*ctx).params();
+            &session_params as *const RigorParams
+        } else {
+            params
+        };
+
+        calculate_p_score_strided(&*ctx, state, params, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT, 0.0, result)
+    })
+}
+
+/// Calculate P-score against obstacles with individual radii
+///
+/// Obstacles are read as `[x, y, z, radius]` records and each margin is
+/// `dist - radius - params.min_margin`. Otherwise identical to
+/// `calculate_p_score`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// Caller must ensure:
+/// - `obstacles` points to a valid array of at least `obstacle_count * 4` floats
+///   (allocate four floats per obstacle, not three)
+/// - `result` is a valid pointer to a VerificationResult struct
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_sized(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_sized", || {
+        calculate_p_score_strided(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count, OBSTACLE_STRIDE_SIZED, 0.0, result)
+    })
+}
+
+/// Obstacle record for `calculate_p_score_obstacles`
+///
+/// Same memory layout as one `[x, y, z, radius]` record of
+/// `calculate_p_score_sized`, but typed, so a mismatched layout on the
+/// caller's side is a compile error instead of garbage reads.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Obstacle {
+    pub pos: [c_float; 3], // Center
+    pub radius: c_float,   // 0 for a point obstacle
+}
+
+// `calculate_p_score_obstacles` reads the array as flat sized records
+const _: () = assert!(std::mem::size_of::<Obstacle>() == OBSTACLE_STRIDE_SIZED * std::mem::size_of::<c_float>());
+
+/// Calculate P-score against an array of `Obstacle` structs
+///
+/// The typed form of `calculate_p_score_sized`: each margin is
+/// `dist - radius - params.min_margin`, and results (evidence hash
+/// included) are identical to passing the same values as flat
+/// `[x, y, z, radius]` floats. Prefer it for new callers; the flat-float
+/// functions remain for existing ones.
+///
+/// # Safety
+///
+/// `state`, `params` and `result` must be valid pointers, and `obstacles`
+/// must point to at least `obstacle_count` Obstacle structs (or be null
+/// when the count is 0).
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_obstacles(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const Obstacle,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_obstacles", || {
+        calculate_p_score_strided(
+            &DEFAULT_CONTEXT,
+            state,
+            params,
+            obstacles.cast::<c_float>(),
+            obstacle_count,
+            OBSTACLE_STRIDE_SIZED,
+            0.0,
+            result,
+        )
+    })
+}
+
+/// Calculate P-score against moving obstacles
+///
+/// Obstacles are read as `[x, y, z, vx, vy, vz]` records. For each one the
+/// closest approach under the agent/obstacle relative velocity is predicted
+/// over `params.horizon_secs` (same math as the static time-to-collision
+/// check); a predicted separation below `min_margin` is reported as
+/// `DYNAMIC_COLLISION`. With `horizon_secs == 0` only the current distance is
+/// checked.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// Caller must ensure:
+/// - `obstacles` points to a valid array of at least `obstacle_count * 6` floats
+///   (allocate six floats per obstacle)
+/// - `result` is a valid pointer to a VerificationResult struct
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_dynamic(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_dynamic", || {
+        calculate_p_score_strided(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count, OBSTACLE_STRIDE_DYNAMIC, 0.0, result)
+    })
+}
+
+/// Calculate P-score for a `State7DQuat` (full 3D orientation)
+///
+/// Identical to `calculate_p_score` except that the slope-direction term
+/// uses the quaternion's forward axis instead of `heading`: its angle in the
+/// ground (x/z) plane to the uphill direction, which is the registered
+/// heightmap's gradient or else +Z (see `State7DQuat::to_state7d`). The breach callback receives the equivalent `State7D`.
+/// Returns `OutOfRange` for a zero quaternion.
+///
+/// # Safety
+///
+/// Same requirements as `calculate_p_score`.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_quat(
+    state: *const State7DQuat,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_quat", || {
+        p_score_quat_in(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count, result)
+    })
+}
+
+/// `calculate_p_score_quat` against any context (its terrain orients the heading)
+unsafe fn p_score_quat_in(
+    ctx: &NavContext,
+    state: *const State7DQuat,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    if state.is_null() || params.is_null() || result.is_null() {
+        return NavStatus::NullPointer;
+    }
+
+    let quat_state = *state;
+    let params = *params;
+    let obstacle_slice = match obstacle_slice(ctx, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) {
+        Ok(slice) => slice,
+        Err(status) => return status,
+    };
+    if !quat_state.is_finite() || !params.is_finite() || !obstacle_slice.iter().all(|v| v.is_finite()) {
+        return NavStatus::NonFinite;
+    }
+    let terrain = ctx.heightmap();
+    let Some(mut state) = quat_state.to_state7d(terrain.as_deref()) else {
+        return NavStatus::OutOfRange;
+    };
+    if let Err(status) = params.check_cognitive(&mut state) {
+        return status;
+    }
+
+    let mut eval = evaluate(&state, &params, terrain.as_deref(), obstacle_slice, OBSTACLE_STRIDE_POINT);
+    ctx.apply_hysteresis(&mut eval, true);
+    let evidence_hash_hex = compute_evidence_hash(
+        &state,
+        Some(&quat_state.orientation),
+        &params,
+        EvidenceObstacles::Raw(obstacle_slice),
+        eval.p_score,
+        eval.is_safe,
+        eval.margin,
+    );
+    *result = build_result(ctx, &state, &eval, 0.0, evidence_hash_hex);
+    NavStatus::Ok
+}
+
+/// Calculate P-score over a fixed-capacity `[x, y, z]` obstacle pool
+///
+/// `valid_mask` holds one byte per obstacle slot (`obstacle_count` bytes,
+/// the same count as the obstacle array): `valid_mask[i] != 0` marks slot
+/// `i` live, and inactive slots are skipped entirely: they never affect the
+/// margin or flags and may hold stale or even non-finite values. The result
+/// matches `calculate_p_score` over the compacted live obstacles, except
+/// that `closest_obstacle_index` is the slot index in the full pool. The
+/// evidence hash commits to each live slot's index and coordinates only, so
+/// it doesn't change as dead slots are overwritten.
+///
+/// # Safety
+///
+/// Same pointer requirements as `calculate_p_score`; `valid_mask` must
+/// point to `obstacle_count` bytes (it may be null only when
+/// `obstacle_count` is 0, otherwise `NullPointer` is returned).
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_masked(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    valid_mask: *const u8,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_masked", || {
+        if state.is_null() || params.is_null() || result.is_null() || (valid_mask.is_null() && obstacle_count > 0) {
+            return NavStatus::NullPointer;
+        }
+        let mut state = *state;
+        let params = *params;
+        let obstacles = match obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) {
+            Ok(slice) => slice,
+            Err(status) => return status,
+        };
+        let mask = if obstacles.is_empty() { &[][..] } else { std::slice::from_raw_parts(valid_mask, obstacle_count) };
+        let live = || {
+            obstacles
+                .chunks_exact(OBSTACLE_STRIDE_POINT)
+                .zip(mask)
+                .enumerate()
+                .filter(|(_, (_, &valid))| valid != 0)
+                .map(|(i, (obstacle, _))| (i, obstacle))
+        };
+        if !state.is_finite() || !params.is_finite() || !live().all(|(_, o)| o.iter().all(|v| v.is_finite())) {
+            return NavStatus::NonFinite;
+        }
+        if let Err(status) = params.check_cognitive(&mut state) {
+            return status;
+        }
+
+        let terrain = DEFAULT_CONTEXT.heightmap();
+        let mut eval = evaluate_indexed(
+            &state,
+            &params,
+            terrain.as_deref(),
+            live().map(|(i, obstacle)| (i, obstacle, obstacle_margin(&state, &params, obstacle, OBSTACLE_STRIDE_POINT))),
+            OBSTACLE_STRIDE_POINT,
+        );
+        DEFAULT_CONTEXT.apply_hysteresis(&mut eval, true);
+        let live: Vec<(usize, &[c_float])> = live().collect();
+        let evidence_hash_hex = compute_evidence_hash(
+            &state,
+            None,
+            &params,
+            EvidenceObstacles::Masked(&live),
+            eval.p_score,
+            eval.is_safe,
+            eval.margin,
+        );
+        *result = build_result(&DEFAULT_CONTEXT, &state, &eval, 0.0, evidence_hash_hex);
+        NavStatus::Ok
+    })
+}
+
+/// Obstacle layout `[x, y, z]`
+const OBSTACLE_STRIDE_POINT: usize = 3;
+/// Obstacle layout `[x, y, z, radius]`
+const OBSTACLE_STRIDE_SIZED: usize = 4;
+/// Obstacle layout `[x, y, z, vx, vy, vz]`
+const OBSTACLE_STRIDE_DYNAMIC: usize = 6;
+
+/// Shared FFI body: validate pointers, evaluate, hash and write the result
+/// (`sigma` is stored as is; only the uncertainty variant passes non-zero)
+#[allow(clippy::too_many_arguments)]
+unsafe fn calculate_p_score_strided(
+    ctx: &NavContext,
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    stride: usize,
+    sigma: c_float,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    // Validate inputs
+    if state.is_null() || params.is_null() || result.is_null() {
+        return NavStatus::NullPointer;
+    }
+
+    let mut state = *state;
+    let params = *params;
+    let obstacle_slice = match obstacle_slice(ctx, obstacles, obstacle_count, stride) {
+        Ok(slice) => slice,
+        Err(status) => return status,
+    };
+
+    // Reject NaN/inf anywhere in the inputs before they reach the verdict.
+    // Safety-critical: this check is unconditional, not debug-only.
+    if !state.is_finite() || !params.is_finite() || !obstacle_slice.iter().all(|v| v.is_finite()) {
+        return NavStatus::NonFinite;
+    }
+    if let Err(status) = params.check_cognitive(&mut state) {
+        return status;
+    }
+
+    *result = verify_state(ctx, &state, &params, obstacle_slice, stride, Latch::Update, sigma);
+    NavStatus::Ok
+}
+
+/// Returned by `verify_flags_only` for invalid input (every flag bit set)
+pub const NAV_FLAGS_INVALID_INPUT: c_ulonglong = c_ulonglong::MAX;
+
+/// Breach flags for a state without building a result
+///
+/// A dry run of `calculate_p_score` for high-frequency pre-checks: it runs
+/// the same evaluation (`evaluate_in`, including the default context's
+/// terrain and hysteresis) and returns the `breach_flags` the full call
+/// would report, but allocates nothing. A latched breach is held but the
+/// latch is left as it was. No `breach_reason` or `evidence_hash` string is
+/// built, the breach is not logged and the breach callback does not fire.
+///
+/// 0 means safe, but non-zero does not always mean unsafe: with
+/// `soft_cognitive_breach` the FATIGUE and LOW_CERTAINTY bits are warnings
+/// that the full call reports alongside `is_safe = 1`.
+///
+/// Invalid input (a null `state` or `params`, any NaN/inf, certainty or
+/// fatigue out of range without `clamp_cognitive`) returns
+/// `NAV_FLAGS_INVALID_INPUT`. It has every bit set, so a caller that only
+/// tests for non-zero treats bad input as unsafe.
+///
+/// # Safety
+///
+/// `state` and `params` must be null or valid pointers, and `obstacles` must
+/// point to at least `obstacle_count * 3` floats.
+#[no_mangle]
+pub unsafe extern "C" fn verify_flags_only(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+) -> c_ulonglong {
+    verify_flags_in(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count)
+}
+
+/// `verify_flags_only` against any context
+unsafe fn verify_flags_in(
+    ctx: &NavContext,
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+) -> c_ulonglong {
+    if state.is_null() || params.is_null() {
+        return NAV_FLAGS_INVALID_INPUT;
+    }
+    let mut state = *state;
+    let params = *params;
+    let Ok(obstacle_slice) = obstacle_slice(ctx, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) else {
+        return NAV_FLAGS_INVALID_INPUT;
+    };
+    if !state.is_finite() || !params.is_finite() || !obstacle_slice.iter().all(|v| v.is_finite()) {
+        return NAV_FLAGS_INVALID_INPUT;
+    }
+    if params.check_cognitive(&mut state).is_err() {
+        return NAV_FLAGS_INVALID_INPUT;
+    }
+
+    evaluate_in(ctx, &state, &params, obstacle_slice, OBSTACLE_STRIDE_POINT, Latch::Read).breach_flags
+}
+
+/// Calculate P-scores for many agents against one shared obstacle array
+///
+/// Equivalent to calling `calculate_p_score` for each of `states[0..count]`,
+/// writing `results[i]` for `states[i]`, but crosses the FFI boundary once and
+/// validates `params` and the obstacle array only once. Inputs are validated
+/// up front: on any error no result is written.
+///
+/// Every result owns its own `breach_reason` and `evidence_hash`; the caller
+/// must free each result with `free_verification_result`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// Caller must ensure:
+/// - `states` points to at least `count` State7D structs
+/// - `results` points to at least `count` writable VerificationResult structs
+/// - `obstacles` points to a valid array of at least `obstacle_count * 3` floats
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_batch(
+    states: *const State7D,
+    count: usize,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    results: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_batch", || {
+        if results.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let (checked, params, obstacle_slice) = match checked_batch(states, count, params, obstacles, obstacle_count) {
+            Ok(batch) => batch,
+            Err(status) => return status,
+        };
+
+        let results = std::slice::from_raw_parts_mut(results, count);
+        for (state, result) in checked.iter().zip(results.iter_mut()) {
+            *result = verify_state(&DEFAULT_CONTEXT, state, &params, obstacle_slice, OBSTACLE_STRIDE_POINT, Latch::Off, 0.0);
+        }
+        NavStatus::Ok
+    })
+}
+
+/// Receives the result for `states[index]` from `calculate_p_score_batch_cb`
+///
+/// The result (including its `breach_reason` and `evidence_hash` strings)
+/// is only valid for the duration of the call and remains owned by the
+/// library: copy what you need and do NOT free it.
+pub type BatchResultCallback = extern "C" fn(index: usize, result: *const VerificationResult);
+
+/// Calculate P-scores for many agents, delivering each result to a callback
+///
+/// Same inputs, validation and per-state results as
+/// `calculate_p_score_batch`, but instead of filling a preallocated array
+/// each result is passed to `cb` as soon as its state is verified, in index
+/// order, and freed when the callback returns. Memory use stays constant
+/// however large the batch. Inputs are validated up front: on any error the
+/// callback is never invoked.
+///
+/// # Safety
+///
+/// `states` must point to at least `count` State7D structs and `obstacles`
+/// to at least `obstacle_count * 3` floats. `cb` must not retain the result
+/// pointer (see `BatchResultCallback`).
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_batch_cb(
+    states: *const State7D,
+    count: usize,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    cb: Option<BatchResultCallback>,
+) -> NavStatus {
+    with_last_error("calculate_p_score_batch_cb", || {
+        let Some(cb) = cb else {
+            return NavStatus::NullPointer;
+        };
+        let (checked, params, obstacle_slice) = match checked_batch(states, count, params, obstacles, obstacle_count) {
+            Ok(batch) => batch,
+            Err(status) => return status,
+        };
+
+        for (index, state) in checked.iter().enumerate() {
+            let mut result = verify_state(&DEFAULT_CONTEXT, state, &params, obstacle_slice, OBSTACLE_STRIDE_POINT, Latch::Off, 0.0);
+            cb(index, &result);
+            free_verification_result(&mut result);
+        }
+        NavStatus::Ok
+    })
+}
+
+/// Validate the shared inputs of the batch functions, returning the states
+/// after the certainty/fatigue range policy, the params and the obstacles
+unsafe fn checked_batch<'a>(
+    states: *const State7D,
+    count: usize,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+) -> Result<(Vec<State7D>, RigorParams, &'a [c_float]), NavStatus> {
+    if states.is_null() || params.is_null() {
+        return Err(NavStatus::NullPointer);
+    }
+    if count == 0 {
+        return Err(NavStatus::EmptyInput);
+    }
+
+    let params = *params;
+    let states = std::slice::from_raw_parts(states, count);
+    let obstacle_slice = obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT)?;
+
+    if !params.is_finite()
+        || !obstacle_slice.iter().all(|v| v.is_finite())
+        || !states.iter().all(State7D::is_finite)
+    {
+        return Err(NavStatus::NonFinite);
+    }
+    let mut checked = states.to_vec();
+    if let Some(status) = checked.iter_mut().find_map(|state| params.check_cognitive(state).err()) {
+        return Err(status);
+    }
+    Ok((checked, params, obstacle_slice))
+}
+
+/// Aggregate a fleet's margins into one risk number
+///
+/// Writes the `percentile` (in `[0, 1]`, e.g. 0.05 for the 5th-percentile
+/// worst-case clearance) of `results[i].margin` to `out`, interpolated the
+/// same way as `calculate_sim2val_montecarlo`. Pass the array filled by
+/// `calculate_p_score_batch`; only `margin` is read, so the results may be
+/// freed before or after the call.
+///
+/// A negative aggregate means the fleet is in breach at that percentile:
+/// at least `floor(percentile * (count - 1)) + 1` agents (roughly that
+/// fraction of the fleet) are inside their margin. Agents without
+/// obstacles contribute `c_float::MAX`.
+///
+/// Returns `EmptyInput` for `count == 0`, `OutOfRange` when `percentile` is
+/// outside `[0, 1]` and `NonFinite` for a NaN percentile or margin.
+///
+/// # Safety
+///
+/// `results` must point to at least `count` VerificationResult structs and
+/// `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn aggregate_fleet_risk(
+    results: *const VerificationResult,
+    count: usize,
+    percentile: c_float,
+    out: *mut c_float,
+) -> NavStatus {
+    with_last_error("aggregate_fleet_risk", || {
+        if results.is_null() || out.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if count == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if percentile.is_nan() {
+            return NavStatus::NonFinite;
+        }
+        if !(0.0..=1.0).contains(&percentile) {
+            return NavStatus::OutOfRange;
+        }
+
+        let mut margins: Vec<c_float> = std::slice::from_raw_parts(results, count).iter().map(|r| r.margin).collect();
+        if margins.iter().any(|m| m.is_nan()) {
+            return NavStatus::NonFinite;
+        }
+        margins.sort_by(|a, b| a.total_cmp(b));
+        *out = interpolated_percentile(&margins, percentile);
+        NavStatus::Ok
+    })
+}
+
+/// Minimum obstacle clearance only, for per-frame proximity displays
+///
+/// Returns the same value as `VerificationResult::margin` from
+/// `calculate_p_score` (`dist - min_margin` to the closest `[x, y, z]`
+/// obstacle) without computing the score or allocating any strings, so
+/// there is nothing to free. A negative return means the agent is inside
+/// the margin (a VNC breach); `c_float::MAX` means no obstacles. Returns NaN
+/// when `state` or `params` is null or any input is NaN/infinite, so an
+/// invalid call can't be mistaken for clearance.
+///
+/// # Safety
+///
+/// `state` and `params` must be null or valid pointers; `obstacles` must be
+/// null or point to at least `obstacle_count * 3` floats.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_min_margin(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+) -> c_float {
+    if state.is_null() || params.is_null() {
+        return c_float::NAN;
+    }
+    let state = &*state;
+    let params = &*params;
+    let Ok(obstacles) = obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) else {
+        return c_float::NAN;
+    };
+    if !state.is_finite() || !params.is_finite() || !obstacles.iter().all(|v| v.is_finite()) {
+        return c_float::NAN;
+    }
+
+    // Same scan as `evaluate`: stop at the first breach
+    let mut min_margin_dist = c_float::MAX;
+    for obstacle in obstacles.chunks_exact(OBSTACLE_STRIDE_POINT) {
+        let margin = obstacle_margin(state, params, obstacle, OBSTACLE_STRIDE_POINT);
+        min_margin_dist = min_margin_dist.min(margin);
+        if margin < 0.0 {
+            break;
+        }
+    }
+    min_margin_dist
+}
+
+/// Returned by `calculate_breaching_obstacles` for invalid input (distinct
+/// from any required-capacity count)
+pub const NAV_BREACHING_INVALID_INPUT: isize = isize::MIN;
+
+/// Indices of every `[x, y, z]` obstacle inside the margin, not just the closest
+///
+/// An obstacle breaches when its `obstacle_margin` (the per-obstacle value
+/// `calculate_p_score` takes the minimum of) is negative. Unlike the main
+/// scan this one doesn't stop at the first breach, so an agent boxed in by
+/// several obstacles gets all of them, in ascending index order.
+///
+/// Buffer sizing: returns the number of indices written to `out_indices`.
+/// If more than `out_cap` obstacles breach nothing is written and the
+/// negated required count is returned, so the caller can grow the buffer
+/// and retry (passing null with `out_cap` 0 just queries the count). 0 means
+/// no breach. Returns `NAV_BREACHING_INVALID_INPUT` when `state` or `params`
+/// is null, any input is NaN/infinite, or `out_indices` is null but needed.
+///
+/// # Safety
+///
+/// `state` and `params` must be null or valid pointers; `obstacles` must be
+/// null or point to at least `obstacle_count * 3` floats; `out_indices` must
+/// be null or point to `out_cap` writable ints.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_breaching_obstacles(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    out_indices: *mut c_int,
+    out_cap: usize,
+) -> isize {
+    if state.is_null() || params.is_null() {
+        return NAV_BREACHING_INVALID_INPUT;
+    }
+    let state = &*state;
+    let params = &*params;
+    let Ok(obstacles) = obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) else {
+        return NAV_BREACHING_INVALID_INPUT;
+    };
+    if !state.is_finite() || !params.is_finite() || !obstacles.iter().all(|v| v.is_finite()) {
+        return NAV_BREACHING_INVALID_INPUT;
+    }
+
+    let breaching: Vec<c_int> = obstacles
+        .chunks_exact(OBSTACLE_STRIDE_POINT)
+        .enumerate()
+        .filter(|(_, obstacle)| obstacle_margin(state, params, obstacle, OBSTACLE_STRIDE_POINT) < 0.0)
+        .map(|(i, _)| i as c_int)
+        .collect();
+
+    if breaching.len() > out_cap {
+        return -(breaching.len() as isize);
+    }
+    if breaching.is_empty() {
+        return 0;
+    }
+    if out_indices.is_null() {
+        return NAV_BREACHING_INVALID_INPUT;
+    }
+    std::slice::from_raw_parts_mut(out_indices, breaching.len()).copy_from_slice(&breaching);
+    breaching.len() as isize
+}
+
+/// Clearance heatmap of a static scene over a `width x height` XY grid
+///
+/// Fills `out` with the margin (`dist - min_margin`, as in
+/// `VerificationResult::margin`) of a stationary agent at each cell center,
+/// for mission planners to see where in the map the agent would be unsafe.
+/// Cell `(col, row)` is centered at `(origin_x + (col + 0.5) * cell_size,
+/// origin_y + (row + 0.5) * cell_size, 0)` and stored row-major at
+/// `out[row * width + col]`, so `origin` is the grid's minimum corner.
+///
+/// A static snapshot: velocity, the horizon, the score terms and cognitive
+/// state play no part, only the obstacle geometry, `min_margin`,
+/// `planar_mode` (with it off, obstacle z still counts against the z = 0
+/// plane) and `distance_metric`. Each cell holds the minimum over all
+/// obstacles (`calculate_min_margin` stops at the first breach instead, so
+/// it can differ inside overlapping obstacles); negative is a breach and
+/// `c_float::MAX` means no obstacles. Cost is `width * height * count`.
+///
+/// Returns `NullPointer` for a null `params` or `out`, `NonFinite` for
+/// NaN/inf inputs, `OutOfRange` when `cell_size <= 0`, a dimension is 0 or
+/// `width * height` overflows, and `TooManyObstacles` past the default
+/// context's cap. `out` is untouched on error.
+///
+/// # Safety
+///
+/// `params` must be null or valid; `obstacles` must be null or point to at
+/// least `obstacle_count * 3` floats; `out` must be null or point to
+/// `width * height` writable floats.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn compute_risk_grid(
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    origin_x: c_float,
+    origin_y: c_float,
+    cell_size: c_float,
+    width: usize,
+    height: usize,
+    out: *mut c_float,
+) -> NavStatus {
+    with_last_error("compute_risk_grid", || {
+        if params.is_null() || out.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let params = &*params;
+        let obstacles = match obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) {
+            Ok(slice) => slice,
+            Err(status) => return status,
+        };
+        if !params.is_finite()
+            || ![origin_x, origin_y, cell_size].iter().all(|v| v.is_finite())
+            || !obstacles.iter().all(|v| v.is_finite())
+        {
+            return NavStatus::NonFinite;
+        }
+        let Some(cells) = width.checked_mul(height).filter(|&cells| cells > 0 && cell_size > 0.0) else {
+            return NavStatus::OutOfRange;
+        };
+
+        let out = std::slice::from_raw_parts_mut(out, cells);
+        let mut probe = State7D {
+            position: [0.0; 3],
+            velocity: [0.0; 3],
+            heading: 0.0,
+            timestamp: 0,
+            certainty: 1.0,
+            fatigue: 1.0,
+        };
+        for (row, cells) in out.chunks_exact_mut(width).enumerate() {
+            probe.position[1] = origin_y + (row as c_float + 0.5) * cell_size;
+            for (col, cell) in cells.iter_mut().enumerate() {
+                probe.position[0] = origin_x + (col as c_float + 0.5) * cell_size;
+                *cell = obstacles
+                    .chunks_exact(OBSTACLE_STRIDE_POINT)
+                    .map(|obstacle| obstacle_margin(&probe, params, obstacle, OBSTACLE_STRIDE_POINT))
+                    .fold(c_float::MAX, c_float::min);
+            }
+        }
+        NavStatus::Ok
+    })
+}
+
+/// Distance needed to brake to a stop from `speed` at a constant
+/// `max_decel`: `v^2 / (2 * max_decel)`
+///
+/// 0 when `max_decel <= 0`, which disables the stopping-distance check.
+pub fn stopping_distance(speed: c_float, max_decel: c_float) -> c_float {
+    if max_decel > 0.0 {
+        speed * speed / (2.0 * max_decel)
+    } else {
+        0.0
+    }
+}
+
+/// Stopping distance for a state's current speed under `params.max_decel`
+///
+/// The clearance `calculate_p_score` reports as `margin` must exceed this,
+/// or the verdict carries `BREACH_FLAG_STOPPING_DISTANCE`. Speed is the
+/// velocity magnitude (X/Y only in `planar_mode`). Returns 0 when
+/// `max_decel` is 0, and NaN when `state` or `params` is null or any input
+/// is NaN/infinite.
+///
+/// # Safety
+///
+/// `state` and `params` must be null or valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_stopping_distance(state: *const State7D, params: *const RigorParams) -> c_float {
+    if state.is_null() || params.is_null() {
+        return c_float::NAN;
+    }
+    let state = &*state;
+    let params = &*params;
+    if !state.is_finite() || !params.is_finite() {
+        return c_float::NAN;
+    }
+    let vz = if params.planar_mode != 0 { 0.0 } else { state.velocity[2] };
+    let speed = (state.velocity[0].powi(2) + state.velocity[1].powi(2) + vz.powi(2)).sqrt();
+    params.stopping_distance(speed)
+}
+
+/// View a flat obstacle buffer as a slice (empty when null or zero-length)
+///
+/// `TooManyObstacles` when the count exceeds `ctx`'s cap, checked before the
+/// pointer is read (and before `obstacle_count * stride` could overflow).
+unsafe fn obstacle_slice<'a>(
+    ctx: &NavContext,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    stride: usize,
+) -> Result<&'a [c_float], NavStatus> {
+    if obstacle_count > ctx.max_obstacles() {
+        Err(NavStatus::TooManyObstacles)
+    } else if obstacles.is_null() || obstacle_count == 0 {
+        Ok(&[])
+    } else {
+        Ok(std::slice::from_raw_parts(obstacles, obstacle_count * stride))
+    }
+}
+
+// --- Obstacle Fusion ---
+/// Returned by `nav_merge_obstacles` for invalid input (distinct from any overflow count)
+pub const NAV_MERGE_INVALID_INPUT: isize = isize::MIN;
+
+/// Merge `[x, y, z]` obstacle arrays from several sensors, collapsing
+/// detections of the same object into one
+///
+/// Clustering rule (greedy, deterministic): obstacles are visited array by
+/// array, in order. Each joins the first existing cluster whose centroid is
+/// within `merge_radius` (inclusive), and that centroid becomes the mean of
+/// all its members; otherwise it starts a new cluster. The output holds one
+/// centroid per cluster, in order of creation. A radius of 0 removes exact
+/// duplicates only. Because centroids move as members join, the result can
+/// depend on input order when detections straddle the radius.
+///
+/// Returns the number of obstacles written to `out`. If more than `out_cap`
+/// clusters are needed nothing is written and the negated required count is
+/// returned, so the caller can grow the buffer and retry. Returns
+/// `NAV_MERGE_INVALID_INPUT` when `merge_radius` is negative or not finite,
+/// any coordinate is NaN/infinite, or a pointer needed for a non-empty input
+/// or output is null.
+///
+/// # Safety
+///
+/// `arrays` and `counts` must be null or point to `array_count` entries;
+/// each `arrays[i]` must be null or point to `counts[i] * 3` floats; `out`
+/// must be null or point to `out_cap * 3` writable floats.
+#[no_mangle]
+pub unsafe extern "C" fn nav_merge_obstacles(
+    arrays: *const *const c_float,
+    counts: *const usize,
+    array_count: usize,
+    merge_radius: c_float,
+    out: *mut c_float,
+    out_cap: usize,
+) -> isize {
+    if !merge_radius.is_finite() || merge_radius < 0.0 {
+        return NAV_MERGE_INVALID_INPUT;
+    }
+    if array_count > 0 && (arrays.is_null() || counts.is_null()) {
+        return NAV_MERGE_INVALID_INPUT;
+    }
+
+    // Running sum and member count per cluster; the centroid is sum / n
+    let mut clusters: Vec<([c_float; 3], c_float)> = Vec::new();
+    let radius_sq = merge_radius * merge_radius;
+    for i in 0..array_count {
+        let (array, count) = (*arrays.add(i), *counts.add(i));
+        if array.is_null() && count > 0 {
+            return NAV_MERGE_INVALID_INPUT;
+        }
+        let Ok(obstacles) = obstacle_slice(&DEFAULT_CONTEXT, array, count, OBSTACLE_STRIDE_POINT) else {
+            return NAV_MERGE_INVALID_INPUT;
+        };
+        if !obstacles.iter().all(|v| v.is_finite()) {
+            return NAV_MERGE_INVALID_INPUT;
+        }
+        for o in obstacles.chunks_exact(OBSTACLE_STRIDE_POINT) {
+            let near = clusters.iter_mut().find(|(sum, n)| {
+                (0..3).map(|k| (sum[k] / *n - o[k]).powi(2)).sum::<c_float>() <= radius_sq
+            });
+            match near {
+                Some((sum, n)) => {
+                    for (s, v) in sum.iter_mut().zip(o) {
+                        *s += v;
+                    }
+                    *n += 1.0;
+                }
+                None => clusters.push(([o[0], o[1], o[2]], 1.0)),
+            }
+        }
+    }
+
+    if clusters.len() > out_cap {
+        return -(clusters.len() as isize);
+    }
+    if clusters.is_empty() {
+        return 0;
+    }
+    if out.is_null() {
+        return NAV_MERGE_INVALID_INPUT;
+    }
+    let out = std::slice::from_raw_parts_mut(out, clusters.len() * OBSTACLE_STRIDE_POINT);
+    for (centroid, (sum, n)) in out.chunks_exact_mut(OBSTACLE_STRIDE_POINT).zip(&clusters) {
+        for (c, s) in centroid.iter_mut().zip(sum) {
+            *c = s / n;
+        }
+    }
+    clusters.len() as isize
+}
+
+// --- Fixed-Point Scoring ---
+/// One in Q16.16 fixed point; divide a fixed field by this to get the real value
+pub const NAV_FIXED_ONE: i32 = 1 << 16;
+
+/// Documented agreement between `calculate_p_score_fixed` and the float path
+/// (score and margin units), for inputs within +-100 and unit weights
+pub const NAV_FIXED_TOLERANCE: c_float = 1.0 / 1024.0;
+
+/// Integer-only verdict from `calculate_p_score_fixed` (nothing to free)
+///
+/// Score, margin and time are Q16.16: the real value times `NAV_FIXED_ONE`,
+/// saturated to the `i32` range.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationResultFixed {
+    pub p_score: i32,
+    pub is_safe: c_int,                // bool as int (0 = false, 1 = true)
+    pub margin: i32,                   // i32::MAX if no obstacles
+    pub time_to_collision: i32,        // Seconds until predicted breach (i32::MAX if none)
+    pub closest_obstacle_index: c_int, // -1 if none
+    pub breach_flags: c_ulonglong,     // BREACH_FLAG_* bits, as in VerificationResult
+}
+
+impl Default for VerificationResultFixed {
+    fn default() -> Self {
+        VerificationResultFixed {
+            p_score: 0,
+            is_safe: 0,
+            margin: i32::MAX,
+            time_to_collision: i32::MAX,
+            closest_obstacle_index: -1,
+            breach_flags: 0,
+        }
+    }
+}
+
+/// Q16.16 arithmetic for `calculate_p_score_fixed`
+///
+/// Values are `i128` holding the real value times 2^16, wide enough that no
+/// intermediate product overflows. Products of two Q16.16 values are Q32.32
+/// until shifted back; square roots take Q32.32 and yield Q16.16. Every
+/// rounding is an integer shift or division, identical on every platform.
+mod fixed {
+    use super::{c_int, NAV_METRIC_CHEBYSHEV, NAV_METRIC_MANHATTAN};
+
+    pub(crate) type Fx = i128;
+
+    pub(crate) const ONE: Fx = 1 << 16;
+    const HALF_PI: Fx = 102_944; // round(pi / 2 * 2^16)
+    const PI: Fx = 205_887;
+    const TWO_PI: Fx = 411_775;
+    const LN_2: Fx = 45_426;
+
+    /// Quantize a float to Q16.16, or None outside the `i32` range
+    pub(crate) fn from_f32(v: f32) -> Option<Fx> {
+        // f32 -> f64 and the power-of-two scale are exact, and round() is
+        // IEEE-defined, so the conversion itself is platform-independent
+        let scaled = (v as f64 * ONE as f64).round();
+        (scaled.abs() <= i32::MAX as f64).then_some(scaled as Fx)
+    }
+
+    pub(crate) fn saturate(v: Fx) -> i32 {
+        v.clamp(i32::MIN as Fx, i32::MAX as Fx) as i32
+    }
+
+    pub(crate) fn mul(a: Fx, b: Fx) -> Fx {
+        (a * b) >> 16
+    }
+
+    /// Square root of a Q32.32 value (e.g. a sum of raw products) as Q16.16
+    pub(crate) fn sqrt_wide(v: Fx) -> Fx {
+        (v.max(0) as u128).isqrt() as Fx
+    }
+
+    /// Euclidean norm of a Q16.16 vector
+    pub(crate) fn norm(v: [Fx; 3]) -> Fx {
+        sqrt_wide(v[0] * v[0] + v[1] * v[1] + v[2] * v[2])
+    }
+
+    /// Norm of a Q16.16 vector under a `NAV_METRIC_*` distance metric
+    pub(crate) fn metric_norm(v: [Fx; 3], metric: c_int) -> Fx {
+        match metric {
+            NAV_METRIC_MANHATTAN => v[0].abs() + v[1].abs() + v[2].abs(),
+            NAV_METRIC_CHEBYSHEV => v[0].abs().max(v[1].abs()).max(v[2].abs()),
+            _ => norm(v),
+        }
+    }
+
+    /// cos(x): fold into [0, pi/2], then a 10th-order Taylor series
+    pub(crate) fn cos(x: Fx) -> Fx {
+        let mut a = x.rem_euclid(TWO_PI);
+        if a > PI {
+            a = TWO_PI - a;
+        }
+        let (a, sign) = if a > HALF_PI { (PI - a, -1) } else { (a, 1) };
+        let x2 = mul(a, a);
+        // 1 - x^2/2 (1 - x^2/12 (1 - x^2/30 (1 - x^2/56 (1 - x^2/90))))
+        let mut c = ONE - x2 / 90;
+        for k in [56, 30, 12, 2] {
+            c = ONE - mul(x2, c) / k;
+        }
+        sign * c
+    }
+
+    /// exp(-x) for x >= 0: split off whole multiples of ln 2 as a shift,
+    /// then a 7th-order Taylor series on the remainder in [0, ln 2)
+    pub(crate) fn exp_neg(x: Fx) -> Fx {
+        let k = x / LN_2;
+        let r = x - k * LN_2;
+        // 1 - r (1 - r/2 (1 - r/3 (... (1 - r/7))))
+        let mut e = ONE - r / 7;
+        for k in [6, 5, 4, 3, 2, 1] {
+            e = ONE - mul(r, e) / k;
+        }
+        if k >= 64 {
+            0
+        } else {
+            e >> k
+        }
+    }
+
+    /// Integer twin of `predict_time_to_collision` (all inputs Q16.16)
+    ///
+    /// Solves the same quadratic `a t^2 - 2 b t + c = 0`. Its Q32.32
+    /// coefficients are shifted down together when large, so `b^2` and
+    /// `a c` fit in 128 bits; the root is unchanged by a common scale.
+    pub(crate) fn time_to_collision(rel: [Fx; 3], velocity: [Fx; 3], min_margin: Fx, horizon: Fx) -> Option<Fx> {
+        if horizon <= 0 {
+            return None;
+        }
+        let dot = |u: [Fx; 3], v: [Fx; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+        let speed_sq = dot(velocity, velocity);
+        if speed_sq <= 1 << 9 {
+            return None; // f32::EPSILON in Q32.32, as in the float path
+        }
+        let rel_dot_v = dot(rel, velocity);
+        let margin_sq = min_margin * min_margin;
+
+        let widest = speed_sq.max(rel_dot_v.abs()).max((dot(rel, rel) - margin_sq).abs());
+        let shift = (128 - widest.leading_zeros()).saturating_sub(60);
+        let (a, b, c) = (speed_sq >> shift, rel_dot_v >> shift, (dot(rel, rel) - margin_sq) >> shift);
+
+        // Closest approach over the horizon
+        let t_closest = ((b << 16) / a).clamp(0, horizon);
+        let closest = [0, 1, 2].map(|i| rel[i] - mul(velocity[i], t_closest));
+        if dot(closest, closest) >= margin_sq {
+            return None;
+        }
+
+        let disc = (b * b - a * c).max(0);
+        Some((((b - sqrt_wide(disc)) << 16) / a).max(0))
+    }
+}
+
+/// Fixed-point counterpart of `evaluate` for point obstacles
+///
+/// None when any input falls outside the Q16.16 range.
+fn evaluate_fixed(state: &State7D, params: &RigorParams, obstacles: &[c_float]) -> Option<VerificationResultFixed> {
+    use fixed::{from_f32 as q, metric_norm, mul, norm, Fx};
+
+    let planar = params.planar_mode != 0;
+    let vector = |v: [c_float; 3]| -> Option<[Fx; 3]> {
+        Some([q(v[0])?, q(v[1])?, if planar { 0 } else { q(v[2])? }])
+    };
+    let position = vector(state.position)?;
+    let velocity = vector(state.velocity)?;
+    let origin = vector(params.reference_origin)?;
+    let min_margin = q(params.min_margin)?;
+    let horizon = q(params.horizon_secs)?;
+    let certainty = q(state.certainty)?;
+    let fatigue = q(state.fatigue)?;
+
+    // Terms, as in `evaluate_indexed`
+    let pos_norm = metric_norm([0, 1, 2].map(|i| position[i] - origin[i]), params.distance_metric);
+    let period = if params.time_period == 0 { DEFAULT_TIME_PERIOD } else { params.time_period };
+    let t_phase = (((state.timestamp % period) as Fx) << 16) / period as Fx;
+    let g_gradient = if planar { 0 } else { mul(position[1], fixed::cos(q(state.heading)?)) / 10 };
+    let i_intent = if params.speed_confidence_decay <= 0.0 {
+        certainty
+    } else {
+        mul(certainty, fixed::exp_neg(mul(q(params.speed_confidence_decay)?, norm(velocity))))
+    };
+    let c_consciousness = fatigue;
+
+    let mut p_score = 0;
+    for (w, term) in params.weights.iter().zip([pos_norm, t_phase, g_gradient, i_intent, c_consciousness]) {
+        p_score += mul(q(*w)?, term);
+    }
+
+    // Obstacles: same scan order and early exit as the float path
+    let mut breach_flags: c_ulonglong = 0;
+    let mut margin = Fx::MAX;
+    let mut time_to_collision = Fx::MAX;
+    let mut closest_obstacle_index: c_int = -1;
+    for (i, obstacle) in obstacles.chunks_exact(OBSTACLE_STRIDE_POINT).enumerate() {
+        let rel = {
+            let obstacle = vector([obstacle[0], obstacle[1], obstacle[2]])?;
+            [0, 1, 2].map(|k| obstacle[k] - position[k])
+        };
+        let obstacle_margin = metric_norm(rel, params.distance_metric) - min_margin;
+        if obstacle_margin < margin {
+            margin = obstacle_margin;
+            closest_obstacle_index = i as c_int;
+        }
+        if obstacle_margin < 0 {
+            breach_flags |= BREACH_FLAG_VNC_VIOLATION;
+            time_to_collision = 0;
+            break;
+        }
+        if let Some(ttc) = fixed::time_to_collision(rel, velocity, min_margin, horizon) {
+            breach_flags |= BREACH_FLAG_PREDICTED_COLLISION;
+            time_to_collision = time_to_collision.min(ttc);
+        }
+    }
+    if params.max_decel > 0.0 && breach_flags & BREACH_FLAG_VNC_VIOLATION == 0 && margin < Fx::MAX {
+        // v^2 / (2a), with the division rescaled to Q16.16
+        let speed = norm(velocity);
+        let stopping = (mul(speed, speed) << 16) / (2 * q(params.max_decel)?.max(1));
+        if margin < stopping {
+            breach_flags |= BREACH_FLAG_STOPPING_DISTANCE;
+        }
+    }
+    if fatigue < q(FATIGUE_LIMIT)? {
+        breach_flags |= BREACH_FLAG_FATIGUE;
+    }
+    if i_intent < q(CERTAINTY_LIMIT)? {
+        breach_flags |= BREACH_FLAG_LOW_CERTAINTY;
+    }
+
+    Some(VerificationResultFixed {
+        p_score: fixed::saturate(p_score),
+        is_safe: (params.hard_breach_flags(breach_flags) == 0) as c_int,
+        margin: fixed::saturate(margin),
+        time_to_collision: fixed::saturate(time_to_collision),
+        closest_obstacle_index,
+        breach_flags,
+    })
+}
+
+/// Calculate P-score in Q16.16 fixed point, bit-identical on every platform
+///
+/// For lockstep simulations whose determinism checks can't tolerate the
+/// float path's x86/ARM differences. Each float input is quantized once to
+/// Q16.16 (`round(v * 65536)`, exact and platform-independent); everything
+/// after that is integer arithmetic. Terms, breach flags and planar/horizon
+/// handling follow `calculate_p_score` for `[x, y, z]` obstacles, but no
+/// evidence hash or breach reason is built, nothing is logged and the
+/// breach callback does not fire. A heightmap registered with
+/// `nav_set_heightmap` is ignored: the gradient term always uses the linear
+/// `y * 0.1` approximation.
+///
+/// Precision limits:
+/// - Inputs are quantized to 2^-16 (~1.5e-5) and must lie within +-32767.99;
+///   anything larger returns `OutOfRange`.
+/// - `cos(heading)` and the speed discount's `exp` are series
+///   approximations accurate to a few 2^-16 steps.
+/// - With inputs within +-100 and unit weights, score and margin agree with
+///   the float path to `NAV_FIXED_TOLERANCE` (1/1024). The error grows with
+///   the weights and with `position.y` (the gradient term scales it).
+/// - The limits are quantized too, so a value within 2^-16 of one (e.g.
+///   certainty exactly 0.5) may flag differently from the float path.
+///
+/// # Safety
+///
+/// Same pointer requirements as `calculate_p_score`, with `result` pointing
+/// to a `VerificationResultFixed`. On error `result` is left untouched.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_fixed(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    result: *mut VerificationResultFixed,
+) -> NavStatus {
+    with_last_error("calculate_p_score_fixed", || {
+        if state.is_null() || params.is_null() || result.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let mut state = *state;
+        let params = *params;
+        let obstacles = match obstacle_slice(&DEFAULT_CONTEXT, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT) {
+            Ok(slice) => slice,
+            Err(status) => return status,
+        };
+        if !state.is_finite() || !params.is_finite() || !obstacles.iter().all(|v| v.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        if let Err(status) = params.check_cognitive(&mut state) {
+            return status;
+        }
+
+        match evaluate_fixed(&state, &params, obstacles) {
+            Some(fixed) => {
+                *result = fixed;
+                NavStatus::Ok
+            }
+            None => NavStatus::OutOfRange,
+        }
+    })
+}
+
+// --- Obstacle Spatial Index ---
+/// Uniform spatial hash over point obstacles, built once per scene
+///
+/// Obstacles are bucketed into square XY columns of `cell_size` (Z is not
+/// bucketed, so the same grid serves planar and 3D queries). A query scans
+/// rings of cells outward from the agent until no unscanned cell can hold
+/// the closest obstacle or anything within the collision reach
+/// (`min_margin + |velocity| * horizon_secs`), so verdicts match the linear
+/// `calculate_p_score` exactly.
+///
+/// Cell-size tradeoff: cells much smaller than the typical reach make a
+/// query walk many empty cells; cells much larger than it put many
+/// irrelevant obstacles in each scanned cell. A cell about the size of the
+/// reach (or of the typical obstacle spacing, if larger) is a good start.
+///
+/// Opaque to C: build with `nav_build_obstacle_grid`, free with
+/// `nav_free_obstacle_grid`. A built grid is immutable and may be queried
+/// from several threads at once.
+pub struct ObstacleGrid {
+    cell_size: c_float,
+    obstacles: Vec<c_float>, // Flat [x, y, z] copy; indices match the input array
+    cells: std::collections::HashMap<(i64, i64), Vec<usize>>,
+    min_cell: (i64, i64),
+    max_cell: (i64, i64),
+    digest: [u8; 32], // SHA-256 of the obstacle array, for evidence hashes
+}
+
+impl ObstacleGrid {
+    fn cell_of(&self, x: c_float, y: c_float) -> (i64, i64) {
+        ((x / self.cell_size).floor() as i64, (y / self.cell_size).floor() as i64)
+    }
+
+    /// Indices (ascending) of every obstacle that can influence the verdict
+    fn candidates(&self, state: &State7D, params: &RigorParams) -> Vec<usize> {
+        let planar = params.planar_mode != 0;
+        let vz = if planar { 0.0 } else { state.velocity[2] };
+        let speed = (state.velocity[0].powi(2) + state.velocity[1].powi(2) + vz.powi(2)).sqrt();
+        let reach = params.min_margin.max(0.0) + if params.horizon_secs > 0.0 { speed * params.horizon_secs } else { 0.0 };
+
+        let (cx, cy) = self.cell_of(state.position[0], state.position[1]);
+        let max_ring = (cx - self.min_cell.0)
+            .abs()
+            .max((cx - self.max_cell.0).abs())
+            .max((cy - self.min_cell.1).abs())
+            .max((cy - self.max_cell.1).abs());
+
+        // Far from a sparse scene the rings are mostly empty; past this many
+        // cell probes a full scan is cheaper
+        let probe_budget = self.cells.len() * 4 + 64;
+        let mut probes = 0;
+
+        let mut found = Vec::new();
+        let mut nearest = c_float::INFINITY;
+        for r in 0..=max_ring {
+            for dx in -r..=r {
+                // Top and bottom rows in full, the sides only at their ends
+                let dys: &[i64] = if dx.abs() == r { &[] } else { &[-r, r] };
+                let full_column = dx.abs() == r;
+                let mut visit = |dy: i64| {
+                    if let Some(indices) = self.cells.get(&(cx + dx, cy + dy)) {
+                        for &i in indices {
+                            let o = &self.obstacles[i * 3..i * 3 + 3];
+                            let dz = if planar { 0.0 } else { state.position[2] - o[2] };
+                            let dist = params.metric_norm([state.position[0] - o[0], state.position[1] - o[1], dz]);
+                            nearest = nearest.min(dist);
+                            found.push(i);
+                        }
+                    }
+                };
+                if full_column {
+                    for dy in -r..=r {
+                        visit(dy);
+                    }
+                    probes += (2 * r + 1) as usize;
+                } else {
+                    for &dy in dys {
+                        visit(dy);
+                    }
+                    probes += dys.len();
+                }
+            }
+
+            if probes > probe_budget {
+                return (0..self.obstacles.len() / 3).collect();
+            }
+            // Every obstacle beyond ring r is at least r cells away in XY
+            // (less a sliver of slack for cell-boundary rounding), and no
+            // distance metric is shorter than the larger XY offset
+            if r > 0 && r as c_float * self.cell_size * 0.999 > nearest.max(reach) {
+                break;
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+}
+
+/// Build a spatial index over `count` `[x, y, z]` obstacles
+///
+/// Returns null if `cell_size` is not a positive finite number, any obstacle
+/// coordinate is NaN/infinite, or `obstacles` is null with `count > 0`. An
+/// empty grid (`count == 0`) is valid. The obstacles are copied, so the input
+/// array may be freed afterwards. See `ObstacleGrid` for choosing `cell_size`.
+///
+/// # Safety
+///
+/// `obstacles` must be null or point to at least `count * 3` floats.
+#[no_mangle]
+pub unsafe extern "C" fn nav_build_obstacle_grid(
+    obstacles: *const c_float,
+    count: usize,
+    cell_size: c_float,
+) -> *mut ObstacleGrid {
+    if !cell_size.is_finite() || cell_size <= 0.0 || (obstacles.is_null() && count > 0) {
+        return ptr::null_mut();
+    }
+    let Ok(obstacles) = obstacle_slice(&DEFAULT_CONTEXT, obstacles, count, OBSTACLE_STRIDE_POINT) else {
+        return ptr::null_mut();
+    };
+    let obstacles = obstacles.to_vec();
+    if !obstacles.iter().all(|v| v.is_finite()) {
+        return ptr::null_mut();
+    }
+
+    let mut grid = ObstacleGrid {
+        cell_size,
+        obstacles: Vec::new(),
+        cells: std::collections::HashMap::new(),
+        min_cell: (0, 0),
+        max_cell: (0, 0),
+        digest: [0; 32],
+    };
+    let mut bounds: Option<((i64, i64), (i64, i64))> = None;
+    for (i, o) in obstacles.chunks_exact(OBSTACLE_STRIDE_POINT).enumerate() {
+        let cell = grid.cell_of(o[0], o[1]);
+        grid.cells.entry(cell).or_default().push(i);
+        bounds = Some(match bounds {
+            Some((lo, hi)) => ((lo.0.min(cell.0), lo.1.min(cell.1)), (hi.0.max(cell.0), hi.1.max(cell.1))),
+            None => (cell, cell),
+        });
+    }
+    if let Some((lo, hi)) = bounds {
+        grid.min_cell = lo;
+        grid.max_cell = hi;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update((obstacles.len() as u64).to_le_bytes());
+    for v in &obstacles {
+        hasher.update(v.to_le_bytes());
+    }
+    grid.digest = hasher.finalize().into();
+    grid.obstacles = obstacles;
+
+    Box::into_raw(Box::new(grid))
+}
+
+/// Free a grid built by `nav_build_obstacle_grid`
+///
+/// # Safety
+///
+/// `grid` must be null or a pointer returned by `nav_build_obstacle_grid`
+/// that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn nav_free_obstacle_grid(grid: *mut ObstacleGrid) {
+    if !grid.is_null() {
+        drop(Box::from_raw(grid));
+    }
+}
+
+/// Calculate P-score against an `ObstacleGrid`, scanning only nearby cells
+///
+/// Produces the same verdict, margin, flags and closest obstacle as
+/// `calculate_p_score` over the grid's obstacle array. The evidence hash
+/// commits to the grid's obstacle digest (computed once at build time)
+/// rather than rehashing every obstacle, so it differs from the linear
+/// path's hash for the same scene.
+///
+/// # Safety
+///
+/// `state`, `params` and `result` must be valid pointers and `grid` a live
+/// grid handle.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_grid(
+    state: *const State7D,
+    params: *const RigorParams,
+    grid: *const ObstacleGrid,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_grid", || {
+        if state.is_null() || params.is_null() || grid.is_null() || result.is_null() {
+            return NavStatus::NullPointer;
+        }
+        let mut state = *state;
+        let params = *params;
+        let grid = &*grid;
+        if !state.is_finite() || !params.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        if let Err(status) = params.check_cognitive(&mut state) {
+            return status;
+        }
+
+        let candidates = grid.candidates(&state, &params);
+        let terrain = DEFAULT_CONTEXT.heightmap();
+        let mut eval = evaluate_indexed(
+            &state,
+            &params,
+            terrain.as_deref(),
+            candidates.iter().map(|&i| {
+                let obstacle = &grid.obstacles[i * 3..i * 3 + 3];
+                (i, obstacle, obstacle_margin(&state, &params, obstacle, OBSTACLE_STRIDE_POINT))
+            }),
+            OBSTACLE_STRIDE_POINT,
+        );
+        DEFAULT_CONTEXT.apply_hysteresis(&mut eval, true);
+        let evidence_hash_hex = compute_evidence_hash(
+            &state,
+            None,
+            &params,
+            EvidenceObstacles::GridDigest(&grid.digest),
+            eval.p_score,
+            eval.is_safe,
+            eval.margin,
+        );
+        *result = build_result(&DEFAULT_CONTEXT, &state, &eval, 0.0, evidence_hash_hex);
+        NavStatus::Ok
+    })
+}
+
+// --- Terrain Heightmap ---
+/// Terrain grid registered with `nav_set_heightmap`
+///
+/// Heights are row-major (`heights[row * width + col]`); column `col`, row
+/// `row` sits at world `(origin[0] + col * cell_size, origin[1] + row *
+/// cell_size)` in x/z. The gradient at every node is precomputed once.
+struct Heightmap {
+    width: usize,
+    height: usize,
+    cell_size: c_float,
+    origin: [c_float; 2],
+    gradient: Vec<[c_float; 2]>, // (dh/dx, dh/dz) per node
+}
+
+impl Heightmap {
+    /// Precompute node gradients by central differences of the neighboring
+    /// cells, one-sided along the edges (zero along a single-cell axis)
+    fn new(heights: &[c_float], width: usize, height: usize, cell_size: c_float, origin: [c_float; 2]) -> Self {
+        let at = |col: usize, row: usize| heights[row * width + col];
+        let diff = |lo: usize, hi: usize, h_lo: c_float, h_hi: c_float| {
+            if hi == lo {
+                0.0
+            } else {
+                (h_hi - h_lo) / ((hi - lo) as c_float * cell_size)
+            }
+        };
+
+        let mut gradient = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                let (left, right) = (col.saturating_sub(1), (col + 1).min(width - 1));
+                let (down, up) = (row.saturating_sub(1), (row + 1).min(height - 1));
+                gradient.push([
+                    diff(left, right, at(left, row), at(right, row)),
+                    diff(down, up, at(col, down), at(col, up)),
+                ]);
+            }
+        }
+        Heightmap { width, height, cell_size, origin, gradient }
+    }
+
+    /// Slope magnitude `|grad h|` (rise over run) at world `(x, z)`
+    fn slope(&self, x: c_float, z: c_float) -> c_float {
+        let g = self.gradient(x, z);
+        (g[0] * g[0] + g[1] * g[1]).sqrt()
+    }
+
+    /// Gradient `(dh/dx, dh/dz)` at world `(x, z)`, pointing uphill
+    ///
+    /// The node gradients are bilinearly interpolated between the four
+    /// surrounding nodes. Points outside the grid are clamped to its edge,
+    /// so terrain beyond the map continues with the border slope.
+    fn gradient(&self, x: c_float, z: c_float) -> [c_float; 2] {
+        let axis = |v: c_float, origin: c_float, nodes: usize| {
+            let t = ((v - origin) / self.cell_size).clamp(0.0, (nodes - 1) as c_float);
+            let lo = (t as usize).min(nodes.saturating_sub(2));
+            let hi = (lo + 1).min(nodes - 1);
+            (lo, hi, t - lo as c_float)
+        };
+        let (c0, c1, fx) = axis(x, self.origin[0], self.width);
+        let (r0, r1, fz) = axis(z, self.origin[1], self.height);
+
+        let node = |col: usize, row: usize| self.gradient[row * self.width + col];
+        let lerp = |a: [c_float; 2], b: [c_float; 2], t: c_float| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+        lerp(lerp(node(c0, r0), node(c1, r0), fx), lerp(node(c0, r1), node(c1, r1), fx), fz)
+    }
+}
+
+/// Register the terrain the gradient term samples (null `data` clears it)
+///
+/// `data` holds `width * height` heights, row-major with rows along Z
+/// (`data[row * width + col]` is the height at world
+/// `x = origin_x + col * cell_size`, `z = origin_z + row * cell_size`). The
+/// data is copied, so the caller may free it on return.
+///
+/// With a heightmap, the gradient term is the local slope `|grad h|` at the
+/// agent's x/z, projected onto its heading (`cos(heading)`, 0 = uphill):
+/// - Node gradients are central finite differences of the neighboring
+///   cells (one-sided on the border) and are bilinearly interpolated
+///   between the four nodes around the agent.
+/// - Outside the grid the position is clamped to the nearest edge.
+///
+/// Without one (the default, and after clearing) the gradient term keeps
+/// the linear approximation `position.y * 0.1`. The term is zero in planar
+/// mode either way, and `calculate_p_score_fixed` never samples the map.
+/// Passing a null `ctx` sets the default context's heightmap, used by the
+/// global `calculate_p_score*` functions.
+///
+/// Returns `EmptyInput` for a zero `width` or `height`, `NonFinite` for
+/// non-finite heights, `cell_size` or origin, and `OutOfRange` for a
+/// `cell_size` that isn't positive or a grid too large to address. On
+/// error the current heightmap is left in place.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle; `data` must be null or
+/// point to at least `width * height` floats.
+#[no_mangle]
+pub unsafe extern "C" fn nav_set_heightmap(
+    ctx: *mut NavContext,
+    data: *const c_float,
+    width: usize,
+    height: usize,
+    cell_size: c_float,
+    origin_x: c_float,
+    origin_z: c_float,
+) -> NavStatus {
+    with_last_error("nav_set_heightmap", || {
+        let ctx = log_context(ctx);
+        if data.is_null() {
+            ctx.session().heightmap = None;
+            return NavStatus::Ok;
+        }
+        if width == 0 || height == 0 {
+            return NavStatus::EmptyInput;
+        }
+        let Some(len) = width.checked_mul(height).filter(|&n| n <= isize::MAX as usize / std::mem::size_of::<c_float>()) else {
+            return NavStatus::OutOfRange;
+        };
+        let heights = std::slice::from_raw_parts(data, len);
+        if !cell_size.is_finite() || !origin_x.is_finite() || !origin_z.is_finite() || !heights.iter().all(|h| h.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        if cell_size <= 0.0 {
+            return NavStatus::OutOfRange;
+        }
+
+        let heightmap = Heightmap::new(heights, width, height, cell_size, [origin_x, origin_z]);
+        ctx.session().heightmap = Some(Arc::new(heightmap));
+        NavStatus::Ok
+    })
+}
+
+// --- Breach Audit Log ---
+/// Breaches each context keeps before overwriting the oldest
+pub const NAV_BREACH_LOG_DEFAULT_CAPACITY: usize = 256;
+
+/// One logged violation, as copied out by `nav_breach_log_get`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BreachRecord {
+    pub timestamp: c_ulonglong,        // State7D timestamp of the breaching state
+    pub breach_flags: c_ulonglong,     // BREACH_FLAG_* bits
+    pub p_score: c_float,
+    pub margin: c_float,
+    pub evidence_hash: [c_char; NAV_EVIDENCE_HASH_MAX_LEN + 1], // NUL-terminated "<algo>:<hex digest>"
+    pub sigma: c_float,                // SIM2VAL++ sigma of the verification (0 unless calculate_p_score_with_uncertainty)
+}
+
+impl BreachRecord {
+    fn new(state: &State7D, eval: &Evaluation, sigma: c_float, evidence_hash_hex: &str) -> Self {
+        let mut evidence_hash = [0 as c_char; NAV_EVIDENCE_HASH_MAX_LEN + 1];
+        for (dst, src) in evidence_hash.iter_mut().zip(evidence_hash_hex.bytes().take(NAV_EVIDENCE_HASH_MAX_LEN)) {
+            *dst = src as c_char;
+        }
+        BreachRecord {
+            timestamp: state.timestamp,
+            breach_flags: eval.breach_flags,
+            p_score: eval.p_score,
+            margin: eval.margin,
+            evidence_hash,
+            sigma,
+        }
+    }
+
+    /// The evidence hash up to its NUL terminator
+    fn evidence_hash_str(&self) -> String {
+        self.evidence_hash.iter().take_while(|&&c| c != 0).map(|&c| c as u8 as char).collect()
+    }
+
+    /// The record as one line of JSON, newline included
+    fn json_line(&self) -> String {
+        let evidence_hash = self.evidence_hash_str();
+        let mut line = serde_json::json!({
+            "timestamp": self.timestamp,
+            "breach_flags": self.breach_flags,
+            "p_score": self.p_score,
+            "margin": self.margin,
+            "evidence_hash": evidence_hash,
+        })
+        .to_string();
+        line.push('\n');
+        line
+    }
+}
+
+/// Fixed-capacity ring buffer of breach records
+struct BreachLog {
+    records: std::collections::VecDeque<BreachRecord>,
+    capacity: usize,
+}
+
+impl BreachLog {
+    const fn new() -> Self {
+        BreachLog {
+            records: std::collections::VecDeque::new(),
+            capacity: NAV_BREACH_LOG_DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Append, dropping the oldest record when full (no-op at capacity 0)
+    fn push(&mut self, record: BreachRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        while self.records.len() > capacity {
+            self.records.pop_front();
+        }
+        self.capacity = capacity;
+    }
+}
+
+/// Resolve a breach-log or heightmap context argument: null means the
+/// default context used by the legacy global functions
+unsafe fn log_context<'a>(ctx: *const NavContext) -> &'a NavContext {
+    if ctx.is_null() {
+        &DEFAULT_CONTEXT
+    } else {
+        &*ctx
+    }
+}
+
+/// Number of breaches currently held in a context's audit log
+///
+/// Every failed verification is recorded in the context it ran in
+/// (`nav_calculate_p_score` uses its `ctx`; the global `calculate_p_score*`
+/// functions use the default context, read by passing null). The log is a
+/// ring buffer: once it holds its capacity (`NAV_BREACH_LOG_DEFAULT_CAPACITY`
+/// unless changed), each new breach overwrites the oldest.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_breach_log_count(ctx: *const NavContext) -> usize {
+    log_context(ctx).session().breach_log.records.len()
+}
+
+/// Copy the breach at `index` (0 = oldest retained) into `record`
+///
+/// Returns `OutOfRange` when `index >= nav_breach_log_count(ctx)`.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle; `record` must be a valid
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nav_breach_log_get(
+    ctx: *const NavContext,
+    index: usize,
+    record: *mut BreachRecord,
+) -> NavStatus {
+    with_last_error("nav_breach_log_get", || {
+        if record.is_null() {
+            return NavStatus::NullPointer;
+        }
+        match log_context(ctx).session().breach_log.records.get(index) {
+            Some(entry) => {
+                *record = *entry;
+                NavStatus::Ok
+            }
+            None => NavStatus::OutOfRange,
+        }
+    })
+}
+
+/// Set how many breaches a context retains (0 disables logging)
+///
+/// Shrinking below the current count drops the oldest records.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_breach_log_set_capacity(ctx: *mut NavContext, capacity: usize) {
+    log_context(ctx).session().breach_log.set_capacity(capacity);
+}
+
+/// Export a context's breach log as CSV (RFC 4180, CRLF line endings)
+///
+/// A header row `timestamp,breach_flags,p_score,margin,sigma,evidence_hash`
+/// followed by one row per retained breach, oldest first. Fields containing
+/// a comma, quote or line break are quoted, with inner quotes doubled. An
+/// empty log yields just the header. Free the string with `free_c_string`.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_breach_log_to_csv(ctx: *const NavContext) -> *mut c_char {
+    let mut csv = String::from("timestamp,breach_flags,p_score,margin,sigma,evidence_hash\r\n");
+    for record in log_context(ctx).session().breach_log.records.iter() {
+        let fields = [
+            record.timestamp.to_string(),
+            record.breach_flags.to_string(),
+            record.p_score.to_string(),
+            record.margin.to_string(),
+            record.sigma.to_string(),
+            record.evidence_hash_str(),
+        ];
+        let row: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    // Number formatting never emits NUL (NaN and inf included), and
+    // evidence_hash_str stops at the record's first NUL
+    CString::new(csv).expect("breach log CSV has no interior NUL").into_raw()
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+// --- Breach Notifications ---
+/// Called with the offending state and its breach-reason string
+///
+/// Both pointers are only valid for the duration of the call and remain owned
+/// by the library: the callback must NOT free them.
+pub type BreachCallback = extern "C" fn(state: *const State7D, breach_reason: *const c_char);
+
+static BREACH_CALLBACK: Mutex<Option<BreachCallback>> = Mutex::new(None);
+
+/// Register a callback fired whenever a verification fails
+/// Passing null clears it and disables notifications. Safe to call
+/// concurrently with verifications.
+#[no_mangle]
+pub extern "C" fn nav_set_breach_callback(cb: Option<BreachCallback>) {
+    *BREACH_CALLBACK.lock().unwrap_or_else(|e| e.into_inner()) = cb;
+}
+
+// Durable breach sink: one JSON line per breach, from every context
+static BREACH_LOG_FILE: Mutex<Option<std::fs::File>> = Mutex::new(None);
+
+/// Append every breach to a file as one JSON line
+///
+/// Each line holds `timestamp`, `breach_flags`, `p_score`, `margin` and
+/// `evidence_hash` (as in `BreachRecord`) and is written with a single
+/// write under a lock, then flushed, so concurrent verifications never
+/// interleave lines and a crash loses at most the breach being written.
+/// Breaches from every context go to the same file, alongside the
+/// in-memory ring buffers. The file is created if missing and appended to
+/// otherwise. A write error drops that line; verification is unaffected.
+///
+/// Passing null closes the current file and disables file logging, as
+/// does `rust_core_shutdown`. Returns `IoError` if the file cannot be opened
+/// (the previous file, if any, stays active) and `OutOfRange` if `path` is
+/// not UTF-8.
+///
+/// # Safety
+///
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn nav_set_breach_log_file(path: *const c_char) -> NavStatus {
+    with_last_error("nav_set_breach_log_file", || {
+        if path.is_null() {
+            *BREACH_LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            return NavStatus::Ok;
+        }
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            return NavStatus::OutOfRange;
+        };
+        match std::fs::OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => {
+                *BREACH_LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+                NavStatus::Ok
+            }
+            Err(_) => NavStatus::IoError,
+        }
+    })
+}
+
+/// Append a breach to the log file, if one is set
+fn write_breach_line(record: &BreachRecord) {
+    use std::io::Write;
+
+    let mut file = BREACH_LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = file.as_mut() {
+        let _ = file.write_all(record.json_line().as_bytes()).and_then(|()| file.flush());
+    }
+}
+
+/// Fire the breach callback, if any, without holding the lock during the call
+fn notify_breach(state: &State7D, breach_reason: *const c_char) {
+    let cb = *BREACH_CALLBACK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cb) = cb {
+        cb(state, breach_reason);
+    }
+}
+
+/// How an evaluation uses its context's latched verdict
+#[derive(Debug, Clone, Copy)]
+enum Latch {
+    Off,    // Batch states belong to different agents
+    Read,   // Hold a latched breach without moving the latch (dry runs)
+    Update, // Hold a latched breach, then latch this verdict
+}
+
+/// Evaluate one validated state in `ctx`, with its terrain and hysteresis
+///
+/// The single evaluator behind a verdict's flags: `verify_state` builds the
+/// FFI result from it and `verify_flags_only` returns its `breach_flags`, so
+/// a dry run reports exactly what the full call would.
+fn evaluate_in(
+    ctx: &NavContext,
+    state: &State7D,
+    params: &RigorParams,
+    obstacles: &[c_float],
+    stride: usize,
+    latch: Latch,
+) -> Evaluation {
+    let terrain = ctx.heightmap();
+    let mut eval = evaluate(state, params, terrain.as_deref(), obstacles, stride);
+    match latch {
+        Latch::Off => {}
+        Latch::Read => ctx.apply_hysteresis(&mut eval, false),
+        Latch::Update => ctx.apply_hysteresis(&mut eval, true),
+    }
+    eval
+}
+
+/// Evaluate one validated state and build its FFI result (allocates the strings)
+///
+/// `sigma` goes into the result and any breach record unchanged.
+fn verify_state(
+    ctx: &NavContext,
+    state: &State7D,
+    params: &RigorParams,
+    obstacles: &[c_float],
+    stride: usize,
+    latch: Latch,
+    sigma: c_float,
+) -> VerificationResult {
+    let eval = evaluate_in(ctx, state, params, obstacles, stride, latch);
+
+    // Evidence hash over every input and the verdict
+    let evidence_hash_hex = compute_evidence_hash(
+        state,
+        None,
+        params,
+        EvidenceObstacles::Raw(obstacles),
+        eval.p_score,
+        eval.is_safe,
+        eval.margin,
+    );
+    build_result(ctx, state, &eval, sigma, evidence_hash_hex)
+}
+
+/// Allocate the FFI strings for an evaluation, log any breach to `ctx` and
+/// fire the breach callback
+fn build_result(
+    ctx: &NavContext,
+    state: &State7D,
+    eval: &Evaluation,
+    sigma: c_float,
+    evidence_hash_hex: String,
+) -> VerificationResult {
+    if eval.is_safe == 0 {
+        let record = BreachRecord::new(state, eval, sigma, &evidence_hash_hex);
+        ctx.session().breach_log.push(record);
+        write_breach_line(&record);
+    }
+
+    // Create result
+    let breach_reason_ptr = CString::new(breach_reason_for(eval.breach_flags)).unwrap().into_raw();
+    let evidence_hash_ptr = CString::new(evidence_hash_hex).unwrap().into_raw();
+
+    if eval.is_safe == 0 {
+        notify_breach(state, breach_reason_ptr);
+    }
+
+    VerificationResult {
+        p_score: eval.p_score,
+        is_safe: eval.is_safe,
+        margin: eval.margin,
+        sigma, // Non-zero only from calculate_p_score_with_uncertainty
+        breach_reason: breach_reason_ptr,
+        evidence_hash: evidence_hash_ptr,
+        time_to_collision: eval.time_to_collision,
+        closest_obstacle_index: eval.closest_obstacle_index,
+        closest_obstacle_pos: eval.closest_obstacle_pos,
+        breach_flags: eval.breach_flags,
+        severity: eval.severity,
+        terms: eval.terms,
+    }
+}
+
+/// Outcome of the Ironclad evaluation before any FFI allocation
+struct Evaluation {
+    p_score: c_float,
+    is_safe: c_int,
+    margin: c_float,
+    breach_flags: c_ulonglong,
+    time_to_collision: c_float,
+    closest_obstacle_index: c_int,
+    closest_obstacle_pos: [c_float; 3],
+    severity: c_int,
+    terms: [c_float; 5], // Weighted terms p_score is summed from
+}
+
+/// Ironclad 7D evaluation over a flat obstacle buffer
+///
+/// `obstacles` holds `stride` floats per obstacle; the first three are the
+/// position, followed by the radius for `OBSTACLE_STRIDE_SIZED` or the
+/// obstacle velocity for `OBSTACLE_STRIDE_DYNAMIC`.
+///
+/// `pos_norm` and obstacle distances use `params.distance_metric`
+/// (`NAV_METRIC_*`, Euclidean by default). In planar mode
+/// (`params.planar_mode != 0`) the Z component is dropped everywhere: with
+/// the Euclidean metric `pos_norm = sqrt(x^2 + y^2)` (relative to
+/// `params.reference_origin`) and obstacle distance is `sqrt(dx^2 + dy^2)`,
+/// the collision prediction ignores `vz`, and the gradient term is zeroed
+/// since its slope input is not planar.
+///
+/// `terrain` supplies the slope for the gradient term; without one it falls
+/// back to the linear `y * 0.1` approximation.
+fn evaluate(
+    state: &State7D,
+    params: &RigorParams,
+    terrain: Option<&Heightmap>,
+    obstacles: &[c_float],
+    stride: usize,
+) -> Evaluation {
+    #[cfg(feature = "simd")]
+    if obstacles.len() / stride >= simd::MIN_OBSTACLES {
+        return evaluate_indexed(state, params, terrain, simd::Margins::new(state, params, obstacles, stride), stride);
+    }
+
+    evaluate_indexed(
+        state,
+        params,
+        terrain,
+        obstacles
+            .chunks_exact(stride)
+            .enumerate()
+            .map(|(i, obstacle)| (i, obstacle, obstacle_margin(state, params, obstacle, stride))),
+        stride,
+    )
+}
+
+/// Sawtooth phase `(timestamp % period) / period`, in `[0, 1)`
+///
+/// A zero period falls back to `DEFAULT_TIME_PERIOD` instead of dividing by
+/// zero. The phase is linear between wraps (each tick adds `1 / period`) and
+/// drops back to 0.0 once per period, so pick a period longer than the
+/// window over which the time term should vary smoothly.
+fn time_phase(timestamp: c_ulonglong, period: c_ulonglong) -> c_float {
+    let period = if period == 0 { DEFAULT_TIME_PERIOD } else { period };
+    (timestamp % period) as c_float / period as c_float
+}
+
+/// Signed clearance from the agent to one obstacle record:
+/// `dist - radius - min_margin`, negative inside the margin, with `dist`
+/// under `params.distance_metric`
+///
+/// The `simd` feature computes the same expression lane-wise with identical
+/// operation order, so both paths give bit-identical margins.
+#[inline]
+fn obstacle_margin(state: &State7D, params: &RigorParams, obstacle: &[c_float], stride: usize) -> c_float {
+    let planar = params.planar_mode != 0;
+    let radius = if stride == OBSTACLE_STRIDE_SIZED { obstacle[3] } else { 0.0 };
+    let dx = state.position[0] - obstacle[0];
+    let dy = state.position[1] - obstacle[1];
+    let dz = if planar { 0.0 } else { state.position[2] - obstacle[2] };
+
+    params.metric_norm([dx, dy, dz]) - radius - params.min_margin
+}
+
+/// `evaluate` over an explicit `(index, record, margin)` sequence, in
+/// ascending index order, so a pre-filtered subset (e.g. from an
+/// `ObstacleGrid`) reports the same closest index as the full array
+fn evaluate_indexed<'a>(
+    state: &State7D,
+    params: &RigorParams,
+    terrain: Option<&Heightmap>,
+    obstacles: impl Iterator<Item = (usize, &'a [c_float], c_float)>,
+    stride: usize,
+) -> Evaluation {
+    let planar = params.planar_mode != 0;
+    let pos_z = if planar { 0.0 } else { state.position[2] };
+    let velocity = if planar {
+        [state.velocity[0], state.velocity[1], 0.0]
+    } else {
+        state.velocity
+    };
+
+    // 1. Calculate "x" (Position Norm) - Distance to the reference origin
+    //    (world origin unless the level recenters it) under the params'
+    //    distance metric
+    let origin = params.reference_origin;
+    let origin_z = if planar { 0.0 } else { origin[2] };
+    let pos_norm = params.metric_norm([
+        state.position[0] - origin[0],
+        state.position[1] - origin[1],
+        pos_z - origin_z,
+    ]);
+
+    // 2. Calculate "t" (Time Phase) - Sawtooth system sync in [0, 1):
+    //    rises by 1/period per tick and wraps to exactly 0.0 at every
+    //    multiple of the period (including timestamp 0)
+    let t_phase = time_phase(state.timestamp, params.time_period);
+    
+    // 3. Calculate "g" (Gradient) - Terrain slope under the agent (or the
+    //    linear approximation without a heightmap), projected onto the
+    //    travel direction: cos(heading) is 1 climbing straight uphill and 0
+    //    when traversing a contour
+    let g_gradient = if planar {
+        0.0
+    } else {
+        let slope = match terrain {
+            Some(terrain) => terrain.slope(state.position[0], state.position[2]),
+            None => state.position[1] * 0.1,
+        };
+        slope * state.heading.cos()
+    };
+
+    // 4. Calculate "i" (Intent) - Model Confidence, discounted by speed
+    let i_intent = params.effective_certainty(state.certainty, velocity);
+
+    // 5. Calculate "c" (Consciousness/Fatigue)
+    let c_consciousness = state.fatigue;
+
+    // 6. Safety Check (The "Ironclad" Constraint)
+    let mut vnc_breach = false;
+    let mut predicted_breach = false;
+    let mut dynamic_breach = false;
+    let mut min_margin_dist = c_float::MAX;
+    let mut time_to_collision = c_float::MAX;
+    let mut closest_obstacle_index: c_int = -1;
+    let mut closest_obstacle_pos = [0.0; 3];
+
+    for (i, obstacle, margin) in obstacles {
+        let obs_x = obstacle[0];
+        let obs_y = obstacle[1];
+        let obs_z = obstacle[2];
+        let radius = if stride == OBSTACLE_STRIDE_SIZED { obstacle[3] } else { 0.0 };
+        let moving = stride == OBSTACLE_STRIDE_DYNAMIC;
+
+        let dx = state.position[0] - obs_x;
+        let dy = state.position[1] - obs_y;
+        let dz = if planar { 0.0 } else { pos_z - obs_z };
+
+        if margin < min_margin_dist {
+            min_margin_dist = margin;
+            closest_obstacle_index = i as c_int;
+            closest_obstacle_pos = [obs_x, obs_y, obs_z];
+        }
+        
+        // Check Breach (If Margin < 0)
+        if margin < 0.0 {
+            vnc_breach = true;
+            time_to_collision = 0.0;
+            break;
+        }
+
+        // Check Predicted Breach within the lookahead horizon, using the
+        // velocity relative to the obstacle when it is moving
+        let relative_velocity = if moving {
+            let obs_vz = if planar { 0.0 } else { obstacle[5] };
+            [velocity[0] - obstacle[3], velocity[1] - obstacle[4], velocity[2] - obs_vz]
+        } else {
+            velocity
+        };
+        if let Some(ttc) = predict_time_to_collision(
+            [-dx, -dy, -dz],
+            relative_velocity,
+            radius + params.min_margin,
+            params.horizon_secs,
+        ) {
+            if moving {
+                dynamic_breach = true;
+            } else {
+                predicted_breach = true;
+            }
+            if ttc < time_to_collision {
+                time_to_collision = ttc;
+            }
+        }
+    }
+
+    let mut breach_flags: c_ulonglong = 0;
+    if vnc_breach {
+        breach_flags |= BREACH_FLAG_VNC_VIOLATION;
+    }
+    if predicted_breach {
+        breach_flags |= BREACH_FLAG_PREDICTED_COLLISION;
+    }
+    if dynamic_breach {
+        breach_flags |= BREACH_FLAG_DYNAMIC_COLLISION;
+    }
+
+    // Clear of every obstacle, but too close to brake to a stop before the
+    // nearest one (the closest obstacle is always scanned, so this holds for
+    // the indexed and SIMD paths too)
+    if !vnc_breach && min_margin_dist < c_float::MAX {
+        let speed = (velocity[0].powi(2) + velocity[1].powi(2) + velocity[2].powi(2)).sqrt();
+        if min_margin_dist < params.stopping_distance(speed) {
+            breach_flags |= BREACH_FLAG_STOPPING_DISTANCE;
+        }
+    }
+
+    // Check fatigue breach
+    if state.fatigue < FATIGUE_LIMIT {
+        breach_flags |= BREACH_FLAG_FATIGUE;
+    }
+
+    // Check certainty breach (against the speed-discounted value)
+    if i_intent < CERTAINTY_LIMIT {
+        breach_flags |= BREACH_FLAG_LOW_CERTAINTY;
+    }
+
+    // --- SUM IT UP (The Formula: P = x + y + z + t + g + i + c) ---
+    // Note: x, y, z are combined into pos_norm; each term is scaled by its weight
+    let w = params.weights;
+    let terms = [
+        w[0] * pos_norm,
+        w[1] * t_phase,
+        w[2] * g_gradient,
+        w[3] * i_intent,
+        w[4] * c_consciousness,
+    ];
+    let p_score = terms[0] + terms[1] + terms[2] + terms[3] + terms[4];
+
+    Evaluation {
+        p_score,
+        is_safe: if params.hard_breach_flags(breach_flags) == 0 { 1 } else { 0 },
+        margin: min_margin_dist,
+        breach_flags,
+        time_to_collision,
+        closest_obstacle_index,
+        closest_obstacle_pos,
+        severity: severity_for(breach_flags, min_margin_dist, i_intent, state.fatigue, params),
+        terms,
+    }
+}
+
+/// Vectorized obstacle margins (`simd` feature)
+///
+/// Computes `obstacle_margin` for `LANES` obstacles at a time. When no
+/// collision prediction can fire (horizon disabled, or a stationary agent
+/// among static obstacles), whole blocks that can neither breach nor beat
+/// the closest margin so far are skipped without visiting their lanes, so
+/// `evaluate_indexed` sees only obstacles that can change its result.
+/// Blocks are filled lazily, so a VNC breach still stops the scan early.
+#[cfg(feature = "simd")]
+mod simd {
+    use super::*;
+    use wide::{f32x8, CmpLt};
+
+    const LANES: usize = 8;
+
+    /// Below this many obstacles the gather/setup cost outweighs the gain
+    pub(crate) const MIN_OBSTACLES: usize = 32;
+
+    pub(crate) struct Margins<'a> {
+        obstacles: &'a [c_float],
+        stride: usize,
+        count: usize,
+        planar: bool,
+        metric: c_int,
+        position: [f32x8; 3],
+        min_margin: f32x8,
+        skip_blocks: bool,
+        closest: c_float, // Smallest margin yielded so far
+        block: [c_float; LANES],
+        next: usize, // Index of the next obstacle to yield
+    }
+
+    impl<'a> Margins<'a> {
+        pub(crate) fn new(state: &State7D, params: &RigorParams, obstacles: &'a [c_float], stride: usize) -> Self {
+            let planar = params.planar_mode != 0;
+            // Mirrors the early returns in `predict_time_to_collision`
+            let v = [state.velocity[0], state.velocity[1], if planar { 0.0 } else { state.velocity[2] }];
+            let speed_sq = v[0] * v[0] + v[1] * v[1] + v[2] * v[2];
+            let ttc_impossible =
+                params.horizon_secs <= 0.0 || (stride != OBSTACLE_STRIDE_DYNAMIC && speed_sq <= c_float::EPSILON);
+
+            Margins {
+                obstacles,
+                stride,
+                count: obstacles.len() / stride,
+                planar,
+                metric: params.distance_metric,
+                position: state.position.map(f32x8::splat),
+                min_margin: f32x8::splat(params.min_margin),
+                skip_blocks: ttc_impossible,
+                closest: c_float::MAX,
+                block: [0.0; LANES],
+                next: 0,
+            }
+        }
+
+        /// Margins for obstacles `start..start + LANES` (padding lanes past
+        /// the end are +inf)
+        fn margins(&self, start: usize) -> f32x8 {
+            let mut lanes = [[0.0; LANES]; 4]; // x, y, z, radius
+            let records = self.obstacles[start * self.stride..self.count * self.stride].chunks_exact(self.stride);
+            let mut filled = 0;
+            for (lane, o) in records.take(LANES).enumerate() {
+                lanes[0][lane] = o[0];
+                lanes[1][lane] = o[1];
+                lanes[2][lane] = o[2];
+                if self.stride == OBSTACLE_STRIDE_SIZED {
+                    lanes[3][lane] = o[3];
+                }
+                filled += 1;
+            }
+            for radius in &mut lanes[3][filled..] {
+                *radius = c_float::NEG_INFINITY;
+            }
+
+            let dx = self.position[0] - f32x8::from(lanes[0]);
+            let dy = self.position[1] - f32x8::from(lanes[1]);
+            let dz = if self.planar { f32x8::ZERO } else { self.position[2] - f32x8::from(lanes[2]) };
+
+            let dist = match self.metric {
+                NAV_METRIC_MANHATTAN => dx.abs() + dy.abs() + dz.abs(),
+                NAV_METRIC_CHEBYSHEV => dx.abs().max(dy.abs()).max(dz.abs()),
+                _ => (dx * dx + dy * dy + dz * dz).sqrt(),
+            };
+            dist - f32x8::from(lanes[3]) - self.min_margin
+        }
+    }
+
+    impl<'a> Iterator for Margins<'a> {
+        type Item = (usize, &'a [c_float], c_float);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            while self.next < self.count && self.next % LANES == 0 {
+                let margins = self.margins(self.next);
+                // A lane matters if it breaches or would become the closest
+                let floor = f32x8::splat(self.closest.max(0.0));
+                if self.skip_blocks && !margins.cmp_lt(floor).any() {
+                    self.next += LANES;
+                    continue;
+                }
+                self.block = margins.to_array();
+                self.closest = self.block.iter().fold(self.closest, |a, &b| a.min(b));
+                break;
+            }
+
+            let i = self.next;
+            let obstacle = self.obstacles.get(i * self.stride..(i + 1) * self.stride)?;
+            self.next += 1;
+            Some((i, obstacle, self.block[i % LANES]))
+        }
+    }
+}
+
+/// Predict when an agent moving at constant `velocity` first comes within
+/// `min_margin` of an obstacle at relative position `rel` (obstacle - agent).
+///
+/// Solves `|rel - velocity * t| = min_margin` for the earliest `t` in
+/// `[0, horizon_secs]`. Returns `None` when the agent is stationary, the
+/// horizon is disabled, or the closest approach (at
+/// `t* = (rel . v) / |v|^2`, clamped to the horizon) stays outside the margin,
+/// which also covers parallel/receding motion where `t*` clamps to 0.
+fn predict_time_to_collision(
+    rel: [c_float; 3],
+    velocity: [c_float; 3],
+    min_margin: c_float,
+    horizon_secs: c_float,
+) -> Option<c_float> {
+    if horizon_secs <= 0.0 {
+        return None;
+    }
+
+    let speed_sq = velocity[0] * velocity[0] + velocity[1] * velocity[1] + velocity[2] * velocity[2];
+    if speed_sq <= c_float::EPSILON {
+        return None; // Zero velocity: the static distance check already covers it
+    }
+
+    let rel_dot_v = rel[0] * velocity[0] + rel[1] * velocity[1] + rel[2] * velocity[2];
+    let rel_sq = rel[0] * rel[0] + rel[1] * rel[1] + rel[2] * rel[2];
+
+    // Closest approach over the horizon
+    let t_closest = (rel_dot_v / speed_sq).clamp(0.0, horizon_secs);
+    let cx = rel[0] - velocity[0] * t_closest;
+    let cy = rel[1] - velocity[1] * t_closest;
+    let cz = rel[2] - velocity[2] * t_closest;
+    let closest_sq = cx * cx + cy * cy + cz * cz;
+    if closest_sq >= min_margin * min_margin {
+        return None;
+    }
+
+    // Earliest root of |v|^2 t^2 - 2 (rel . v) t + |rel|^2 - m^2 = 0
+    let disc = rel_dot_v * rel_dot_v - speed_sq * (rel_sq - min_margin * min_margin);
+    let t_enter = (rel_dot_v - disc.max(0.0).sqrt()) / speed_sq;
+    Some(t_enter.max(0.0))
+}
+
+/// How the obstacle set enters the evidence hash
+enum EvidenceObstacles<'a> {
+    /// Every float, length-prefixed
+    Raw(&'a [c_float]),
+    /// SHA-256 of the grid's obstacle array, computed once at build time so
+    /// grid queries don't rehash the whole scene
+    GridDigest(&'a [u8; 32]),
+    /// Live `(slot index, record)` pairs of a masked pool, length-prefixed
+    Masked(&'a [(usize, &'a [c_float])]),
+}
+
+/// Evidence hasher for a `NAV_HASH_*` algorithm (unknown values: SHA-256)
+enum EvidenceHasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>), // Boxed: its state is far larger than the SHA-2 ones
+}
+
+impl EvidenceHasher {
+    fn new(hash_algo: c_int) -> Self {
+        match hash_algo {
+            NAV_HASH_SHA512 => EvidenceHasher::Sha512(Sha512::new()),
+            NAV_HASH_BLAKE3 => EvidenceHasher::Blake3(Box::default()),
+            _ => EvidenceHasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: impl AsRef<[u8]>) {
+        match self {
+            EvidenceHasher::Sha256(h) => Digest::update(h, bytes),
+            EvidenceHasher::Sha512(h) => Digest::update(h, bytes),
+            EvidenceHasher::Blake3(h) => {
+                h.update(bytes.as_ref());
+            }
+        }
+    }
+
+    /// `<name>:<lowercase hex digest>`
+    fn finalize_prefixed(self) -> String {
+        let (name, digest) = match self {
+            EvidenceHasher::Sha256(h) => ("sha256", h.finalize().to_vec()),
+            EvidenceHasher::Sha512(h) => ("sha512", h.finalize().to_vec()),
+            EvidenceHasher::Blake3(h) => ("blake3", h.finalize().as_bytes().to_vec()),
+        };
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}:{}", name, hex)
+    }
+}
+
+/// Compute the evidence hash as `<algo>:<lowercase hex digest>`
+///
+/// The algorithm is `params.hash_algo` (`NAV_HASH_*`, SHA-256 by default).
+/// Every field is fed to the hasher in declaration order as little-endian
+/// bytes, so identical inputs always yield the identical hash regardless of
+/// host platform.
+/// For `calculate_p_score_quat` the quaternion is hashed after the state,
+/// so the evidence commits to the full orientation rather than the derived
+/// heading alone.
+fn compute_evidence_hash(
+    state: &State7D,
+    orientation: Option<&[c_float; 4]>,
+    params: &RigorParams,
+    obstacles: EvidenceObstacles<'_>,
+    p_score: c_float,
+    is_safe: c_int,
+    margin: c_float,
+) -> String {
+    let mut hasher = EvidenceHasher::new(params.hash_algo);
+
+    // Inputs: State7D
+    for v in state.position.iter().chain(state.velocity.iter()) {
+        hasher.update(v.to_le_bytes());
+    }
+    hasher.update(state.heading.to_le_bytes());
+    hasher.update(state.timestamp.to_le_bytes());
+    hasher.update(state.certainty.to_le_bytes());
+    hasher.update(state.fatigue.to_le_bytes());
+    if let Some(orientation) = orientation {
+        hasher.update(b"quat");
+        for v in orientation {
+            hasher.update(v.to_le_bytes());
+        }
+    }
+
+    // Inputs: RigorParams
+    hasher.update(params.alpha.to_le_bytes());
+    hasher.update(params.min_margin.to_le_bytes());
+    for w in params.weights.iter() {
+        hasher.update(w.to_le_bytes());
+    }
+    hasher.update(params.horizon_secs.to_le_bytes());
+    hasher.update(params.planar_mode.to_le_bytes());
+    for v in params.reference_origin.iter() {
+        hasher.update(v.to_le_bytes());
+    }
+    hasher.update(params.clamp_cognitive.to_le_bytes());
+    hasher.update(params.time_period.to_le_bytes());
+    hasher.update(params.speed_confidence_decay.to_le_bytes());
+    hasher.update(params.soft_cognitive_breach.to_le_bytes());
+    hasher.update(params.max_decel.to_le_bytes());
+    hasher.update(params.distance_metric.to_le_bytes());
+    hasher.update(params.hash_algo.to_le_bytes());
+
+    // Inputs: obstacles (length-prefixed so the boundary is unambiguous)
+    match obstacles {
+        EvidenceObstacles::Raw(obstacles) => {
+            hasher.update((obstacles.len() as u64).to_le_bytes());
+            for v in obstacles {
+                hasher.update(v.to_le_bytes());
+            }
+        }
+        EvidenceObstacles::GridDigest(digest) => {
+            hasher.update(b"grid");
+            hasher.update(digest);
+        }
+        EvidenceObstacles::Masked(live) => {
+            hasher.update(b"mask");
+            hasher.update((live.len() as u64).to_le_bytes());
+            for (i, obstacle) in live {
+                hasher.update((*i as u64).to_le_bytes());
+                for v in obstacle.iter() {
+                    hasher.update(v.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    // Outputs
+    hasher.update(p_score.to_le_bytes());
+    hasher.update(is_safe.to_le_bytes());
+    hasher.update(margin.to_le_bytes());
+
+    hasher.finalize_prefixed()
+}
+
+/// Serialize a VerificationResult as a JSON object for telemetry
+///
+/// Emits `p_score`, `is_safe` (bool), `margin`, `sigma`, `breach_reason`,
+/// `evidence_hash`, `time_to_collision`, `closest_obstacle_index`,
+/// `closest_obstacle_pos`, `breach_flags`, `severity` and `terms` (array of
+/// the five weighted contributions). Null string pointers become JSON
+/// `null`. Returns a heap string the caller frees with `free_c_string`, or
+/// null if `result` is null.
+///
+/// # Safety
+///
+/// `result` must be null or point to a valid VerificationResult whose string
+/// pointers are null or valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn verification_result_to_json(result: *const VerificationResult) -> *mut c_char {
+    if result.is_null() {
+        return ptr::null_mut();
+    }
+    let result = &*result;
+
+    let c_str_or_null = |p: *mut c_char| {
+        if p.is_null() {
+            serde_json::Value::Null
+        } else {
+            serde_json::Value::String(CStr::from_ptr(p).to_string_lossy().into_owned())
+        }
+    };
+
+    let json = serde_json::json!({
+        "p_score": result.p_score,
+        "is_safe": result.is_safe != 0,
+        "margin": result.margin,
+        "sigma": result.sigma,
+        "breach_reason": c_str_or_null(result.breach_reason),
+        "evidence_hash": c_str_or_null(result.evidence_hash),
+        "time_to_collision": result.time_to_collision,
+        "closest_obstacle_index": result.closest_obstacle_index,
+        "closest_obstacle_pos": result.closest_obstacle_pos,
+        "breach_flags": result.breach_flags,
+        "severity": result.severity,
+        "terms": result.terms,
+    });
+
+    // serde_json never emits interior NUL bytes
+    CString::new(json.to_string()).unwrap().into_raw()
+}
+
+/// Compare two results for golden-file regression tests
+/// Returns 1 if equivalent, 0 otherwise
+///
+/// - `p_score`, `margin` and `sigma` match when `|a - b| <= tol`: an
+///   absolute tolerance in the field's own units, so 0 demands exact
+///   equality (with `0.0 == -0.0`). Identical infinities (e.g. a `FLT_MAX`
+///   or infinite margin) and two NaNs also match.
+/// - `is_safe` must be equal.
+/// - `breach_reason` strings must be byte-equal; two null pointers match,
+///   a null and a non-null one don't.
+///
+/// No other field is compared; in particular `evidence_hash` covers the
+/// exact score bits, so it differs whenever a score moves within `tol`.
+/// Null pointers and a negative or NaN `tol` return 0.
+///
+/// # Safety
+///
+/// `a` and `b` must be null or point to valid VerificationResults whose
+/// `breach_reason` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verification_result_equal(
+    a: *const VerificationResult,
+    b: *const VerificationResult,
+    tol: c_float,
+) -> c_int {
+    if a.is_null() || b.is_null() || tol.is_nan() || tol < 0.0 {
+        return 0;
+    }
+    let (a, b) = (&*a, &*b);
+
+    let close = |x: c_float, y: c_float| x == y || (x.is_nan() && y.is_nan()) || (x - y).abs() <= tol;
+    let reason = |p: *mut c_char| (!p.is_null()).then(|| CStr::from_ptr(p));
+    let equal = close(a.p_score, b.p_score)
+        && close(a.margin, b.margin)
+        && close(a.sigma, b.sigma)
+        && a.is_safe == b.is_safe
+        && reason(a.breach_reason) == reason(b.breach_reason);
+    equal as c_int
+}
+
+/// Free C string allocated by Rust
+/// Caller must call this to prevent memory leaks
+///
+/// # Safety
+///
+/// `ptr` must be null or a pointer previously returned by this library
+/// (e.g. `breach_reason` / `evidence_hash`) that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn free_c_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        let _ = CString::from_raw(ptr);
+    }
+}
+
+/// Free both strings embedded in a VerificationResult and null them
+///
+/// Preferred cleanup for results filled by the `calculate_p_score*`
+/// functions: frees `breach_reason` and `evidence_hash` in one call and
+/// leaves both pointers null, so calling it again (or `free_c_string` on a
+/// field afterwards) is a no-op rather than a double free. Copy any strings
+/// you need before calling it. `free_c_string` remains for standalone
+/// strings such as `verification_result_to_json` output.
+///
+/// # Safety
+///
+/// `result` must be null or point to a VerificationResult whose string
+/// fields are null or were allocated by this library.
+#[no_mangle]
+pub unsafe extern "C" fn free_verification_result(result: *mut VerificationResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = &mut *result;
+    free_c_string(result.breach_reason);
+    free_c_string(result.evidence_hash);
+    result.breach_reason = ptr::null_mut();
+    result.evidence_hash = ptr::null_mut();
+}
+
+/// Calculate SIM2VAL++ uncertainty estimate
+///
+/// Returns `EmptyInput` for `variate_count == 0` and `NonFinite` for NaN/inf
+/// variates.
+/// 
+/// # Safety
+/// 
+/// This function is unsafe because it dereferences raw pointers.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_sim2val_uncertainty(
+    control_variates: *const c_float,
+    variate_count: usize,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    with_last_error("calculate_sim2val_uncertainty", || {
+        if control_variates.is_null() || result_sigma.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if variate_count == 0 {
+            return NavStatus::EmptyInput;
+        }
+
+        let variates = std::slice::from_raw_parts(control_variates, variate_count);
+        if !variates.iter().all(|v| v.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        *result_sigma = sim2val_sigma(variates, false);
+        NavStatus::Ok
+    })
+}
+
+/// Calculate SIM2VAL++ uncertainty using the unbiased sample variance
+///
+/// Same as `calculate_sim2val_uncertainty` but divides by `n - 1` instead of
+/// `n`. Returns `InsufficientSamples` when `variate_count == 1`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// `control_variates` must point to at least `variate_count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_sim2val_uncertainty_sample(
+    control_variates: *const c_float,
+    variate_count: usize,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    with_last_error("calculate_sim2val_uncertainty_sample", || {
+        if control_variates.is_null() || result_sigma.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if variate_count == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if variate_count == 1 {
+            return NavStatus::InsufficientSamples;
+        }
+
+        let variates = std::slice::from_raw_parts(control_variates, variate_count);
+        if !variates.iter().all(|v| v.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        *result_sigma = sim2val_sigma(variates, true);
+        NavStatus::Ok
+    })
+}
+
+// z-score for a two-sided 95% interval, used when the caller passes z = 0
+const SIM2VAL_DEFAULT_Z: c_float = 1.96;
+
+/// Calculate a two-sided SIM2VAL++ confidence interval around the mean
+///
+/// Writes `mean`, `lower = mean - z * sigma / sqrt(n)` and
+/// `upper = mean + z * sigma / sqrt(n)`, where sigma is the same population
+/// standard deviation returned by `calculate_sim2val_uncertainty`. Passing
+/// `z = 0` uses 1.96 (95%). Returns `OutOfRange` for a negative `z` and
+/// `NonFinite` for a non-finite `z` or NaN/inf variates.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// `control_variates` must point to at least `variate_count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_sim2val_interval(
+    control_variates: *const c_float,
+    variate_count: usize,
+    z: c_float,
+    mean: *mut c_float,
+    lower: *mut c_float,
+    upper: *mut c_float,
+) -> NavStatus {
+    with_last_error("calculate_sim2val_interval", || {
+        if control_variates.is_null() || mean.is_null() || lower.is_null() || upper.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if variate_count == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if !z.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        if z < 0.0 {
+            return NavStatus::OutOfRange;
+        }
+        let z = if z == 0.0 { SIM2VAL_DEFAULT_Z } else { z };
+
+        let variates = std::slice::from_raw_parts(control_variates, variate_count);
+        if !variates.iter().all(|v| v.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        let center = sim2val_mean(variates);
+        let half_width = z * sim2val_sigma(variates, false) / (variate_count as c_float).sqrt();
+
+        *mean = center;
+        *lower = center - half_width;
+        *upper = center + half_width;
+        NavStatus::Ok
+    })
+}
+
+/// Calculate a SIM2VAL++ Monte Carlo tail bound (VaR-style)
+///
+/// Sorts a copy of the samples and writes the requested `percentile`
+/// (e.g. 0.95) to `result_var`, linearly interpolating between the two
+/// nearest ranks (`rank = percentile * (n - 1)`). Unlike the sigma functions
+/// this captures tail risk directly.
+///
+/// Returns `EmptyInput` for `sample_count == 0`, `OutOfRange` when
+/// `percentile` is outside `[0, 1]` and `NonFinite` for NaN/inf samples.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// `samples` must point to at least `sample_count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_sim2val_montecarlo(
+    samples: *const c_float,
+    sample_count: usize,
+    percentile: c_float,
+    result_var: *mut c_float,
+) -> NavStatus {
+    with_last_error("calculate_sim2val_montecarlo", || {
+        if samples.is_null() || result_var.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if sample_count == 0 {
+            return NavStatus::EmptyInput;
+        }
+        if !(0.0..=1.0).contains(&percentile) {
+            return NavStatus::OutOfRange;
+        }
+
+        let mut sorted = std::slice::from_raw_parts(samples, sample_count).to_vec();
+        if !sorted.iter().all(|v| v.is_finite()) {
+            return NavStatus::NonFinite;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        *result_var = interpolated_percentile(&sorted, percentile);
+        NavStatus::Ok
+    })
+}
+
+/// Linearly interpolated `percentile` (in `[0, 1]`) of an ascending, non-empty slice
+fn interpolated_percentile(sorted: &[c_float], percentile: c_float) -> c_float {
+    let rank = percentile * (sorted.len() - 1) as c_float;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let frac = rank - lower as c_float;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * frac
+}
+
+/// Calculate a SIM2VAL++ bootstrap sigma for the mean of the control variates
+///
+/// Draws `resamples` resamples (with replacement, each of `variate_count`
+/// values) using the default context's RNG and writes the standard deviation
+/// of their means to `result_sigma`. Deterministic for a given seed; see
+/// `nav_set_rng_seed`.
+///
+/// Returns `InsufficientSamples` for fewer than 2 variates, `OutOfRange` for
+/// `resamples == 0` and `NonFinite` for NaN/inf variates.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+/// `control_variates` must point to at least `variate_count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn calculate_sim2val_bootstrap(
+    control_variates: *const c_float,
+    variate_count: usize,
+    resamples: usize,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    with_last_error("calculate_sim2val_bootstrap", || {
+        sim2val_bootstrap(&DEFAULT_CONTEXT, control_variates, variate_count, resamples, result_sigma)
+    })
+}
+
+/// `calculate_sim2val_bootstrap` drawing from a context's RNG
+///
+/// # Safety
+///
+/// Same requirements as `calculate_sim2val_bootstrap`, plus `ctx` must be
+/// null or a live context handle.
+#[no_mangle]
+pub unsafe extern "C" fn nav_calculate_sim2val_bootstrap(
+    ctx: *const NavContext,
+    control_variates: *const c_float,
+    variate_count: usize,
+    resamples: usize,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    with_last_error("nav_calculate_sim2val_bootstrap", || {
+        if nav_context_check_robustness(ctx) != 1 {
+            return NavStatus::Uninitialized;
+        }
+        sim2val_bootstrap(&*ctx, control_variates, variate_count, resamples, result_sigma)
+    })
+}
+
+unsafe fn sim2val_bootstrap(
+    ctx: &NavContext,
+    control_variates: *const c_float,
+    variate_count: usize,
+    resamples: usize,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    if control_variates.is_null() || result_sigma.is_null() {
+        return NavStatus::NullPointer;
+    }
+    if variate_count < 2 {
+        return NavStatus::InsufficientSamples;
+    }
+    if resamples == 0 {
+        return NavStatus::OutOfRange;
+    }
+    let variates = std::slice::from_raw_parts(control_variates, variate_count);
+    if !variates.iter().all(|v| v.is_finite()) {
+        return NavStatus::NonFinite;
+    }
+
+    // Resample on a copy of the RNG so the session lock isn't held through
+    // `resamples * variate_count` draws. Writing it back keeps sequential
+    // calls on the seeded stream; two bootstraps racing on one context may
+    // draw the same numbers.
+    let mut rng = ctx.session().rng;
+    let means: Vec<c_float> = (0..resamples)
+        .map(|_| {
+            let sum: c_float = (0..variate_count).map(|_| variates[rng.next_index(variate_count)]).sum();
+            sum / variate_count as c_float
+        })
+        .collect();
+    ctx.session().rng = rng;
+
+    *result_sigma = sim2val_sigma(&means, false);
+    NavStatus::Ok
+}
+
+/// Calculate P-score and fill `sigma` from SIM2VAL++ in a single call
+///
+/// Behaves exactly like `calculate_p_score`, then, when `control_variates` is
+/// non-null and `variate_count > 0`, stores the same standard deviation that
+/// `calculate_sim2val_uncertainty` would return in `result.sigma` (and in
+/// the breach log record, if the state breaches). Otherwise sigma stays 0.0.
+/// NaN/inf variates return `NonFinite` before the state is evaluated, so
+/// nothing is logged.
+///
+/// # Safety
+///
+/// Same requirements as `calculate_p_score`, plus:
+/// - `control_variates` is null or points to at least `variate_count` floats
+#[no_mangle]
+pub unsafe extern "C" fn calculate_p_score_with_uncertainty(
+    state: *const State7D,
+    params: *const RigorParams,
+    obstacles: *const c_float,
+    obstacle_count: usize,
+    control_variates: *const c_float,
+    variate_count: usize,
+    result: *mut VerificationResult,
+) -> NavStatus {
+    with_last_error("calculate_p_score_with_uncertainty", || {
+        // Sigma first, so a breach is logged with it
+        let sigma = if !control_variates.is_null() && variate_count > 0 {
+            let variates = std::slice::from_raw_parts(control_variates, variate_count);
+            if !variates.iter().all(|v| v.is_finite()) {
+                return NavStatus::NonFinite;
+            }
+            sim2val_sigma(variates, false)
+        } else {
+            0.0
+        };
+        calculate_p_score_strided(&DEFAULT_CONTEXT, state, params, obstacles, obstacle_count, OBSTACLE_STRIDE_POINT, sigma, result)
+    })
+}
+
+/// Blend a new sigma into the context's exponentially-weighted moving average
+///
+/// `ema = alpha * new_sigma + (1 - alpha) * ema`, with `alpha` in `[0, 1]`
+/// (1 tracks the latest value, 0 freezes the average). The first call on a
+/// context seeds the EMA with `new_sigma`. The smoothed value is written to
+/// `result_sigma`.
+///
+/// The EMA lives in the context, so each agent should use its own handle to
+/// avoid mixing its sigma stream with another's.
+///
+/// Returns `Uninitialized` for a null/uninitialized context, `NonFinite` for
+/// NaN/infinite inputs and `OutOfRange` for a negative sigma or `alpha`
+/// outside `[0, 1]`. On error the stored EMA is unchanged.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context handle; `result_sigma` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sim2val_ema_update(
+    ctx: *const NavContext,
+    new_sigma: c_float,
+    alpha: c_float,
+    result_sigma: *mut c_float,
+) -> NavStatus {
+    with_last_error("sim2val_ema_update", || {
+        if nav_context_check_robustness(ctx) != 1 {
+            return NavStatus::Uninitialized;
+        }
+        if result_sigma.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if !new_sigma.is_finite() || !alpha.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        if new_sigma < 0.0 || !(0.0..=1.0).contains(&alpha) {
+            return NavStatus::OutOfRange;
+        }
+
+        let mut session = (*ctx).session();
+        let smoothed = match session.ema_sigma {
+            Some(ema) => alpha * new_sigma + (1.0 - alpha) * ema,
+            None => new_sigma,
+        };
+        session.ema_sigma = Some(smoothed);
+        *result_sigma = smoothed;
+        NavStatus::Ok
+    })
+}
+
+/// Running SIM2VAL++ sigma over a stream of variates (Welford's algorithm)
+///
+/// Holds only the count, mean and sum of squared deviations (M2), so each
+/// push is O(1) with no allocation and the sigma matches
+/// `calculate_sim2val_uncertainty` over every value pushed since the last
+/// reset. Accumulates in f64 so long streams don't drift.
+#[derive(Debug, Default)]
+pub struct Sim2ValAccumulator {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Sim2ValAccumulator {
+    fn push(&mut self, value: c_float) {
+        let value = value as f64;
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Population sigma (divides by `n`), NaN when empty
+    fn sigma(&self) -> c_float {
+        if self.count == 0 {
+            return c_float::NAN;
+        }
+        (self.m2 / self.count as f64).sqrt() as c_float
+    }
+}
+
+/// Create an empty streaming sigma accumulator
+///
+/// Free it with `sim2val_accum_destroy`. One accumulator per stream; the
+/// handle is not synchronized, so don't push to it from several threads at
+/// once.
+#[no_mangle]
+pub extern "C" fn sim2val_accum_create() -> *mut Sim2ValAccumulator {
+    Box::into_raw(Box::default())
+}
+
+/// Destroy an accumulator created by `sim2val_accum_create`
+///
+/// # Safety
+///
+/// `acc` must be null or a pointer returned by `sim2val_accum_create` that
+/// has not already been destroyed. The handle must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sim2val_accum_destroy(acc: *mut Sim2ValAccumulator) {
+    if !acc.is_null() {
+        drop(Box::from_raw(acc));
+    }
+}
+
+/// Add one variate to the running sigma
+///
+/// Returns `NullPointer` for a null handle and `NonFinite` for NaN/infinity
+/// (the value is not added).
+///
+/// # Safety
+///
+/// `acc` must be null or a live accumulator handle.
+#[no_mangle]
+pub unsafe extern "C" fn sim2val_accum_push(acc: *mut Sim2ValAccumulator, value: c_float) -> NavStatus {
+    with_last_error("sim2val_accum_push", || {
+        if acc.is_null() {
+            return NavStatus::NullPointer;
+        }
+        if !value.is_finite() {
+            return NavStatus::NonFinite;
+        }
+        (*acc).push(value);
+        NavStatus::Ok
+    })
+}
+
+/// Sigma of every variate pushed since creation or the last reset
+///
+/// Same value as `calculate_sim2val_uncertainty` over those variates (to
+/// float rounding). Returns NaN for a null handle or an empty accumulator.
+///
+/// # Safety
+///
+/// `acc` must be null or a live accumulator handle.
+#[no_mangle]
+pub unsafe extern "C" fn sim2val_accum_sigma(acc: *const Sim2ValAccumulator) -> c_float {
+    if acc.is_null() {
+        return c_float::NAN;
+    }
+    (*acc).sigma()
+}
+
+/// Forget every pushed variate, e.g. at the start of the next window
+///
+/// # Safety
+///
+/// `acc` must be null or a live accumulator handle.
+#[no_mangle]
+pub unsafe extern "C" fn sim2val_accum_reset(acc: *mut Sim2ValAccumulator) {
+    if !acc.is_null() {
+        *acc = Sim2ValAccumulator::default();
+    }
+}
+
+/// Arithmetic mean of the control variates
+fn sim2val_mean(variates: &[c_float]) -> c_float {
+    variates.iter().sum::<c_float>() / variates.len() as c_float
+}
+
+/// Standard deviation of the control variates (SIM2VAL++ sigma)
+///
+/// Divides by `n` (population) or, with `sample_variance`, by `n - 1`
+/// (Bessel-corrected). Callers must ensure the denominator is non-zero.
+fn sim2val_sigma(variates: &[c_float], sample_variance: bool) -> c_float {
+    let count = variates.len() as c_float;
+
+    // Calculate mean
+    let mean = sim2val_mean(variates);
+
+    // Calculate variance
+    let variance_sum: c_float = variates.iter().map(|v| (v - mean) * (v - mean)).sum();
+    let denominator = if sample_variance { count - 1.0 } else { count };
+    let variance = variance_sum / denominator;
+
+    // Standard deviation (sigma)
+    variance.sqrt()
+}
+
+/// Safe Rust API over the FFI surface
+///
+/// For Rust consumers linking the crate directly: no raw pointers, owned
+/// results, and errors as `Result`. Calls go through the same FFI functions
+/// (and the default context) as C callers, so breaches are logged and the
+/// breach callback fires exactly as they would from C#.
+pub mod safe {
+    use super::*;
+
+    /// Owned counterpart of `VerificationResult` (nothing to free)
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct VerificationReport {
+        pub p_score: f32,
+        pub is_safe: bool,
+        pub margin: f32,
+        pub sigma: f32,
+        pub breach_reason: String,
+        pub evidence_hash: String,
+        pub time_to_collision: f32,
+        pub closest_obstacle_index: Option<usize>,
+        pub closest_obstacle_pos: [f32; 3],
+        pub breach_flags: u64,
+        pub severity: i32,
+        pub terms: [f32; 5], // Weighted contributions, same order as RigorParams::weights
+    }
+
+    impl VerificationReport {
+        /// Copy the strings out of an FFI result and free it
+        ///
+        /// # Safety
+        ///
+        /// `result` must have been filled by one of the `calculate_p_score*`
+        /// functions and not freed yet.
+        unsafe fn take(result: &mut VerificationResult) -> Self {
+            let owned = |ptr: *mut c_char| {
+                if ptr.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+                }
+            };
+            let report = VerificationReport {
+                p_score: result.p_score,
+                is_safe: result.is_safe != 0,
+                margin: result.margin,
+                sigma: result.sigma,
+                breach_reason: owned(result.breach_reason),
+                evidence_hash: owned(result.evidence_hash),
+                time_to_collision: result.time_to_collision,
+                closest_obstacle_index: usize::try_from(result.closest_obstacle_index).ok(),
+                closest_obstacle_pos: result.closest_obstacle_pos,
+                breach_flags: result.breach_flags,
+                severity: result.severity,
+                terms: result.terms,
+            };
+            free_verification_result(result);
+            report
+        }
+    }
+
+    /// Verify a state against point obstacles (`calculate_p_score`)
+    ///
+    /// Fails with the same `NavStatus` the FFI function would return, e.g.
+    /// `NonFinite` for NaN inputs or `OutOfRange` for unclamped certainty.
+    pub fn verify(
+        state: &State7D,
+        params: &RigorParams,
+        obstacles: &[[f32; 3]],
+    ) -> Result<VerificationReport, NavStatus> {
+        let flat = obstacles.as_flattened();
+        let mut result = VerificationResult::default();
+        // SAFETY: every pointer comes from a live reference or slice, and
+        // `flat` holds exactly `obstacles.len() * 3` floats
+        unsafe {
+            match calculate_p_score(state, params, flat.as_ptr(), obstacles.len(), &mut result) {
+                NavStatus::Ok => Ok(VerificationReport::take(&mut result)),
+                status => Err(status),
+            }
+        }
+    }
+
+    /// `verify` within a session context (`nav_calculate_p_score`)
+    ///
+    /// The breach log, breach log capacity, hysteresis and obstacle cap are
+    /// `ctx`'s own, so a host serving many clients (e.g. the asset server's
+    /// `/verify`) doesn't mix its verdicts into the default context's state.
+    /// Fails with `Uninitialized` for a context that isn't initialized.
+    pub fn verify_in(
+        ctx: &NavContext,
+        state: &State7D,
+        params: &RigorParams,
+        obstacles: &[[f32; 3]],
+    ) -> Result<VerificationReport, NavStatus> {
+        let flat = obstacles.as_flattened();
+        let mut result = VerificationResult::default();
+        // SAFETY: as in `verify`; `ctx` is a live reference
+        unsafe {
+            match nav_calculate_p_score(ctx, state, params, flat.as_ptr(), obstacles.len(), &mut result) {
+                NavStatus::Ok => Ok(VerificationReport::take(&mut result)),
+                status => Err(status),
+            }
+        }
+    }
+
+    /// Verify many states against shared point obstacles
+    /// (`calculate_p_score_batch`), one report per state in order
+    ///
+    /// Inputs are validated up front: on error no state was verified.
+    /// An empty `states` fails with `EmptyInput`.
+    pub fn verify_batch(
+        states: &[State7D],
+        params: &RigorParams,
+        obstacles: &[[f32; 3]],
+    ) -> Result<Vec<VerificationReport>, NavStatus> {
+        let flat = obstacles.as_flattened();
+        let mut results = vec![VerificationResult::default(); states.len()];
+        // SAFETY: `results` has room for one result per state and `flat`
+        // holds exactly `obstacles.len() * 3` floats
+        unsafe {
+            match calculate_p_score_batch(
+                states.as_ptr(),
+                states.len(),
+                params,
+                flat.as_ptr(),
+                obstacles.len(),
+                results.as_mut_ptr(),
+            ) {
+                NavStatus::Ok => Ok(results.iter_mut().map(|result| VerificationReport::take(result)).collect()),
+                status => Err(status),
+            }
+        }
+    }
+}
+
+/// Async wrappers over the safe API (`async` feature)
+///
+/// Verification is CPU-bound: awaiting a large batch inline would stall an
+/// executor thread, and every task scheduled on it, until it finishes.
+/// These functions run the work on tokio's blocking pool instead and hand
+/// back owned reports. They must be awaited inside a tokio runtime. See
+/// `examples/verify_endpoint.rs` for use from an HTTP handler.
+#[cfg(feature = "async")]
+pub mod async_api {
+    use super::safe::{self, VerificationReport};
+    use super::{NavStatus, RigorParams, State7D};
+
+    /// `safe::verify_batch` on the blocking pool
+    ///
+    /// Inputs are taken by value because the blocking task may outlive the
+    /// caller's borrows. A panic during verification resumes in the awaiting
+    /// task.
+    pub async fn verify_batch(
+        states: Vec<State7D>,
+        params: RigorParams,
+        obstacles: Vec<[f32; 3]>,
+    ) -> Result<Vec<VerificationReport>, NavStatus> {
+        let task = tokio::task::spawn_blocking(move || safe::verify_batch(&states, &params, &obstacles));
+        match task.await {
+            Ok(reports) => reports,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Serializes tests that assert on state `rust_core_shutdown` resets
+    // (default context, breach callback, allocation table)
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    fn lock_global_state() -> std::sync::MutexGuard<'static, ()> {
+        GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Baseline inputs: an alert agent at rest at the origin and a 1 m safety
+    // margin. Tests override only the fields they exercise.
+    fn test_state() -> State7D {
+        State7D {
+            position: [0.0, 0.0, 0.0],
+            velocity: [0.0, 0.0, 0.0],
+            heading: 0.0,
+            timestamp: 0,
+            certainty: 0.8,
+            fatigue: 0.9,
+        }
+    }
+
+    fn test_params() -> RigorParams {
+        RigorParams { min_margin: 1.0, ..Default::default() }
+    }
+
+    // A verification that succeeded, with its strings copied out and freed
+    struct Scored {
+        result: VerificationResult,
+        reason: String,
+        hash: String,
+    }
+
+    // P-score over `[x, y, z]` triples in `ctx` (null: `calculate_p_score`
+    // on the default context), which must succeed
+    fn scored(ctx: *const NavContext, state: &State7D, params: *const RigorParams, obstacles: &[c_float]) -> Scored {
+        let mut result = VerificationResult::default();
+        unsafe {
+            let (ptr, count) = (obstacles.as_ptr(), obstacles.len() / 3);
+            let status = if ctx.is_null() {
+                calculate_p_score(state, params, ptr, count, &mut result)
+            } else {
+                nav_calculate_p_score(ctx, state, params, ptr, count, &mut result)
+            };
+            assert_eq!(status, NavStatus::Ok);
+            let reason = CStr::from_ptr(result.breach_reason).to_str().unwrap().to_owned();
+            let hash = CStr::from_ptr(result.evidence_hash).to_str().unwrap().to_owned();
+            free_verification_result(&mut result);
+            Scored { result, reason, hash }
+        }
+    }
+
+    fn p_score(state: &State7D, params: &RigorParams, obstacles: &[c_float]) -> VerificationResult {
+        scored(ptr::null(), state, params, obstacles).result
+    }
+
+    #[test]
+    fn test_rust_core_init() {
+        let _guard = lock_global_state();
+        assert_eq!(rust_core_init(), 1);
+        assert_eq!(check_system_robustness(), 1);
+    }
+
+    #[test]
+    fn test_rust_core_init_checked() {
+        let _guard = lock_global_state();
+        let (state, result, params) = (size_of::<State7D>(), size_of::<VerificationResult>(), size_of::<RigorParams>());
+        rust_core_shutdown();
+
+        // A binding built against an older layout fails at startup, first mismatch first
+        assert_eq!(rust_core_init_checked(state - 4, result, params), NavStatus::StateSizeMismatch);
+        assert_eq!(rust_core_init_checked(state, result + 8, params), NavStatus::ResultSizeMismatch);
+        assert_eq!(rust_core_init_checked(state, result, params - 4), NavStatus::ParamsSizeMismatch);
+        assert_eq!(rust_core_init_checked(0, 0, 0), NavStatus::StateSizeMismatch);
+        assert_eq!(check_system_robustness(), 0);
+
+        assert_eq!(rust_core_init_checked(state, result, params), NavStatus::Ok);
+        assert_eq!(check_system_robustness(), 1);
+        assert_eq!(NavStatus::ParamsSizeMismatch.to_string(), "RigorParams size differs between caller and library");
+    }
+
+    #[test]
+    fn test_rust_core_shutdown_and_reinit() {
+        let _guard = lock_global_state();
+        assert_eq!(rust_core_init(), 1);
+        assert_eq!(rust_core_init(), 1); // Idempotent
+        assert_eq!(check_system_robustness(), 1);
+
+        let mut buffer = [0u8; 16];
+        let alloc = buffer.as_mut_ptr() as *mut c_void;
+        assert_eq!(nav_register_alloc(alloc, 16), NavStatus::Ok);
+        nav_set_rng_seed(7);
+        unsafe {
+            nav_breach_log_set_capacity(ptr::null_mut(), 4);
+            // Raised rather than lowered so unlocked default-context tests still pass
+            assert_eq!(nav_context_set_max_obstacles(ptr::null_mut(), NAV_DEFAULT_MAX_OBSTACLES * 2), NavStatus::Ok);
+        }
+        assert_eq!(DEFAULT_CONTEXT.max_obstacles(), NAV_DEFAULT_MAX_OBSTACLES * 2);
+
+        rust_core_shutdown();
+        assert_eq!(check_system_robustness(), 0);
+        assert_eq!(validate_unity_alloc(alloc, 16), NavStatus::UnknownPointer);
+        assert!(BREACH_CALLBACK.lock().unwrap().is_none());
+        {
+            let session = DEFAULT_CONTEXT.session();
+            assert_eq!(session.rng.0, NAV_DEFAULT_RNG_SEED);
+            assert!(session.breach_log.records.is_empty());
+            assert_eq!(session.breach_log.capacity, NAV_BREACH_LOG_DEFAULT_CAPACITY);
+            assert_eq!(session.max_obstacles, NAV_DEFAULT_MAX_OBSTACLES);
+        }
+        rust_core_shutdown(); // Repeated shutdown is harmless
+
+        assert_eq!(rust_core_init(), 1);
+        assert_eq!(check_system_robustness(), 1);
+    }
+
+    #[test]
+    fn test_calculate_p_score() {
+        rust_core_init();
+        
+        let state = State7D {
+            position: [1.0, 2.0, 3.0],
+            velocity: [0.1, 0.2, 0.3],
+            heading: 45.0,
+            timestamp: 1000,
+            ..test_state()
+        };
+
+        let params = RigorParams {
+            alpha: 5.0,
+            min_margin: 0.5,
+            ..Default::default()
+        };
+
+        let obstacles = [0.0, 0.0, 0.0, 10.0, 10.0, 10.0];
+        let mut result = VerificationResult::default();
+
+        unsafe {
+            let success = calculate_p_score(
+                &state,
+                &params,
+                obstacles.as_ptr(),
+                2,
+                &mut result,
+            );
+
+            assert_eq!(success, NavStatus::Ok);
+            assert!(result.p_score > 0.0);
+            assert!(!result.breach_reason.is_null());
+            assert!(!result.evidence_hash.is_null());
+
+            // Free allocated strings; a second call must be a no-op
+            free_verification_result(&mut result);
+            assert!(result.breach_reason.is_null());
+            assert!(result.evidence_hash.is_null());
+            free_verification_result(&mut result);
+            free_verification_result(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_p_score_weights() {
+        let state = State7D { position: [1.0, 2.0, 3.0], timestamp: 2500, ..test_state() };
+
+        let unit = RigorParams::default();
+        let mut weighted = RigorParams {
+            weights: [0.0, 0.0, 0.0, 2.0, 0.0],
+            ..Default::default()
+        };
+
+        // Unit weights reproduce the plain sum of all five terms
+        let expected = 14.0f32.sqrt() + 0.25 + 0.2 + 0.8 + 0.9;
+        assert!((p_score(&state, &unit, &[]).p_score - expected).abs() < 1e-5);
+
+        // Only the intent term survives, doubled
+        assert!((p_score(&state, &weighted, &[]).p_score - 1.6).abs() < 1e-5);
+
+        // Non-finite weights are rejected
+        weighted.weights[2] = f32::NAN;
+        let mut result = VerificationResult::default();
+        assert_eq!(
+            unsafe { calculate_p_score(&state, &weighted, ptr::null(), 0, &mut result) },
+            NavStatus::NonFinite
+        );
+    }
+
+    #[test]
+    fn test_state7d_lerp() {
+        use std::f32::consts::PI;
+        let a = State7D {
+            position: [0.0, 2.0, -4.0],
+            velocity: [1.0, 0.0, 0.0],
+            heading: 3.0,
+            timestamp: 1000,
+            certainty: 0.9,
+            fatigue: 0.5,
+        };
+        let b = State7D {
+            position: [4.0, 2.0, 0.0],
+            velocity: [3.0, 0.0, 0.0],
+            heading: -3.0,
+            timestamp: 2000,
+            certainty: 0.5,
+            ..test_state()
+        };
+        let lerp = |a: &State7D, b: &State7D, t: f32| unsafe {
+            let mut out = *a;
+            assert_eq!(state7d_lerp(a, b, t, &mut out), NavStatus::Ok);
+            out
+        };
+
+        let quarter = lerp(&a, &b, 0.25);
+        assert_eq!(quarter.position, [1.0, 2.0, -3.0]);
+        assert_eq!(quarter.velocity, [1.5, 0.0, 0.0]);
+        assert_eq!(quarter.timestamp, 1250);
+        assert!((quarter.certainty - 0.8).abs() < 1e-6);
+        assert!((quarter.fatigue - 0.6).abs() < 1e-6);
+
+        // 3.0 -> -3.0 is a 0.28 rad turn through ±π, not 6 rad through 0
+        let arc = 2.0 * PI - 6.0;
+        assert!((quarter.heading - (3.0 + arc * 0.25)).abs() < 1e-5, "{}", quarter.heading);
+        let past_wrap = lerp(&a, &b, 0.75).heading;
+        assert!((past_wrap - (3.0 + arc * 0.75 - 2.0 * PI)).abs() < 1e-5, "{past_wrap}");
+        assert!(past_wrap < -3.0);
+        let midpoint = lerp(&a, &b, 0.5).heading;
+        assert!((midpoint.abs() - PI).abs() < 1e-5, "{midpoint}");
+        // And the same arc backwards
+        let reverse = lerp(&b, &a, 0.25).heading;
+        assert!((reverse - (-3.0 - arc * 0.25)).abs() < 1e-5, "{reverse}");
+        // Away from the wrap it is a plain lerp
+        let plain = lerp(&State7D { heading: -1.0, ..a }, &State7D { heading: 1.0, ..b }, 0.25).heading;
+        assert!((plain + 0.5).abs() < 1e-6);
+
+        // t is clamped: no extrapolation
+        let before = lerp(&a, &b, -1.0);
+        let after = lerp(&a, &b, 2.0);
+        assert_eq!((before.position, before.timestamp), (a.position, a.timestamp));
+        assert_eq!((after.position, after.timestamp), (b.position, b.timestamp));
+        assert!((after.heading - b.heading).abs() < 1e-6);
+
+        unsafe {
+            let mut out = a;
+            assert_eq!(state7d_lerp(ptr::null(), &b, 0.5, &mut out), NavStatus::NullPointer);
+            assert_eq!(state7d_lerp(&a, &b, 0.5, ptr::null_mut()), NavStatus::NullPointer);
+            assert_eq!(state7d_lerp(&a, &b, f32::NAN, &mut out), NavStatus::NonFinite);
+            let bad = State7D { heading: f32::INFINITY, ..b };
+            assert_eq!(state7d_lerp(&a, &bad, 0.5, &mut out), NavStatus::NonFinite);
+            assert_eq!(out.timestamp, a.timestamp);
+        }
+    }
+
+    #[test]
+    fn test_evidence_hash() {
+        let state = State7D {
+            position: [1.0, 2.0, 3.0],
+            velocity: [0.1, 0.2, 0.3],
+            heading: 45.0,
+            timestamp: 1000,
+            ..test_state()
+        };
+        let params = RigorParams {
+            min_margin: 0.5,
+            ..Default::default()
+        };
+        let obstacles = [10.0, 10.0, 10.0];
+
+        let hash_with =
+            |state: &State7D, params: &RigorParams, obstacles: &[f32]| scored(ptr::null(), state, params, obstacles).hash;
+        let hash_of = |state: &State7D, obstacles: &[f32]| hash_with(state, &params, obstacles);
+        let is_hex = |digest: &str| digest.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+
+        let first = hash_of(&state, &obstacles);
+        let digest = first.strip_prefix("sha256:").unwrap();
+        assert_eq!(digest.len(), 64);
+        assert!(is_hex(digest));
+
+        // Deterministic
+        assert_eq!(first, hash_of(&state, &obstacles));
+
+        // Any input change alters the hash
+        let nudged = State7D { heading: f32::from_bits(45.0f32.to_bits() + 1), ..state };
+        assert_ne!(first, hash_of(&nudged, &obstacles));
+        assert_ne!(first, hash_of(&state, &[10.0, 10.0, 10.5]));
+
+        // Each algorithm names itself; SHA-512 digests are twice as long
+        let sha512 = hash_with(&state, &RigorParams { hash_algo: NAV_HASH_SHA512, ..params }, &obstacles);
+        let blake3 = hash_with(&state, &RigorParams { hash_algo: NAV_HASH_BLAKE3, ..params }, &obstacles);
+        let sha512_digest = sha512.strip_prefix("sha512:").unwrap();
+        let blake3_digest = blake3.strip_prefix("blake3:").unwrap();
+        assert_eq!(sha512_digest.len(), 128);
+        assert_eq!(blake3_digest.len(), 64);
+        assert!(is_hex(sha512_digest) && is_hex(blake3_digest));
+        assert_ne!(blake3_digest, digest);
+        assert_eq!(sha512.len(), NAV_EVIDENCE_HASH_MAX_LEN);
+        assert_eq!(sha512, hash_with(&state, &RigorParams { hash_algo: NAV_HASH_SHA512, ..params }, &obstacles));
+        // Unknown values fall back to SHA-256 (but hash the value they carry)
+        assert!(hash_with(&state, &RigorParams { hash_algo: 9, ..params }, &obstacles).starts_with("sha256:"));
+
+        // A breach record holds the longest digest whole
+        let breach = RigorParams { min_margin: 20.0, hash_algo: NAV_HASH_SHA512, ..params };
+        let ctx = nav_context_create();
+        let hash = scored(ctx, &state, &breach, &obstacles).hash;
+        unsafe {
+            let mut record = std::mem::zeroed::<BreachRecord>();
+            assert_eq!(nav_breach_log_get(ctx, 0, &mut record), NavStatus::Ok);
+            assert_eq!(record.evidence_hash_str(), hash);
+            nav_context_destroy(ctx);
+        }
+    }
+
+    #[test]
+    fn test_breach_reason_precedence() {
+        let safe_state = State7D { position: [1.0, 2.0, 3.0], timestamp: 1000, ..test_state() };
+        let params = RigorParams {
+            min_margin: 0.5,
+            ..Default::default()
+        };
+        let far = [10.0, 10.0, 10.0];
+        let near = [1.0, 2.0, 3.2];
+
+        let reason_of = |state: &State7D, obstacles: &[f32]| {
+            let Scored { result, reason, .. } = scored(ptr::null(), state, &params, obstacles);
+            (reason, result.is_safe)
+        };
+
+        assert_eq!(reason_of(&safe_state, &far), ("SAFE".to_owned(), 1));
+
+        let low_certainty = State7D { certainty: 0.2, ..safe_state };
+        assert_eq!(reason_of(&low_certainty, &far), ("LOW_CERTAINTY".to_owned(), 0));
+
+        let tired = State7D { fatigue: 0.1, ..low_certainty };
+        assert_eq!(reason_of(&tired, &far), ("FATIGUE".to_owned(), 0));
+        assert_eq!(reason_of(&tired, &near), ("VNC_VIOLATION".to_owned(), 0));
+    }
+
+    #[test]
+    fn test_predicted_collision() {
+        let state = State7D { velocity: [2.0, 0.0, 0.0], timestamp: 1000, ..test_state() };
+        let params = RigorParams {
+            horizon_secs: 5.0,
+            ..test_params()
+        };
+        // Obstacle dead ahead at x = 5: margin reached at x = 4, i.e. t = 2s
+        let ahead = [5.0, 0.0, 0.0];
+        // Obstacle offset sideways by 3: closest approach stays at 3
+        let beside = [5.0, 3.0, 0.0];
+
+        let verify = |state: &State7D, params: &RigorParams, obstacles: &[f32]| {
+            let Scored { result, reason, .. } = scored(ptr::null(), state, params, obstacles);
+            (reason, result.is_safe, result.time_to_collision)
+        };
+
+        let (reason, is_safe, ttc) = verify(&state, &params, &ahead);
+        assert_eq!((reason.as_str(), is_safe), ("PREDICTED_COLLISION", 0));
+        assert!((ttc - 2.0).abs() < 1e-5);
+
+        let (reason, _, ttc) = verify(&state, &params, &beside);
+        assert_eq!(reason, "SAFE");
+        assert_eq!(ttc, c_float::MAX);
+
+        // Moving away: closest approach never decreases
+        let receding = State7D { velocity: [-2.0, 0.0, 0.0], ..state };
+        assert_eq!(verify(&receding, &params, &ahead).0, "SAFE");
+
+        // Zero velocity falls back to the static distance
+        let stationary = State7D { velocity: [0.0; 3], ..state };
+        assert_eq!(verify(&stationary, &params, &ahead).0, "SAFE");
+
+        // Collision beyond the horizon is ignored
+        let short = RigorParams { horizon_secs: 1.0, ..params };
+        assert_eq!(verify(&state, &short, &ahead).0, "SAFE");
+    }
+
+    #[test]
+    fn test_p_score_with_uncertainty() {
+        let state = State7D { position: [1.0, 2.0, 3.0], timestamp: 1000, ..test_state() };
+        let params = RigorParams::default();
+        let variates = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+        unsafe {
+            let mut expected_sigma = 0.0;
+            assert_eq!(
+                calculate_sim2val_uncertainty(variates.as_ptr(), variates.len(), &mut expected_sigma),
+                NavStatus::Ok
+            );
+            assert!((expected_sigma - 2.0).abs() < 1e-6);
+
+            let mut result = VerificationResult::default();
+            assert_eq!(
+                calculate_p_score_with_uncertainty(
+                    &state,
+                    &params,
+                    ptr::null(),
+                    0,
+                    variates.as_ptr(),
+                    variates.len(),
+                    &mut result,
+                ),
+                NavStatus::Ok
+            );
+            assert_eq!(result.sigma, expected_sigma);
+            free_verification_result(&mut result);
+
+            // No variates keeps sigma at 0.0
+            assert_eq!(
+                calculate_p_score_with_uncertainty(&state, &params, ptr::null(), 0, ptr::null(), 0, &mut result),
+                NavStatus::Ok
+            );
+            assert_eq!(result.sigma, 0.0);
+            free_verification_result(&mut result);
+
+            // A NaN/inf variate is rejected, never turned into a NaN sigma
+            for bad in [f32::NAN, f32::INFINITY] {
+                let variates = [2.0, bad, 4.0];
+                let mut result = VerificationResult::default();
+                assert_eq!(
+                    calculate_p_score_with_uncertainty(&state, &params, ptr::null(), 0, variates.as_ptr(), 3, &mut result),
+                    NavStatus::NonFinite
+                );
+                assert!(result.evidence_hash.is_null());
+                let mut sigma = 0.0;
+                assert_eq!(calculate_sim2val_uncertainty(variates.as_ptr(), 3, &mut sigma), NavStatus::NonFinite);
+                assert_eq!(calculate_sim2val_uncertainty_sample(variates.as_ptr(), 3, &mut sigma), NavStatus::NonFinite);
+                assert_eq!(sigma, 0.0);
+                let (mut mean, mut lower, mut upper) = (0.0, 0.0, 0.0);
+                assert_eq!(
+                    calculate_sim2val_interval(variates.as_ptr(), 3, 0.0, &mut mean, &mut lower, &mut upper),
+                    NavStatus::NonFinite
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sim2val_sample_variance() {
+        // Sum of squared deviations is 32 over 8 values
+        let variates = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut population = 0.0;
+        let mut sample = 0.0;
+
+        unsafe {
+            assert_eq!(calculate_sim2val_uncertainty(variates.as_ptr(), 8, &mut population), NavStatus::Ok);
+            assert_eq!(calculate_sim2val_uncertainty_sample(variates.as_ptr(), 8, &mut sample), NavStatus::Ok);
+            assert!((population - 2.0).abs() < 1e-6);
+            assert!((sample - (32.0f32 / 7.0).sqrt()).abs() < 1e-6);
+            assert!(sample > population);
+
+            // A single variate has no sample variance
+            assert_eq!(
+                calculate_sim2val_uncertainty_sample(variates.as_ptr(), 1, &mut sample),
+                NavStatus::InsufficientSamples
+            );
+            assert_eq!(calculate_sim2val_uncertainty(variates.as_ptr(), 1, &mut population), NavStatus::Ok);
+            assert_eq!(population, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_closest_obstacle() {
+        let state = State7D { timestamp: 1000, ..test_state() };
+        let params = RigorParams {
+            min_margin: 0.5,
+            ..Default::default()
+        };
+        let obstacles = [10.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 7.0];
+
+        let result = p_score(&state, &params, &obstacles);
+        assert_eq!(result.closest_obstacle_index, 1);
+        assert_eq!(result.closest_obstacle_pos, [0.0, 3.0, 0.0]);
+        assert!((result.margin - 2.5).abs() < 1e-6);
+
+        assert_eq!(p_score(&state, &params, &[]).closest_obstacle_index, -1);
+    }
+
+    #[test]
+    fn test_calculate_p_score_sized() {
+        let state = State7D { timestamp: 1000, ..test_state() };
+        let params = RigorParams {
+            min_margin: 0.5,
+            ..Default::default()
+        };
+        // Small obstacle at 3m clears; large obstacle at 4m breaches
+        let sized = [3.0, 0.0, 0.0, 1.0, 0.0, 4.0, 0.0, 3.8];
+
+        unsafe {
+            let mut result = VerificationResult::default();
+            assert_eq!(calculate_p_score_sized(&state, &params, sized.as_ptr(), 1, &mut result), NavStatus::Ok);
+            assert!((result.margin - 1.5).abs() < 1e-6);
+            assert_eq!(result.is_safe, 1);
+            free_verification_result(&mut result);
+
+            assert_eq!(calculate_p_score_sized(&state, &params, sized.as_ptr(), 2, &mut result), NavStatus::Ok);
+            assert_eq!(result.is_safe, 0);
+            assert_eq!(result.closest_obstacle_index, 1);
+            assert!((result.margin - -0.3).abs() < 1e-6);
+            free_verification_result(&mut result);
+        }
+    }
+
+    #[test]
+    fn test_calculate_p_score_obstacles_matches_flat() {
+        let state = State7D {
+            position: [0.5, -1.0, 2.0],
+            velocity: [1.0, 0.0, -0.5],
+            heading: 0.3,
+            timestamp: 1000,
+            ..test_state()
+        };
+        let params = RigorParams {
+            min_margin: 0.5,
+            horizon_secs: 3.0,
+            ..Default::default()
+        };
+        let obstacles = [
+            Obstacle { pos: [3.0, 0.0, 0.0], radius: 1.0 },
+            Obstacle { pos: [0.0, 4.0, 2.0], radius: 3.8 },
+            Obstacle { pos: [-6.0, 1.0, 1.0], radius: 0.0 },
+        ];
+        let flat: Vec<c_float> = obstacles.iter().flat_map(|o| [o.pos[0], o.pos[1], o.pos[2], o.radius]).collect();
+
+        for count in 0..=obstacles.len() {
+            unsafe {
+                let mut typed = VerificationResult::default();
+                let mut legacy = VerificationResult::default();
+                assert_eq!(calculate_p_score_obstacles(&state, &params, obstacles.as_ptr(), count, &mut typed), NavStatus::Ok);
+                assert_eq!(calculate_p_score_sized(&state, &params, flat.as_ptr(), count, &mut legacy), NavStatus::Ok);
+                assert_eq!(typed.p_score.to_bits(), legacy.p_score.to_bits());
+                assert_eq!(typed.margin.to_bits(), legacy.margin.to_bits());
+                assert_eq!(typed.is_safe, legacy.is_safe);
+                assert_eq!(typed.breach_flags, legacy.breach_flags);
+                assert_eq!(typed.closest_obstacle_index, legacy.closest_obstacle_index);
+                assert_eq!(typed.time_to_collision.to_bits(), legacy.time_to_collision.to_bits());
+                assert_eq!(CStr::from_ptr(typed.breach_reason), CStr::from_ptr(legacy.breach_reason));
+                assert_eq!(CStr::from_ptr(typed.evidence_hash), CStr::from_ptr(legacy.evidence_hash));
+                free_verification_result(&mut typed);
+                free_verification_result(&mut legacy);
+            }
+        }
+
+        let bad = [Obstacle { pos: [f32::NAN, 0.0, 0.0], radius: 1.0 }];
+        let mut result = VerificationResult::default();
+        assert_eq!(
+            unsafe { calculate_p_score_obstacles(&state, &params, bad.as_ptr(), 1, &mut result) },
+            NavStatus::NonFinite
+        );
+    }
+
+    #[test]
+    fn test_nav_context() {
+        let state = State7D { timestamp: 1000, ..test_state() };
+        let obstacles = [1.0, 0.0, 0.0];
+        let strict = RigorParams {
+            min_margin: 2.0,
+            ..Default::default()
+        };
+
+        unsafe {
+            let a = nav_context_create();
+            let b = nav_context_create();
+            assert_eq!(nav_context_check_robustness(a), 1);
+            assert_eq!(nav_context_check_robustness(ptr::null()), 0);
+
+            // Contexts hold independent session params
+            assert_eq!(nav_context_set_params(a, &strict), NavStatus::Ok);
+
+            assert_eq!(scored(a, &state, ptr::null(), &obstacles).result.is_safe, 0);
+            assert_eq!(scored(b, &state, ptr::null(), &obstacles).result.is_safe, 1);
+
+            // Null handle is refused
+            let mut result = VerificationResult::default();
+            assert_eq!(
+                nav_calculate_p_score(ptr::null(), &state, &strict, obstacles.as_ptr(), 1, &mut result),
+                NavStatus::Uninitialized
+            );
+
+            nav_context_destroy(a);
+            nav_context_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_nav_status_codes() {
+        let mut sigma = 0.0;
+        let variates = [1.0, 2.0];
+
+        unsafe {
+            assert_eq!(
+                calculate_p_score(ptr::null(), ptr::null(), ptr::null(), 0, ptr::null_mut()),
+                NavStatus::NullPointer
+            );
+            assert_eq!(calculate_sim2val_uncertainty(ptr::null(), 2, &mut sigma), NavStatus::NullPointer);
+            assert_eq!(calculate_sim2val_uncertainty(variates.as_ptr(), 0, &mut sigma), NavStatus::EmptyInput);
+
+            let mut byte = 0u8;
+            let alloc = &mut byte as *mut u8 as *mut c_void;
+            assert_eq!(validate_unity_alloc(ptr::null_mut(), 16), NavStatus::NullPointer);
+            assert_eq!(validate_unity_alloc(alloc, 0), NavStatus::EmptyInput);
+            assert_eq!(validate_unity_alloc(alloc, 2 * 1024 * 1024 * 1024), NavStatus::OutOfRange);
+
+            let msg = std::ffi::CStr::from_ptr(nav_status_message(NavStatus::NonFinite as c_int));
+            assert_eq!(msg.to_str().unwrap(), "Non-finite float input (NaN or infinity)");
+            let unknown = std::ffi::CStr::from_ptr(nav_status_message(99));
+            assert_eq!(unknown.to_str().unwrap(), "Unknown status code");
+        }
+    }
+
+    #[test]
+    fn test_nav_last_error() {
+        let last_error = || unsafe { CStr::from_ptr(nav_last_error()) }.to_str().unwrap().to_string();
+        // Fresh thread: nothing has failed yet
+        std::thread::spawn(move || assert_eq!(last_error(), "")).join().unwrap();
+
+        let mut result = VerificationResult::default();
+        let status = unsafe { calculate_p_score(ptr::null(), ptr::null(), ptr::null(), 0, &mut result) };
+        assert_eq!(status, NavStatus::NullPointer);
+        assert_eq!(last_error(), "calculate_p_score: Null pointer argument");
+
+        // Successful calls leave it alone; the next failure replaces it
+        let state = State7D { certainty: 0.9, ..test_state() };
+        let mut out = state;
+        unsafe {
+            assert_eq!(state7d_lerp(&state, &state, 0.5, &mut out), NavStatus::Ok);
+            assert_eq!(last_error(), "calculate_p_score: Null pointer argument");
+            assert_eq!(state7d_lerp(&state, &state, f32::NAN, &mut out), NavStatus::NonFinite);
+        }
+        assert_eq!(last_error(), "state7d_lerp: Non-finite float input (NaN or infinity)");
+
+        // Thread-local: another thread's failure doesn't show up here
+        std::thread::spawn(move || {
+            assert_eq!(validate_unity_alloc(ptr::null_mut(), 16), NavStatus::NullPointer);
+            assert_eq!(last_error(), "validate_unity_alloc: Null pointer argument");
+        })
+        .join()
+        .unwrap();
+        assert_eq!(last_error(), "state7d_lerp: Non-finite float input (NaN or infinity)");
+    }
+
+    #[test]
+    fn test_validate_rigor_params() {
+        let valid = RigorParams::default();
+        let check = |params: RigorParams| unsafe { validate_rigor_params(&params) };
+        assert_eq!(check(valid), NavStatus::Ok);
+        assert_eq!(check(RigorParams { min_margin: 0.5, horizon_secs: 2.0, time_period: 0, planar_mode: 7, ..valid }), NavStatus::Ok);
+        assert_eq!(check(RigorParams { distance_metric: NAV_METRIC_CHEBYSHEV, ..valid }), NavStatus::Ok);
+        assert_eq!(unsafe { validate_rigor_params(ptr::null()) }, NavStatus::NullPointer);
+
+        for alpha in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(check(RigorParams { alpha, ..valid }), NavStatus::InvalidAlpha, "{alpha}");
+        }
+        for min_margin in [-0.1, f32::NAN] {
+            assert_eq!(check(RigorParams { min_margin, ..valid }), NavStatus::InvalidMinMargin);
+        }
+        for bad in [-1.0, f32::INFINITY] {
+            let mut weights = [1.0; 5];
+            weights[3] = bad;
+            assert_eq!(check(RigorParams { weights, ..valid }), NavStatus::InvalidWeights);
+        }
+        for horizon_secs in [-2.0, f32::NAN] {
+            assert_eq!(check(RigorParams { horizon_secs, ..valid }), NavStatus::InvalidHorizon);
+        }
+        assert_eq!(
+            check(RigorParams { reference_origin: [0.0, f32::NEG_INFINITY, 0.0], ..valid }),
+            NavStatus::InvalidReferenceOrigin
+        );
+        for speed_confidence_decay in [-0.5, f32::NAN] {
+            assert_eq!(check(RigorParams { speed_confidence_decay, ..valid }), NavStatus::InvalidSpeedDecay);
+        }
+        for max_decel in [-1.0, f32::INFINITY] {
+            assert_eq!(check(RigorParams { max_decel, ..valid }), NavStatus::InvalidMaxDecel);
+        }
+        for distance_metric in [-1, 3] {
+            assert_eq!(check(RigorParams { distance_metric, ..valid }), NavStatus::InvalidDistanceMetric);
+        }
+        assert_eq!(check(RigorParams { hash_algo: NAV_HASH_BLAKE3, ..valid }), NavStatus::Ok);
+        assert_eq!(check(RigorParams { hash_algo: 3, ..valid }), NavStatus::InvalidHashAlgo);
+
+        // First bad field in declaration order wins
+        assert_eq!(check(RigorParams { alpha: 0.0, min_margin: -1.0, ..valid }), NavStatus::InvalidAlpha);
+        let msg = unsafe { CStr::from_ptr(nav_status_message(NavStatus::InvalidMinMargin as c_int)) };
+        assert_eq!(msg.to_str().unwrap(), "RigorParams.min_margin must be non-negative and finite");
+    }
+
+    #[test]
+    fn test_non_finite_inputs_rejected() {
+        let state = State7D { position: [1.0, 2.0, 3.0], timestamp: 1000, ..test_state() };
+        let params = RigorParams::default();
+        let obstacles = [10.0, 10.0, 10.0];
+
+        let status_of = |state: &State7D, params: &RigorParams, obstacles: &[f32]| unsafe {
+            let mut result = VerificationResult::default();
+            let status = calculate_p_score(state, params, obstacles.as_ptr(), obstacles.len() / 3, &mut result);
+            if status != NavStatus::Ok {
+                // No verdict was produced
+                assert!(result.breach_reason.is_null());
+                assert!(result.evidence_hash.is_null());
+            }
+            free_verification_result(&mut result);
+            status
+        };
+
+        assert_eq!(status_of(&state, &params, &obstacles), NavStatus::Ok);
+
+        let nan_position = State7D { position: [f32::NAN, 2.0, 3.0], ..state };
+        assert_eq!(status_of(&nan_position, &params, &obstacles), NavStatus::NonFinite);
+
+        let inf_certainty = State7D { certainty: f32::INFINITY, ..state };
+        assert_eq!(status_of(&inf_certainty, &params, &obstacles), NavStatus::NonFinite);
+
+        let inf_margin = RigorParams { min_margin: f32::NEG_INFINITY, ..params };
+        assert_eq!(status_of(&state, &inf_margin, &obstacles), NavStatus::NonFinite);
+
+        assert_eq!(status_of(&state, &params, &[10.0, f32::NAN, 10.0]), NavStatus::NonFinite);
+    }
+
+    #[test]
+    fn test_breach_flags() {
+        let state = State7D { timestamp: 1000, certainty: 0.2, fatigue: 0.1, ..test_state() };
+        let params = test_params();
+        let obstacles = [0.5, 0.0, 0.0];
+
+        let Scored { result, reason, .. } = scored(ptr::null(), &state, &params, &obstacles);
+        assert_eq!(
+            result.breach_flags,
+            BREACH_FLAG_VNC_VIOLATION | BREACH_FLAG_FATIGUE | BREACH_FLAG_LOW_CERTAINTY
+        );
+        assert_eq!(reason, "VNC_VIOLATION");
+
+        let healthy = State7D { certainty: 0.9, fatigue: 0.9, ..state };
+        let result = p_score(&healthy, &params, &[]);
+        assert_eq!(result.breach_flags, 0);
+        assert_eq!(result.is_safe, 1);
+    }
+
+    #[test]
+    fn test_severity_levels() {
+        let state = State7D { timestamp: 1000, certainty: 0.9, ..test_state() };
+        let params = RigorParams {
+            min_margin: 2.0,
+            ..Default::default()
+        };
+        let severity_of =
+            |state: &State7D, obstacle_x: f32| p_score(state, &params, &[obstacle_x, 0.0, 0.0]).severity;
+
+        // Clearance ratio = distance / min_margin
+        assert_eq!(severity_of(&state, 10.0), SEVERITY_SAFE);
+        assert_eq!(severity_of(&state, 2.8), SEVERITY_CAUTION); // 1.4
+        assert_eq!(severity_of(&state, 2.2), SEVERITY_WARNING); // 1.1
+        assert_eq!(severity_of(&state, 1.0), SEVERITY_CRITICAL); // Breach
+
+        // Cognitive limits grade the same way
+        assert_eq!(severity_of(&State7D { certainty: 0.7, ..state }, 10.0), SEVERITY_CAUTION);
+        assert_eq!(severity_of(&State7D { fatigue: 0.35, ..state }, 10.0), SEVERITY_WARNING);
+        assert_eq!(severity_of(&State7D { fatigue: 0.2, ..state }, 10.0), SEVERITY_CRITICAL);
+    }
+
+    #[test]
+    fn test_soft_cognitive_breach() {
+        // Fatigue below FATIGUE_LIMIT
+        let state = State7D { timestamp: 1000, certainty: 0.9, fatigue: 0.2, ..test_state() };
+        let strict = test_params();
+        let soft = RigorParams { soft_cognitive_breach: 1, ..strict };
+        let far = [10.0, 0.0, 0.0];
+        let near = [0.5, 0.0, 0.0];
+
+        unsafe {
+            let ctx = nav_context_create();
+            let verify = |state: &State7D, params: &RigorParams, obstacle: &[c_float; 3]| {
+                let Scored { result, reason, .. } = scored(ctx, state, params, obstacle);
+                (result, reason)
+            };
+
+            // Strict (default): a fatigue dip alone is a hard failure
+            let (result, reason) = verify(&state, &strict, &far);
+            assert_eq!(result.is_safe, 0);
+            assert_eq!(result.breach_flags, BREACH_FLAG_FATIGUE);
+            assert_eq!(result.severity, SEVERITY_CRITICAL);
+            assert_eq!(reason, "FATIGUE");
+            assert_eq!(nav_breach_log_count(ctx), 1);
+
+            // Soft: still flagged and reported, but safe, WARNING and not logged
+            let (result, reason) = verify(&state, &soft, &far);
+            assert_eq!(result.is_safe, 1);
+            assert_eq!(result.breach_flags, BREACH_FLAG_FATIGUE);
+            assert_eq!(result.severity, SEVERITY_WARNING);
+            assert_eq!(reason, "FATIGUE");
+            assert_eq!(nav_breach_log_count(ctx), 1);
+            let low_certainty = State7D { certainty: 0.1, fatigue: 0.9, ..state };
+            let (result, _) = verify(&low_certainty, &soft, &far);
+            assert_eq!((result.is_safe, result.breach_flags), (1, BREACH_FLAG_LOW_CERTAINTY));
+
+            // An obstacle breach is hard in both modes
+            for params in [&strict, &soft] {
+                let (result, reason) = verify(&state, params, &near);
+                assert_eq!(result.is_safe, 0);
+                assert_eq!(result.breach_flags, BREACH_FLAG_VNC_VIOLATION | BREACH_FLAG_FATIGUE);
+                assert_eq!(result.severity, SEVERITY_CRITICAL);
+                assert_eq!(reason, "VNC_VIOLATION");
+            }
+            assert_eq!(nav_breach_log_count(ctx), 3);
+            nav_context_destroy(ctx);
+
+            // The fixed-point path follows the same rule
+            let mut fixed = VerificationResultFixed::default();
+            assert_eq!(calculate_p_score_fixed(&state, &strict, far.as_ptr(), 1, &mut fixed), NavStatus::Ok);
+            assert_eq!(fixed.is_safe, 0);
+            assert_eq!(calculate_p_score_fixed(&state, &soft, far.as_ptr(), 1, &mut fixed), NavStatus::Ok);
+            assert_eq!((fixed.is_safe, fixed.breach_flags), (1, BREACH_FLAG_FATIGUE));
+        }
+    }
+
+    #[test]
+    fn test_calculate_p_score_batch() {
+        let base = State7D { timestamp: 1000, ..test_state() };
+        let states = [
+            base,
+            State7D { position: [5.0, 0.0, 0.0], ..base },
+            State7D { certainty: 0.1, ..base },
+        ];
+        let params = test_params();
+        let obstacles = [5.5, 0.0, 0.0];
+
+        unsafe {
+            let mut results = [VerificationResult::default(); 3];
+            assert_eq!(
+                calculate_p_score_batch(
+                    states.as_ptr(),
+                    states.len(),
+                    &params,
+                    obstacles.as_ptr(),
+                    1,
+                    results.as_mut_ptr(),
+                ),
+                NavStatus::Ok
+            );
+
+            // Each batch entry matches the single-state call
+            for (state, batch) in states.iter().zip(results.iter()) {
+                let single = scored(ptr::null(), state, &params, &obstacles);
+                assert_eq!(batch.p_score, single.result.p_score);
+                assert_eq!(batch.breach_flags, single.result.breach_flags);
+                assert_eq!(CStr::from_ptr(batch.evidence_hash).to_str().unwrap(), single.hash);
+            }
+            assert_eq!(results[0].is_safe, 1);
+            assert_eq!(results[1].breach_flags, BREACH_FLAG_VNC_VIOLATION);
+            assert_eq!(results[2].breach_flags, BREACH_FLAG_LOW_CERTAINTY);
+
+            for result in &mut results {
+                free_verification_result(result);
+            }
+
+            assert_eq!(
+                calculate_p_score_batch(states.as_ptr(), 0, &params, ptr::null(), 0, results.as_mut_ptr()),
+                NavStatus::EmptyInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_p_score_batch_cb() {
+        use std::sync::atomic::AtomicUsize;
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        static BREACHES: AtomicUsize = AtomicUsize::new(0);
+        static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+        extern "C" fn on_result(index: usize, result: *const VerificationResult) {
+            let result = unsafe { &*result };
+            assert_eq!(index, NEXT_INDEX.fetch_add(1, Ordering::SeqCst));
+            assert!(!result.evidence_hash.is_null());
+            if result.is_safe == 0 {
+                BREACHES.fetch_add(1, Ordering::SeqCst);
+            }
+            CALLS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let base = State7D { timestamp: 1000, ..test_state() };
+        // Every fourth agent sits on the obstacle
+        let states: Vec<State7D> = (0..100)
+            .map(|i| State7D { position: [if i % 4 == 0 { -10.0 } else { i as f32 }, 0.0, 0.0], ..base })
+            .collect();
+        let params = RigorParams { min_margin: 0.5, ..Default::default() };
+        let obstacles = [-10.0, 0.0, 0.0];
+
+        unsafe {
+            let batch = |states: &[State7D], cb| {
+                calculate_p_score_batch_cb(states.as_ptr(), states.len(), &params, obstacles.as_ptr(), 1, cb)
+            };
+            assert_eq!(batch(&states, Some(on_result)), NavStatus::Ok);
+            assert_eq!(CALLS.load(Ordering::SeqCst), 100);
+            assert_eq!(BREACHES.load(Ordering::SeqCst), 25);
+
+            // Validation happens before the first callback
+            let mut bad = states.clone();
+            bad[99].certainty = f32::NAN;
+            assert_eq!(batch(&bad, Some(on_result)), NavStatus::NonFinite);
+            assert_eq!(batch(&states[..0], Some(on_result)), NavStatus::EmptyInput);
+            assert_eq!(batch(&states, None), NavStatus::NullPointer);
+            assert_eq!(CALLS.load(Ordering::SeqCst), 100);
+        }
+    }
+
+    #[test]
+    fn test_aggregate_fleet_risk() {
+        let params = test_params();
+        let obstacles = [0.0, 0.0, 0.0];
+        // Clearances -0.5, 1, 3, 5, 7 (one agent in breach)
+        let states: Vec<State7D> = [0.5, 2.0, 4.0, 6.0, 8.0]
+            .iter()
+            .map(|&x| State7D { position: [x, 0.0, 0.0], timestamp: 1000, ..test_state() })
+            .collect();
+        let mut results = vec![VerificationResult::default(); states.len()];
+
+        unsafe {
+            assert_eq!(
+                calculate_p_score_batch(states.as_ptr(), states.len(), &params, obstacles.as_ptr(), 1, results.as_mut_ptr()),
+                NavStatus::Ok
+            );
+            let aggregate = |percentile: f32| {
+                let mut out = 0.0;
+                assert_eq!(aggregate_fleet_risk(results.as_ptr(), results.len(), percentile, &mut out), NavStatus::Ok);
+                out
+            };
+            assert!((aggregate(0.0) + 0.5).abs() < 1e-5);
+            assert!((aggregate(0.5) - 3.0).abs() < 1e-5);
+            assert!((aggregate(1.0) - 7.0).abs() < 1e-5);
+            // Rank 0.2: a fifth of the way from -0.5 to 1, so still in breach
+            assert!((aggregate(0.05) + 0.2).abs() < 1e-5);
+
+            let mut out = 0.0;
+            assert_eq!(aggregate_fleet_risk(results.as_ptr(), 0, 0.05, &mut out), NavStatus::EmptyInput);
+            assert_eq!(aggregate_fleet_risk(results.as_ptr(), results.len(), 1.5, &mut out), NavStatus::OutOfRange);
+            assert_eq!(aggregate_fleet_risk(results.as_ptr(), results.len(), f32::NAN, &mut out), NavStatus::NonFinite);
+            assert_eq!(aggregate_fleet_risk(ptr::null(), 1, 0.05, &mut out), NavStatus::NullPointer);
+
+            for result in &mut results {
+                free_verification_result(result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_heading_scales_gradient() {
+        let uphill = State7D { position: [0.0, 2.0, 0.0], ..test_state() };
+        let contour = State7D { heading: std::f32::consts::FRAC_PI_2, ..uphill };
+        // Only the gradient term contributes
+        let params = RigorParams {
+            weights: [0.0, 0.0, 1.0, 0.0, 0.0],
+            ..Default::default()
+        };
+
+        let score_of = |state: &State7D| p_score(state, &params, &[]).p_score;
+
+        assert!((score_of(&uphill) - 0.2).abs() < 1e-6);
+        assert!(score_of(&contour).abs() < 1e-6);
+        assert!(score_of(&uphill) > score_of(&contour));
+    }
+
+    #[test]
+    fn test_verification_result_equal() {
+        let state = State7D { position: [1.0, 2.0, 0.0], timestamp: 7, certainty: 0.2, ..test_state() };
+        let params = RigorParams::default();
+        let mut a = VerificationResult::default();
+        let mut b = VerificationResult::default();
+        unsafe {
+            assert_eq!(calculate_p_score(&state, &params, ptr::null(), 0, &mut a), NavStatus::Ok);
+            assert_eq!(calculate_p_score(&state, &params, ptr::null(), 0, &mut b), NavStatus::Ok);
+            // Identical runs (FLT_MAX margins included) match even at zero tolerance
+            assert_eq!(verification_result_equal(&a, &b, 0.0), 1);
+
+            // Score drift is judged by the absolute tolerance
+            b.p_score += 1e-3;
+            assert_eq!(verification_result_equal(&a, &b, 0.0), 0);
+            assert_eq!(verification_result_equal(&a, &b, 1e-2), 1);
+            b.sigma = f32::NAN;
+            assert_eq!(verification_result_equal(&a, &b, 1e-2), 0);
+            a.sigma = f32::NAN;
+            assert_eq!(verification_result_equal(&a, &b, 1e-2), 1);
+
+            // The verdict and reason must match exactly
+            b.is_safe = 1 - b.is_safe;
+            assert_eq!(verification_result_equal(&a, &b, 1.0), 0);
+            b.is_safe = a.is_safe;
+            let reason = b.breach_reason;
+            b.breach_reason = CString::new("OTHER").unwrap().into_raw();
+            assert_eq!(verification_result_equal(&a, &b, 1.0), 0);
+            free_c_string(b.breach_reason);
+            b.breach_reason = ptr::null_mut();
+            assert_eq!(verification_result_equal(&a, &b, 1.0), 0);
+            b.breach_reason = reason;
+            assert_eq!(verification_result_equal(&a, &b, 1.0), 1);
+
+            assert_eq!(verification_result_equal(&a, &b, -1.0), 0);
+            assert_eq!(verification_result_equal(&a, &b, f32::NAN), 0);
+            assert_eq!(verification_result_equal(&a, ptr::null(), 1.0), 0);
+            free_verification_result(&mut a);
+            free_verification_result(&mut b);
+        }
+    }
+
+    #[test]
+    fn test_verification_result_to_json() {
+        let state = State7D { timestamp: 1000, certainty: 0.2, ..test_state() };
+        let params = RigorParams::default();
+
+        unsafe {
+            let mut result = VerificationResult::default();
+            assert_eq!(calculate_p_score(&state, &params, ptr::null(), 0, &mut result), NavStatus::Ok);
+
+            let json_ptr = verification_result_to_json(&result);
+            let json: serde_json::Value = serde_json::from_str(CStr::from_ptr(json_ptr).to_str().unwrap()).unwrap();
+            assert_eq!(json["is_safe"], false);
+            assert_eq!(json["breach_reason"], "LOW_CERTAINTY");
+            assert_eq!(json["evidence_hash"].as_str().unwrap().len(), "sha256:".len() + 64);
+            assert!((json["p_score"].as_f64().unwrap() - result.p_score as f64).abs() < 1e-6);
+            let terms: Vec<f32> = serde_json::from_value(json["terms"].clone()).unwrap();
+            assert_eq!(terms, result.terms);
+            assert!((terms.iter().sum::<f32>() - result.p_score).abs() < 1e-5);
+            free_c_string(json_ptr);
+            free_verification_result(&mut result);
+
+            // Null strings become JSON null
+            let empty = VerificationResult::default();
+            let json_ptr = verification_result_to_json(&empty);
+            let json: serde_json::Value = serde_json::from_str(CStr::from_ptr(json_ptr).to_str().unwrap()).unwrap();
+            assert!(json["breach_reason"].is_null());
+            assert!(json["evidence_hash"].is_null());
+            free_c_string(json_ptr);
+
+            assert!(verification_result_to_json(ptr::null()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_alloc_registry() {
+        let _guard = lock_global_state();
+        let mut buffer = [0u8; 64];
+        let alloc = buffer.as_mut_ptr() as *mut c_void;
+
+        assert_eq!(validate_unity_alloc(alloc, 64), NavStatus::UnknownPointer);
+
+        assert_eq!(nav_register_alloc(alloc, 64), NavStatus::Ok);
+        assert_eq!(validate_unity_alloc(alloc, 64), NavStatus::Ok);
+        assert_eq!(validate_unity_alloc(alloc, 128), NavStatus::SizeMismatch);
+
+        assert_eq!(nav_unregister_alloc(alloc), NavStatus::Ok);
+        assert_eq!(validate_unity_alloc(alloc, 64), NavStatus::UnknownPointer);
+        assert_eq!(nav_unregister_alloc(alloc), NavStatus::UnknownPointer);
+        assert_eq!(nav_register_alloc(ptr::null_mut(), 64), NavStatus::NullPointer);
+    }
+
+    #[test]
+    fn test_version_matches_manifest() {
+        let expected = format!(
+            "{}.{}.{}",
+            NAV_CORE_VERSION_MAJOR, NAV_CORE_VERSION_MINOR, NAV_CORE_VERSION_PATCH
+        );
+        assert_eq!(expected, env!("CARGO_PKG_VERSION"));
+        assert_eq!(nav_core_version(), 0x0000_0200);
+    }
+
+    #[test]
+    fn test_abi_hash_is_stable() {
+        assert_eq!(nav_core_abi_hash(), nav_core_abi_hash());
+        assert_ne!(nav_core_abi_hash(), 0);
+    }
+
+    #[test]
+    fn test_breach_callback() {
+        use std::sync::atomic::AtomicUsize;
+
+        // Unique timestamp so concurrent tests don't count towards this one
+        const MARKER: c_ulonglong = 424_242;
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        extern "C" fn on_breach(state: *const State7D, reason: *const c_char) {
+            unsafe {
+                if (*state).timestamp == MARKER {
+                    assert_eq!(CStr::from_ptr(reason).to_str().unwrap(), "FATIGUE");
+                    CALLS.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let tired = State7D { timestamp: MARKER, certainty: 0.9, fatigue: 0.1, ..test_state() };
+        let rested = State7D { fatigue: 0.9, ..tired };
+        let params = RigorParams::default();
+
+        let verify = |state: &State7D| {
+            p_score(state, &params, &[]);
+        };
+
+        let _guard = lock_global_state();
+        nav_set_breach_callback(Some(on_breach));
+        verify(&tired);
+        verify(&rested);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        nav_set_breach_callback(None);
+        verify(&tired);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_planar_mode_ignores_z() {
+        let low = State7D { position: [3.0, 4.0, 0.0], timestamp: 1000, ..test_state() };
+        let high = State7D { position: [3.0, 4.0, 250.0], ..low };
+        let planar = RigorParams {
+            planar_mode: 1,
+            ..test_params()
+        };
+        let spatial = RigorParams { planar_mode: 0, ..planar };
+        let obstacles = [3.0, 6.0, -40.0];
+
+        let verify = |state: &State7D, params: &RigorParams| {
+            let result = p_score(state, params, &obstacles);
+            (result.p_score, result.margin)
+        };
+
+        let (low_score, low_margin) = verify(&low, &planar);
+        let (high_score, high_margin) = verify(&high, &planar);
+        assert_eq!(low_score, high_score);
+        assert_eq!(low_margin, high_margin);
+        // pos_norm 5 + t_phase 0.1 + gradient 0 + 0.8 + 0.9; margin 2 - 1
+        assert!((low_score - 6.8).abs() < 1e-5);
+        assert!((low_margin - 1.0).abs() < 1e-6);
+
+        assert_ne!(verify(&low, &spatial).0, verify(&high, &spatial).0);
+    }
+
+    #[test]
+    fn test_distance_metric() {
+        let state = State7D { position: [3.0, 4.0, 0.0], ..test_state() };
+        // Both obstacles are 5 away in L2, but 7 and 5 in L1, 4 and 5 in L-inf
+        let diagonal = [0.0, 0.0, 0.0];
+        let axis = [3.0, -1.0, 0.0];
+        let obstacles = [diagonal, axis].concat();
+        let metric = |distance_metric: c_int| RigorParams {
+            weights: [1.0, 0.0, 0.0, 0.0, 0.0], // p_score = pos_norm
+            distance_metric,
+            ..test_params()
+        };
+        let margin = |params: &RigorParams, obstacle: &[c_float]| unsafe {
+            calculate_min_margin(&state, params, obstacle.as_ptr(), 1)
+        };
+        let verify = |params: &RigorParams| {
+            let result = p_score(&state, params, &obstacles);
+            (result.p_score, result.closest_obstacle_index)
+        };
+
+        let euclidean = metric(NAV_METRIC_EUCLIDEAN);
+        assert_eq!(margin(&euclidean, &diagonal), 4.0);
+        assert_eq!(margin(&euclidean, &axis), 4.0);
+        assert_eq!(verify(&euclidean), (5.0, 0)); // Tie: the first obstacle wins
+
+        let manhattan = metric(NAV_METRIC_MANHATTAN);
+        assert_eq!(margin(&manhattan, &diagonal), 6.0);
+        assert_eq!(margin(&manhattan, &axis), 4.0);
+        assert_eq!(verify(&manhattan), (7.0, 1));
+
+        let chebyshev = metric(NAV_METRIC_CHEBYSHEV);
+        assert_eq!(margin(&chebyshev, &diagonal), 3.0);
+        assert_eq!(margin(&chebyshev, &axis), 4.0);
+        assert_eq!(verify(&chebyshev), (4.0, 0));
+
+        // Default is Euclidean, and unknown values fall back to it
+        assert_eq!(RigorParams::default().distance_metric, NAV_METRIC_EUCLIDEAN);
+        assert_eq!(verify(&metric(9)), verify(&euclidean));
+
+        // The fixed-point path applies the same metric
+        for (params, score) in [(euclidean, 5), (manhattan, 7), (chebyshev, 4)] {
+            let mut fixed = VerificationResultFixed::default();
+            unsafe {
+                assert_eq!(calculate_p_score_fixed(&state, &params, obstacles.as_ptr(), 2, &mut fixed), NavStatus::Ok);
+            }
+            assert_eq!(fixed.p_score, score * NAV_FIXED_ONE);
+        }
+
+        // The same offset can breach under one metric and clear another:
+        // (1, 1) is 2 in L1, 1.41 in L2 and 1 in L-inf
+        let near = [4.0, 5.0, 0.0];
+        let tight = |params: RigorParams| margin(&RigorParams { min_margin: 1.5, ..params }, &near);
+        assert!(tight(manhattan) > 0.0);
+        assert!(tight(euclidean) < 0.0);
+        assert!(tight(chebyshev) < 0.0);
+    }
+
+    #[test]
+    fn test_calculate_p_score_dynamic() {
+        let state = State7D { timestamp: 1000, ..test_state() };
+        let params = RigorParams {
+            horizon_secs: 5.0,
+            ..test_params()
+        };
+        // Pedestrian 5m away walking towards the agent at 1 m/s: margin reached at t = 4s
+        let approaching = [5.0, 0.0, 0.0, -1.0, 0.0, 0.0];
+        // Same pedestrian walking away
+        let leaving = [5.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+        let verify = |obstacles: &[f32]| unsafe {
+            let mut result = VerificationResult::default();
+            assert_eq!(
+                calculate_p_score_dynamic(&state, &params, obstacles.as_ptr(), obstacles.len() / 6, &mut result),
+                NavStatus::Ok
+            );
+            let reason = CStr::from_ptr(result.breach_reason).to_str().unwrap().to_owned();
+            free_verification_result(&mut result);
+            (reason, result.breach_flags, result.time_to_collision)
+        };
+
+        let (reason, flags, ttc) = verify(&approaching);
+        assert_eq!(reason, "DYNAMIC_COLLISION");
+        assert_eq!(flags, BREACH_FLAG_DYNAMIC_COLLISION);
+        assert!((ttc - 4.0).abs() < 1e-5);
+
+        assert_eq!(verify(&leaving).0, "SAFE");
+
+        // Stride: the second record is read from floats 6..12
+        let both = [leaving, approaching].concat();
+        assert_eq!(verify(&both).0, "DYNAMIC_COLLISION");
+    }
+
+    #[test]
+    fn test_sim2val_montecarlo() {
+        // 0..=100 shuffled
+        let samples: Vec<f32> = (0..=100).map(|i| ((i * 37) % 101) as f32).collect();
+        let mut var = 0.0;
+
+        unsafe {
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), samples.len(), 0.95, &mut var), NavStatus::Ok);
+            assert!((var - 95.0).abs() < 1e-4);
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), samples.len(), 0.0, &mut var), NavStatus::Ok);
+            assert_eq!(var, 0.0);
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), samples.len(), 1.0, &mut var), NavStatus::Ok);
+            assert_eq!(var, 100.0);
+
+            // Interpolates between ranks
+            let pair = [1.0, 3.0];
+            assert_eq!(calculate_sim2val_montecarlo(pair.as_ptr(), 2, 0.25, &mut var), NavStatus::Ok);
+            assert!((var - 1.5).abs() < 1e-6);
+
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), 0, 0.5, &mut var), NavStatus::EmptyInput);
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), 10, 1.5, &mut var), NavStatus::OutOfRange);
+            assert_eq!(calculate_sim2val_montecarlo(samples.as_ptr(), 10, -0.1, &mut var), NavStatus::OutOfRange);
+        }
+    }
+
+    #[test]
+    fn test_sim2val_ema_update() {
+        let mut sigma = 0.0;
+
+        unsafe {
+            let a = nav_context_create();
+            let b = nav_context_create();
+
+            // First value seeds, later ones blend
+            assert_eq!(sim2val_ema_update(a, 2.0, 0.25, &mut sigma), NavStatus::Ok);
+            assert_eq!(sigma, 2.0);
+            assert_eq!(sim2val_ema_update(a, 6.0, 0.25, &mut sigma), NavStatus::Ok);
+            assert_eq!(sigma, 3.0);
+
+            // Contexts don't share state
+            assert_eq!(sim2val_ema_update(b, 10.0, 0.5, &mut sigma), NavStatus::Ok);
+            assert_eq!(sigma, 10.0);
+            assert_eq!(sim2val_ema_update(a, 3.0, 1.0, &mut sigma), NavStatus::Ok);
+            assert_eq!(sigma, 3.0);
+
+            // Rejected updates leave the average untouched
+            assert_eq!(sim2val_ema_update(a, 1.0, 1.5, &mut sigma), NavStatus::OutOfRange);
+            assert_eq!(sim2val_ema_update(a, -1.0, 0.5, &mut sigma), NavStatus::OutOfRange);
+            assert_eq!(sim2val_ema_update(a, f32::NAN, 0.5, &mut sigma), NavStatus::NonFinite);
+            assert_eq!(sim2val_ema_update(a, 3.0, 0.0, &mut sigma), NavStatus::Ok);
+            assert_eq!(sigma, 3.0);
+
+            assert_eq!(sim2val_ema_update(ptr::null(), 1.0, 0.5, &mut sigma), NavStatus::Uninitialized);
+
+            nav_context_destroy(a);
+            nav_context_destroy(b);
+        }
+    }
+
+    #[test]
+    fn test_sim2val_accumulator_matches_batch() {
+        // Offset sensor-like readings, where naive sum-of-squares loses precision
+        let variates: Vec<f32> = (0..1000).map(|i| 1000.0 + ((i * 37) % 101) as f32 * 0.01).collect();
+
+        unsafe {
+            let acc = sim2val_accum_create();
+            assert!(sim2val_accum_sigma(acc).is_nan());
+            for (n, &v) in variates.iter().enumerate() {
+                assert_eq!(sim2val_accum_push(acc, v), NavStatus::Ok);
+                // Spot-check every prefix against the batch function
+                if n % 97 == 0 || n == variates.len() - 1 {
+                    let mut batch = 0.0;
+                    assert_eq!(calculate_sim2val_uncertainty(variates.as_ptr(), n + 1, &mut batch), NavStatus::Ok);
+                    assert!((sim2val_accum_sigma(acc) - batch).abs() < 1e-3, "n = {}", n + 1);
+                }
+            }
+
+            // Rejected values leave the state untouched
+            let before = sim2val_accum_sigma(acc);
+            assert_eq!(sim2val_accum_push(acc, f32::NAN), NavStatus::NonFinite);
+            assert_eq!(sim2val_accum_sigma(acc), before);
+
+            // Reset starts a fresh window
+            sim2val_accum_reset(acc);
+            assert!(sim2val_accum_sigma(acc).is_nan());
+            for v in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+                sim2val_accum_push(acc, v);
+            }
+            assert_eq!(sim2val_accum_sigma(acc), 2.0);
+
+            assert_eq!(sim2val_accum_push(ptr::null_mut(), 1.0), NavStatus::NullPointer);
+            assert!(sim2val_accum_sigma(ptr::null()).is_nan());
+            sim2val_accum_destroy(acc);
+        }
+    }
+
+    #[test]
+    fn test_sim2val_interval() {
+        let (mut mean, mut lower, mut upper) = (0.0, 0.0, 0.0);
+
+        unsafe {
+            // Constant data: zero width
+            let constant = [3.0; 10];
+            assert_eq!(
+                calculate_sim2val_interval(constant.as_ptr(), 10, 0.0, &mut mean, &mut lower, &mut upper),
+                NavStatus::Ok
+            );
+            assert_eq!((mean, lower, upper), (3.0, 3.0, 3.0));
+
+            // Spread data: mean 5, sigma 2, n 8 -> half width 1.96 * 2 / sqrt(8)
+            let spread = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+            assert_eq!(
+                calculate_sim2val_interval(spread.as_ptr(), 8, 0.0, &mut mean, &mut lower, &mut upper),
+                NavStatus::Ok
+            );
+            let half = 1.96 * 2.0 / 8.0f32.sqrt();
+            assert!((mean - 5.0).abs() < 1e-6);
+            assert!((lower - (5.0 - half)).abs() < 1e-5);
+            assert!((upper - (5.0 + half)).abs() < 1e-5);
+
+            // Explicit z widens the interval proportionally
+            assert_eq!(
+                calculate_sim2val_interval(spread.as_ptr(), 8, 3.92, &mut mean, &mut lower, &mut upper),
+                NavStatus::Ok
+            );
+            assert!((upper - mean - 2.0 * half).abs() < 1e-5);
+
+            assert_eq!(
+                calculate_sim2val_interval(spread.as_ptr(), 8, -1.0, &mut mean, &mut lower, &mut upper),
+                NavStatus::OutOfRange
+            );
+        }
+    }
+
+    #[test]
+    fn test_obstacle_grid_matches_linear() {
+        // Deterministic pseudo-random scene
+        let mut seed: u32 = 12345;
+        let mut next = || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 8) as c_float / (1u32 << 24) as c_float
+        };
+        let obstacles: Vec<c_float> = (0..2000 * 3).map(|_| next() * 200.0 - 100.0).collect();
+
+        unsafe {
+            assert!(nav_build_obstacle_grid(ptr::null(), 5, 1.0).is_null());
+            assert!(nav_build_obstacle_grid(obstacles.as_ptr(), 2000, 0.0).is_null());
+            assert!(nav_build_obstacle_grid(obstacles.as_ptr(), 2000, c_float::NAN).is_null());
+            let bad = [0.0, c_float::INFINITY, 0.0];
+            assert!(nav_build_obstacle_grid(bad.as_ptr(), 1, 1.0).is_null());
+            nav_free_obstacle_grid(ptr::null_mut());
+
+            for cell_size in [0.5, 4.0, 50.0] {
+                let grid = nav_build_obstacle_grid(obstacles.as_ptr(), 2000, cell_size);
+                assert!(!grid.is_null());
+
+                for k in 0..60 {
+                    let state = State7D {
+                        position: [next() * 300.0 - 150.0, next() * 300.0 - 150.0, next() * 20.0 - 10.0],
+                        velocity: [next() * 10.0 - 5.0, next() * 10.0 - 5.0, next() - 0.5],
+                        timestamp: k,
+                        certainty: 0.9,
+                        ..test_state()
+                    };
+                    let params = RigorParams {
+                        min_margin: next() * 3.0,
+                        horizon_secs: if k % 2 == 0 { 0.0 } else { 2.0 },
+                        planar_mode: (k % 3 == 0) as c_int,
+                        distance_metric: (k / 2 % 3) as c_int,
+                        ..Default::default()
+                    };
+
+                    let linear = p_score(&state, &params, &obstacles);
+                    let mut indexed = VerificationResult::default();
+                    assert_eq!(calculate_p_score_grid(&state, &params, grid, &mut indexed), NavStatus::Ok);
+
+                    assert_eq!(indexed.is_safe, linear.is_safe);
+                    assert_eq!(indexed.margin, linear.margin);
+                    assert_eq!(indexed.p_score, linear.p_score);
+                    assert_eq!(indexed.time_to_collision, linear.time_to_collision);
+                    assert_eq!(indexed.closest_obstacle_index, linear.closest_obstacle_index);
+                    assert_eq!(indexed.breach_flags, linear.breach_flags);
+                    assert_eq!(indexed.severity, linear.severity);
+
+                    free_verification_result(&mut indexed);
+                }
+                nav_free_obstacle_grid(grid);
+            }
+
+            // Empty grid: nothing to hit
+            let grid = nav_build_obstacle_grid(ptr::null(), 0, 1.0);
+            assert!(!grid.is_null());
+            let mut result = VerificationResult::default();
+            assert_eq!(
+                calculate_p_score_grid(
+                    &State7D { certainty: 0.9, ..test_state() },
+                    &RigorParams::default(),
+                    grid,
+                    &mut result,
+                ),
+                NavStatus::Ok
+            );
+            assert_eq!(result.closest_obstacle_index, -1);
+            free_verification_result(&mut result);
+            nav_free_obstacle_grid(grid);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_margins_match_scalar() {
+        let mut seed: u32 = 777;
+        let mut next = || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 8) as c_float / (1u32 << 24) as c_float * 100.0 - 50.0
+        };
+
+        for stride in [OBSTACLE_STRIDE_POINT, OBSTACLE_STRIDE_SIZED, OBSTACLE_STRIDE_DYNAMIC] {
+            for case in 0..12 {
+                let state = State7D {
+                    position: [next(), next(), next()],
+                    velocity: if case % 2 == 0 { [0.0; 3] } else { [1.0, -2.0, 0.5] },
+                    certainty: 0.9,
+                    ..test_state()
+                };
+                let params = RigorParams {
+                    min_margin: 0.75,
+                    planar_mode: (case % 3 == 0) as c_int,
+                    horizon_secs: if case % 4 < 2 { 0.0 } else { 3.0 },
+                    distance_metric: (case / 2 % 3) as c_int,
+                    ..Default::default()
+                };
+                // Not a multiple of the lane count, plus a trailing partial record
+                let mut obstacles: Vec<c_float> = (0..101 * stride + 1).map(|_| next()).collect();
+                if stride == OBSTACLE_STRIDE_SIZED {
+                    for radius in obstacles.iter_mut().skip(3).step_by(stride) {
+                        *radius = radius.abs() * 0.01;
+                    }
+                }
+
+                // Every yielded margin matches the scalar computation exactly
+                for (i, obstacle, margin) in simd::Margins::new(&state, &params, &obstacles, stride) {
+                    let scalar = obstacle_margin(&state, &params, obstacle, stride);
+                    assert_eq!(&obstacles[i * stride..(i + 1) * stride], obstacle);
+                    assert_eq!(margin.to_bits(), scalar.to_bits());
+                }
+
+                // Skipped blocks never change the verdict
+                let vector = evaluate(&state, &params, None, &obstacles, stride);
+                let scalar = evaluate_indexed(
+                    &state,
+                    &params,
+                    None,
+                    obstacles
+                        .chunks_exact(stride)
+                        .enumerate()
+                        .map(|(i, o)| (i, o, obstacle_margin(&state, &params, o, stride))),
+                    stride,
+                );
+                assert_eq!(vector.margin.to_bits(), scalar.margin.to_bits());
+                assert_eq!(vector.closest_obstacle_index, scalar.closest_obstacle_index);
+                assert_eq!(vector.breach_flags, scalar.breach_flags);
+                assert_eq!(vector.time_to_collision.to_bits(), scalar.time_to_collision.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn test_verdict_hysteresis() {
+        let params = test_params();
+        let obstacle = [0.0, 0.0, 0.0];
+        // Margin swept down from 1.0 to -0.5 and back, jittering +-0.05 around each step
+        let margins: Vec<c_float> = (0..=75)
+            .chain((0..75).rev())
+            .enumerate()
+            .map(|(frame, step)| 1.0 - step as c_float * 0.02 + if frame % 2 == 0 { 0.05 } else { -0.05 })
+            .collect();
+
+        let verdicts = |band: c_float| unsafe {
+            let ctx = nav_context_create();
+            assert_eq!(nav_context_set_hysteresis(ctx, band), NavStatus::Ok);
+            let verdicts: Vec<(c_int, c_float)> = margins
+                .iter()
+                .map(|&m| {
+                    let state = State7D { position: [1.0 + m, 0.0, 0.0], certainty: 0.9, ..test_state() };
+                    // The dry run sees the latch first but must not move it
+                    let flags = verify_flags_in(&*ctx, &state, &params, obstacle.as_ptr(), 1);
+                    let result = scored(ctx, &state, &params, &obstacle).result;
+                    assert_eq!(flags, result.breach_flags);
+                    assert_eq!(result.is_safe == 0, result.breach_flags == BREACH_FLAG_VNC_VIOLATION);
+                    (result.is_safe, result.margin)
+                })
+                .collect();
+            nav_context_destroy(ctx);
+            verdicts
+        };
+        let flips = |verdicts: &[(c_int, c_float)]| verdicts.windows(2).filter(|w| w[0].0 != w[1].0).count();
+
+        // No band: the verdict tracks the sign of the jittering margin
+        let plain = verdicts(0.0);
+        assert!(flips(&plain) > 2);
+        assert!(plain.iter().all(|&(safe, margin)| (safe == 1) == (margin >= 0.0)));
+
+        // A band wider than the jitter: one breach going down, one recovery coming up
+        let held = verdicts(0.25);
+        assert_eq!(flips(&held), 2);
+        let first_breach = held.iter().position(|&(safe, _)| safe == 0).unwrap();
+        let recovery = first_breach + held[first_breach..].iter().position(|&(safe, _)| safe == 1).unwrap();
+        assert!(held[first_breach].1 < 0.0);
+        assert!(held[recovery].1 >= 0.25);
+        assert!(held[recovery - 1].1 < 0.25);
+
+        unsafe {
+            assert_eq!(nav_context_set_hysteresis(ptr::null_mut(), f32::NAN), NavStatus::NonFinite);
+            assert_eq!(nav_context_set_hysteresis(ptr::null_mut(), -0.1), NavStatus::OutOfRange);
+        }
+    }
+
+    #[test]
+    fn test_max_obstacles() {
+        let state = test_state();
+        let params = test_params();
+        let obstacles = [5.0, 0.0, 0.0, 0.0, 5.0, 0.0, -5.0, 0.0, 0.0];
+        let mut result = VerificationResult::default();
+        unsafe {
+            // A per-context cap: exactly at the cap is fine, one over is rejected
+            let ctx = nav_context_create();
+            assert_eq!(nav_context_set_max_obstacles(ctx, 0), NavStatus::OutOfRange);
+            assert_eq!(nav_context_set_max_obstacles(ctx, 2), NavStatus::Ok);
+            scored(ctx, &state, &params, &obstacles[..6]);
+            assert_eq!(
+                nav_calculate_p_score(ctx, &state, &params, obstacles.as_ptr(), 3, &mut result),
+                NavStatus::TooManyObstacles
+            );
+            // The default context keeps its own cap
+            p_score(&state, &params, &obstacles);
+            nav_context_destroy(ctx);
+
+            // A bogus count is rejected before the 9-float buffer is read
+            let bogus = usize::MAX / 3;
+            assert_eq!(
+                calculate_p_score(&state, &params, obstacles.as_ptr(), bogus, &mut result),
+                NavStatus::TooManyObstacles
+            );
+            assert!(calculate_min_margin(&state, &params, obstacles.as_ptr(), bogus).is_nan());
+            assert_eq!(verify_flags_only(&state, &params, obstacles.as_ptr(), bogus), NAV_FLAGS_INVALID_INPUT);
+            assert!(nav_build_obstacle_grid(obstacles.as_ptr(), bogus, 1.0).is_null());
+        }
+    }
+
+    #[test]
+    fn test_verify_flags_only_matches_full() {
+        let params = RigorParams { horizon_secs: 2.0, ..test_params() };
+        let obstacles = [5.0, 0.0, 0.0, 0.0, 5.0, 0.0, -2.0, -2.0, 0.5];
+        for k in 0..60 {
+            let f = k as c_float;
+            let state = State7D {
+                position: [f * 0.2 - 6.0, (f * 0.7).sin() * 4.0, 0.0],
+                velocity: [(f * 0.3).cos() * 3.0, 0.0, 0.0],
+                timestamp: k,
+                certainty: (f * 0.053) % 1.0,
+                fatigue: (f * 0.031) % 1.0,
+                ..test_state()
+            };
+            let result = p_score(&state, &params, &obstacles);
+            let flags = unsafe { verify_flags_only(&state, &params, obstacles.as_ptr(), 3) };
+            assert_eq!(flags, result.breach_flags);
+            assert_eq!(flags == 0, result.is_safe == 1);
+        }
+
+        // Soft cognitive breach: the warning bits are reported on a safe verdict
+        let soft = RigorParams { soft_cognitive_breach: 1, ..params };
+        let tired = State7D { certainty: 0.2, fatigue: 0.1, ..test_state() };
+        let result = p_score(&tired, &soft, &[]);
+        let flags = unsafe { verify_flags_only(&tired, &soft, ptr::null(), 0) };
+        assert_eq!(flags, BREACH_FLAG_FATIGUE | BREACH_FLAG_LOW_CERTAINTY);
+        assert_eq!((flags, result.is_safe), (result.breach_flags, 1));
+
+        let state = test_state();
+        unsafe {
+            assert_eq!(verify_flags_only(&state, &params, ptr::null(), 0), 0);
+            assert_eq!(verify_flags_only(ptr::null(), &params, ptr::null(), 0), NAV_FLAGS_INVALID_INPUT);
+            assert_eq!(verify_flags_only(&state, ptr::null(), ptr::null(), 0), NAV_FLAGS_INVALID_INPUT);
+            let nan = State7D { heading: f32::NAN, ..state };
+            assert_eq!(verify_flags_only(&nan, &params, ptr::null(), 0), NAV_FLAGS_INVALID_INPUT);
+            let out_of_range = State7D { certainty: 1.5, ..state };
+            assert_eq!(verify_flags_only(&out_of_range, &params, ptr::null(), 0), NAV_FLAGS_INVALID_INPUT);
+            let clamped = RigorParams { clamp_cognitive: 1, ..params };
+            assert_eq!(verify_flags_only(&out_of_range, &clamped, ptr::null(), 0), 0);
+        }
+    }
+
+    #[test]
+    fn test_p_score_terms_sum() {
+        let params = RigorParams {
+            weights: [0.7, 2.5, -1.25, 3.0, 0.4],
+            time_period: 1000,
+            ..Default::default()
+        };
+        let obstacles = [4.0, 0.0, 1.0, -3.0, 2.0, 0.5];
+        for k in 0..50 {
+            let f = k as c_float;
+            let state = State7D {
+                position: [f * 0.37 - 9.0, (f * 0.61).sin() * 20.0, f * -0.13],
+                velocity: [f.cos(), 0.5, -0.25],
+                heading: f * 0.2,
+                timestamp: k * 137,
+                certainty: 0.3 + (f * 0.011) % 0.7,
+                fatigue: 0.1 + (f * 0.017) % 0.9,
+            };
+            let result = p_score(&state, &params, &obstacles);
+            let sum: c_float = result.terms.iter().sum();
+            assert!((sum - result.p_score).abs() <= 1e-5 * result.p_score.abs().max(1.0), "{:?} vs {}", result.terms, result.p_score);
+            // Each entry is the weighted term, e.g. the time phase and fatigue
+            let phase = (state.timestamp % 1000) as c_float / 1000.0;
+            assert_eq!(result.terms[1], 2.5 * phase);
+            assert_eq!(result.terms[4], 0.4 * state.fatigue);
+        }
+
+        // Zero weights zero their contributions
+        let state = State7D { position: [1.0, 2.0, 3.0], certainty: 0.9, ..test_state() };
+        let pos_only = RigorParams { weights: [1.0, 0.0, 0.0, 0.0, 0.0], ..Default::default() };
+        let result = p_score(&state, &pos_only, &[]);
+        assert_eq!(result.terms[1..], [0.0; 4]);
+        assert_eq!(result.terms[0], result.p_score);
+    }
+
+    #[test]
+    fn test_heightmap_gradient() {
+        let ctx = nav_context_create();
+        let state = State7D { position: [2.0, 4.0, 1.0], ..test_state() };
+        // Only the gradient term contributes to the score
+        let params = RigorParams {
+            weights: [0.0, 0.0, 1.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let score = |state: &State7D| scored(ctx, state, &params, &[]).result.p_score;
+        let at = |x: c_float, z: c_float, heading: c_float| State7D { position: [x, 4.0, z], heading, ..state };
+
+        // No heightmap: linear approximation from position.y
+        assert!((score(&state) - 0.4).abs() < 1e-6);
+
+        unsafe {
+            // Plane h = 0.5x + 0.25z on a 5x4 grid with 2m cells, origin (-4, -2)
+            let plane: Vec<c_float> = (0..4)
+                .flat_map(|row| (0..5).map(move |col| 0.5 * (-4.0 + 2.0 * col as c_float) + 0.25 * (-2.0 + 2.0 * row as c_float)))
+                .collect();
+            assert_eq!(nav_set_heightmap(ctx, plane.as_ptr(), 5, 4, 2.0, -4.0, -2.0), NavStatus::Ok);
+            let slope = (0.5f32 * 0.5 + 0.25 * 0.25).sqrt();
+            assert!((score(&state) - slope).abs() < 1e-6);
+            // Clamped to the edge outside the grid, which keeps the plane's slope
+            assert!((score(&at(100.0, -100.0, 0.0)) - slope).abs() < 1e-6);
+            // Traversing a contour contributes nothing
+            assert!(score(&at(2.0, 1.0, std::f32::consts::FRAC_PI_2)).abs() < 1e-6);
+
+            // Ridge [0, 1, 0] along x: node slopes 1, 0, 1, bilinear in between
+            let ridge = [0.0, 1.0, 0.0];
+            assert_eq!(nav_set_heightmap(ctx, ridge.as_ptr(), 3, 1, 1.0, 0.0, 0.0), NavStatus::Ok);
+            assert!((score(&at(0.5, 0.0, 0.0)) - 0.5).abs() < 1e-6);
+            assert!(score(&at(1.0, 7.0, 0.0)).abs() < 1e-6);
+            assert!((score(&at(-5.0, 0.0, 0.0)) - 1.0).abs() < 1e-6);
+            assert!((score(&at(10.0, 0.0, 0.0)) - 1.0).abs() < 1e-6);
+
+            // Planar mode zeroes the term regardless
+            let planar = RigorParams { planar_mode: 1, ..params };
+            assert_eq!(scored(ctx, &at(0.5, 0.0, 0.0), &planar, &[]).result.p_score, 0.0);
+
+            // Invalid grids are rejected and keep the current map
+            assert_eq!(nav_set_heightmap(ctx, ridge.as_ptr(), 0, 1, 1.0, 0.0, 0.0), NavStatus::EmptyInput);
+            assert_eq!(nav_set_heightmap(ctx, ridge.as_ptr(), 3, 1, 0.0, 0.0, 0.0), NavStatus::OutOfRange);
+            assert_eq!(nav_set_heightmap(ctx, ridge.as_ptr(), 3, 1, 1.0, f32::NAN, 0.0), NavStatus::NonFinite);
+            let bad = [0.0, f32::INFINITY, 0.0];
+            assert_eq!(nav_set_heightmap(ctx, bad.as_ptr(), 3, 1, 1.0, 0.0, 0.0), NavStatus::NonFinite);
+            assert_eq!(nav_set_heightmap(ctx, ridge.as_ptr(), usize::MAX, 2, 1.0, 0.0, 0.0), NavStatus::OutOfRange);
+            assert!((score(&at(0.5, 0.0, 0.0)) - 0.5).abs() < 1e-6);
+
+            // Null data clears it, restoring the approximation
+            assert_eq!(nav_set_heightmap(ctx, ptr::null(), 0, 0, 0.0, 0.0, 0.0), NavStatus::Ok);
+            assert!((score(&state) - 0.4).abs() < 1e-6);
+            nav_context_destroy(ctx);
+        }
+    }
+
+    #[test]
+    fn test_reference_origin_shift_invariant() {
+        let state = State7D { position: [3.0, 4.0, 12.0], ..test_state() };
+        // Only the pos_norm term contributes to the score
+        let params = RigorParams {
+            weights: [1.0, 0.0, 0.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let offset = [1000.0, -250.0, 64.0];
+        let shifted_state = State7D {
+            position: [3.0 + offset[0], 4.0 + offset[1], 12.0 + offset[2]],
+            ..state
+        };
+        let shifted_params = RigorParams { reference_origin: offset, ..params };
+
+        let score = |state: &State7D, params: &RigorParams| p_score(state, params, &[]).p_score;
+
+        assert!((score(&state, &params) - 13.0).abs() < 1e-5);
+        assert_eq!(score(&shifted_state, &shifted_params), score(&state, &params));
+        // Shifting only the agent moves it away from the (default) origin
+        assert!(score(&shifted_state, &params) > 1000.0);
+
+        let non_finite = RigorParams { reference_origin: [0.0, f32::NAN, 0.0], ..params };
+        let mut result = VerificationResult::default();
+        assert_eq!(
+            unsafe { calculate_p_score(&state, &non_finite, ptr::null(), 0, &mut result) },
+            NavStatus::NonFinite
+        );
+    }
+
+    #[test]
+    fn test_rng_seed_reproducible() {
+        let _guard = lock_global_state();
+        let variates = [0.12, 0.34, 0.08, 0.51, 0.27, 0.19, 0.44, 0.05];
+        let ctx = nav_context_create();
+        let bootstrap = |seed: u64| unsafe {
+            assert_eq!(nav_context_set_rng_seed(ctx, seed), NavStatus::Ok);
+            let mut first = 0.0;
+            let mut second = 0.0;
+            assert_eq!(
+                nav_calculate_sim2val_bootstrap(ctx, variates.as_ptr(), variates.len(), 200, &mut first),
+                NavStatus::Ok
+            );
+            assert_eq!(
+                nav_calculate_sim2val_bootstrap(ctx, variates.as_ptr(), variates.len(), 200, &mut second),
+                NavStatus::Ok
+            );
+            (first, second)
+        };
+
+        let (a1, a2) = bootstrap(7);
+        let (b1, b2) = bootstrap(7);
+        assert_eq!(a1.to_bits(), b1.to_bits());
+        assert_eq!(a2.to_bits(), b2.to_bits());
+        // The RNG advances between calls, and a different seed gives a different draw
+        assert_ne!(a1, a2);
+        assert_ne!(bootstrap(8).0, a1);
+
+        // Same through the default context used by the global functions
+        let global = |seed: u64| unsafe {
+            nav_set_rng_seed(seed);
+            let mut sigma = 0.0;
+            assert_eq!(
+                calculate_sim2val_bootstrap(variates.as_ptr(), variates.len(), 200, &mut sigma),
+                NavStatus::Ok
+            );
+            sigma
+        };
+        assert_eq!(global(7).to_bits(), a1.to_bits());
+        assert_eq!(global(7).to_bits(), global(7).to_bits());
+
+        // Fresh contexts start from the fixed default seed
+        let fresh = nav_context_create();
+        let reseeded = nav_context_create();
+        unsafe {
+            assert_eq!(nav_context_set_rng_seed(reseeded, NAV_DEFAULT_RNG_SEED), NavStatus::Ok);
+            let mut x = 0.0;
+            let mut y = 0.0;
+            nav_calculate_sim2val_bootstrap(fresh, variates.as_ptr(), variates.len(), 50, &mut x);
+            nav_calculate_sim2val_bootstrap(reseeded, variates.as_ptr(), variates.len(), 50, &mut y);
+            assert_eq!(x.to_bits(), y.to_bits());
+            // Standard error of the mean is close to sigma / sqrt(n)
+            let expected = sim2val_sigma(&variates, false) / (variates.len() as f32).sqrt();
+            assert!((a1 - expected).abs() < expected * 0.3);
+
+            let mut sigma = 0.0;
+            assert_eq!(
+                nav_calculate_sim2val_bootstrap(fresh, variates.as_ptr(), 1, 10, &mut sigma),
+                NavStatus::InsufficientSamples
+            );
+            assert_eq!(
+                nav_calculate_sim2val_bootstrap(fresh, variates.as_ptr(), variates.len(), 0, &mut sigma),
+                NavStatus::OutOfRange
+            );
+            assert_eq!(nav_context_set_rng_seed(ptr::null_mut(), 1), NavStatus::NullPointer);
+            nav_context_destroy(fresh);
+            nav_context_destroy(reseeded);
+            nav_context_destroy(ctx);
+        }
+    }
+
+    #[test]
+    fn test_calculate_p_score_quat() {
+        use std::f32::consts::{FRAC_PI_2, PI};
+        let ctx = nav_context_create();
+        let base = State7D { position: [1.0, 8.0, 2.0], ..test_state() };
+        let heading_state = |heading: f32| State7D { heading, ..base };
+        let quat_state = |orientation: [f32; 4]| State7DQuat {
+            position: base.position,
+            velocity: base.velocity,
+            orientation,
+            timestamp: base.timestamp,
+            certainty: base.certainty,
+            fatigue: base.fatigue,
+        };
+        let params = test_params();
+        let obstacles = [4.0, 4.0, 0.0];
+
+        let score = |state: &State7D, params: &RigorParams| {
+            let result = scored(ctx, state, params, &obstacles).result;
+            (result.p_score, result.margin)
+        };
+        let score_quat = |state: &State7DQuat, params: &RigorParams| unsafe {
+            let mut result = VerificationResult::default();
+            let status = p_score_quat_in(&*ctx, state, params, obstacles.as_ptr(), 1, &mut result);
+            free_verification_result(&mut result);
+            (status, (result.p_score, result.margin))
+        };
+        let matches = |orientation: [f32; 4], heading: f32, params: &RigorParams| {
+            let (status, (quat_score, quat_margin)) = score_quat(&quat_state(orientation), params);
+            assert_eq!(status, NavStatus::Ok);
+            let (score, margin) = score(&heading_state(heading), params);
+            assert!((quat_score - score).abs() < 1e-5, "{quat_score} vs {score}");
+            assert_eq!(quat_margin, margin);
+            quat_score
+        };
+
+        // Y is up: a yaw about +Y by `angle` turns the forward axis (+X) toward -Z.
+        // Without a heightmap, uphill is +Z.
+        let yaw = |angle: f32| [(angle / 2.0).cos(), 0.0, (angle / 2.0).sin(), 0.0];
+        matches(yaw(-FRAC_PI_2), 0.0, &params); // Facing +Z, uphill
+        matches([1.0, 0.0, 0.0, 0.0], FRAC_PI_2, &params); // Facing +X, across the slope
+        matches(yaw(-PI / 3.0), PI / 6.0, &params); // 30 deg off uphill
+        // Pitched straight up: no ground direction, no slope contribution
+        matches([(PI / 4.0).cos(), 0.0, 0.0, (PI / 4.0).sin()], FRAC_PI_2, &params);
+
+        // On a registered heightmap the uphill direction is its gradient:
+        // plane h = 0.5x rises along +X, so a vehicle that only yaws scores
+        // the full slope facing +X, none across it and the negative facing -X
+        let gradient_only = RigorParams { weights: [0.0, 0.0, 1.0, 0.0, 0.0], ..params };
+        let plane: Vec<c_float> = (0..3).flat_map(|_| (0..3).map(|col| 0.5 * col as c_float)).collect();
+        unsafe {
+            assert_eq!(nav_set_heightmap(ctx, plane.as_ptr(), 3, 3, 1.0, 0.0, 0.0), NavStatus::Ok);
+        }
+        assert!((matches(yaw(0.0), 0.0, &gradient_only) - 0.5).abs() < 1e-6);
+        assert!(matches(yaw(-FRAC_PI_2), FRAC_PI_2, &gradient_only).abs() < 1e-6);
+        assert!(matches(yaw(FRAC_PI_2), FRAC_PI_2, &gradient_only).abs() < 1e-6);
+        assert!((matches(yaw(PI), PI, &gradient_only) + 0.5).abs() < 1e-6);
+        assert!((matches(yaw(PI / 3.0), PI / 3.0, &gradient_only) - 0.25).abs() < 1e-6);
+
+        // Scale doesn't matter; zero and non-finite quaternions are rejected
+        let unit = score_quat(&quat_state(yaw(1.0)), &params).1;
+        let scaled = score_quat(&quat_state(yaw(1.0).map(|v| v * 3.0)), &params).1;
+        assert!((unit.0 - scaled.0).abs() < 1e-5);
+        assert_eq!(score_quat(&quat_state([0.0; 4]), &params).0, NavStatus::OutOfRange);
+        assert_eq!(score_quat(&quat_state([f32::NAN, 0.0, 0.0, 1.0]), &params).0, NavStatus::NonFinite);
+        unsafe { nav_context_destroy(ctx) };
+
+        // The exported function is the default-context form
+        let mut result = VerificationResult::default();
+        unsafe {
+            assert_eq!(calculate_p_score_quat(&quat_state(yaw(0.0)), &params, obstacles.as_ptr(), 1, &mut result), NavStatus::Ok);
+            free_verification_result(&mut result);
+            assert_eq!(calculate_p_score_quat(ptr::null(), &params, obstacles.as_ptr(), 1, &mut result), NavStatus::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_cognitive_range_modes() {
+        let state = test_state();
+        let reject = test_params();
+        let clamp = RigorParams { clamp_cognitive: 1, ..reject };
+
+        let verify = |state: &State7D, params: &RigorParams| unsafe {
+            let mut result = VerificationResult::default();
+            let status = calculate_p_score(state, params, ptr::null(), 0, &mut result);
+            let out = (status, result.p_score, result.breach_flags);
+            free_verification_result(&mut result);
+            out
+        };
+
+        for (certainty, fatigue) in [(-0.1, 0.9), (1.5, 0.9), (0.8, -0.1), (0.8, 1.5)] {
+            let bad = State7D { certainty, fatigue, ..state };
+            assert_eq!(verify(&bad, &reject).0, NavStatus::OutOfRange);
+            assert_eq!(verify(&bad, &clamp).0, NavStatus::Ok);
+        }
+
+        // 1.5 saturates to 1.0 instead of inflating the score
+        let high = State7D { certainty: 1.5, ..state };
+        let (_, clamped_score, _) = verify(&high, &clamp);
+        let (_, saturated_score, _) = verify(&State7D { certainty: 1.0, ..state }, &clamp);
+        assert_eq!(clamped_score, saturated_score);
+
+        // -0.1 saturates to 0.0, which still trips the low-certainty breach
+        let negative = State7D { certainty: -0.1, ..state };
+        let (_, _, flags) = verify(&negative, &clamp);
+        assert_ne!(flags & BREACH_FLAG_LOW_CERTAINTY, 0);
+
+        // In-range values are untouched in either mode
+        assert_eq!(verify(&state, &reject), verify(&state, &clamp));
+
+        // Batch validates every state before writing any result
+        let states = [state, high];
+        let mut results = [VerificationResult::default(); 2];
+        unsafe {
+            assert_eq!(
+                calculate_p_score_batch(states.as_ptr(), 2, &reject, ptr::null(), 0, results.as_mut_ptr()),
+                NavStatus::OutOfRange
+            );
+            assert!(results[0].evidence_hash.is_null());
+        }
+    }
+
+    #[test]
+    fn test_calculate_min_margin() {
+        let state = State7D { velocity: [1.0, 0.0, 0.0], ..test_state() };
+        let params = RigorParams { horizon_secs: 5.0, ..test_params() };
+        let clear = [10.0, 0.0, 0.0, 0.0, 4.0, 0.0];
+        let breach = [10.0, 0.0, 0.0, 0.5, 0.0, 0.0];
+
+        unsafe {
+            for obstacles in [&clear, &breach] {
+                let margin = calculate_min_margin(&state, &params, obstacles.as_ptr(), 2);
+                assert_eq!(margin, p_score(&state, &params, obstacles).margin);
+            }
+            assert!((calculate_min_margin(&state, &params, clear.as_ptr(), 2) - 3.0).abs() < 1e-6);
+            assert!(calculate_min_margin(&state, &params, breach.as_ptr(), 2) < 0.0);
+            assert_eq!(calculate_min_margin(&state, &params, ptr::null(), 0), f32::MAX);
+
+            assert!(calculate_min_margin(ptr::null(), &params, clear.as_ptr(), 2).is_nan());
+            let bad = [f32::NAN, 0.0, 0.0];
+            assert!(calculate_min_margin(&state, &params, bad.as_ptr(), 1).is_nan());
+        }
+    }
+
+    #[test]
+    fn test_calculate_p_score_masked() {
+        let state = test_state();
+        let params = test_params();
+        // Odd slots are dead and would breach if scanned
+        let mut pool = [4.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.2, 0.0];
+        let mask = [1u8, 0, 1, 0];
+        let verify = |pool: &[f32], mask: &[u8]| unsafe {
+            let mut result = VerificationResult::default();
+            let status = calculate_p_score_masked(&state, &params, pool.as_ptr(), mask.len(), mask.as_ptr(), &mut result);
+            assert_eq!(status, NavStatus::Ok);
+            let hash = CStr::from_ptr(result.evidence_hash).to_str().unwrap().to_owned();
+            free_verification_result(&mut result);
+            (result, hash)
+        };
+
+        let (masked, hash) = verify(&pool, &mask);
+        assert_eq!(masked.is_safe, 1);
+        assert_eq!(masked.margin, 2.0); // Slot 2 at distance 3, not the dead ones inside the margin
+        assert_eq!(masked.closest_obstacle_index, 2); // Index into the full pool
+
+        // Same verdict as the compacted live obstacles
+        let compacted = [4.0, 0.0, 0.0, 0.0, 3.0, 0.0];
+        let linear = p_score(&state, &params, &compacted);
+        assert_eq!((masked.p_score, masked.margin, masked.breach_flags), (linear.p_score, linear.margin, linear.breach_flags));
+
+        // Dead slots may hold anything, including NaN, without changing the result
+        pool[3] = f32::NAN;
+        pool[10] = 99.0;
+        let (stale, stale_hash) = verify(&pool, &mask);
+        assert_eq!(stale.margin, masked.margin);
+        assert_eq!(stale_hash, hash);
+
+        // Waking a slot brings it back into the scan
+        pool[3] = 0.5;
+        let (woken, _) = verify(&pool, &[1, 1, 1, 0]);
+        assert_eq!(woken.is_safe, 0);
+        assert_eq!(woken.closest_obstacle_index, 1);
+
+        // All dead: no obstacles at all
+        let (empty, _) = verify(&pool, &[0; 4]);
+        assert_eq!(empty.margin, f32::MAX);
+        assert_eq!(empty.closest_obstacle_index, -1);
+
+        unsafe {
+            let mut result = VerificationResult::default();
+            assert_eq!(
+                calculate_p_score_masked(&state, &params, pool.as_ptr(), 4, ptr::null(), &mut result),
+                NavStatus::NullPointer
+            );
+            assert_eq!(
+                calculate_p_score_masked(&state, &params, ptr::null(), 0, ptr::null(), &mut result),
+                NavStatus::Ok
+            );
+            free_verification_result(&mut result);
+            pool[0] = f32::INFINITY;
+            assert_eq!(
+                calculate_p_score_masked(&state, &params, pool.as_ptr(), 4, mask.as_ptr(), &mut result),
+                NavStatus::NonFinite
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_breaching_obstacles() {
+        let state = test_state();
+        let params = test_params();
+        // Boxed in: 0 and 2 are inside the margin, 1 is clear
+        let obstacles = [0.5, 0.0, 0.0, 4.0, 0.0, 0.0, 0.0, -0.8, 0.0];
+
+        unsafe {
+            let mut indices = [-1; 4];
+            let n = calculate_breaching_obstacles(&state, &params, obstacles.as_ptr(), 3, indices.as_mut_ptr(), 4);
+            assert_eq!(n, 2);
+            assert_eq!(indices, [0, 2, -1, -1]);
+
+            // The closest breach is the one calculate_p_score reports
+            assert_eq!(p_score(&state, &params, &obstacles).closest_obstacle_index, 0);
+
+            // Too small: nothing written, negated required count
+            let mut small = [-1; 1];
+            assert_eq!(calculate_breaching_obstacles(&state, &params, obstacles.as_ptr(), 3, small.as_mut_ptr(), 1), -2);
+            assert_eq!(small, [-1]);
+            assert_eq!(calculate_breaching_obstacles(&state, &params, obstacles.as_ptr(), 3, ptr::null_mut(), 0), -2);
+
+            // Clear, empty and invalid input
+            let clear = RigorParams { min_margin: 0.1, ..params };
+            assert_eq!(calculate_breaching_obstacles(&state, &clear, obstacles.as_ptr(), 3, ptr::null_mut(), 0), 0);
+            assert_eq!(calculate_breaching_obstacles(&state, &params, ptr::null(), 0, ptr::null_mut(), 0), 0);
+            let invalid = NAV_BREACHING_INVALID_INPUT;
+            assert_eq!(calculate_breaching_obstacles(ptr::null(), &params, obstacles.as_ptr(), 3, indices.as_mut_ptr(), 4), invalid);
+            let bad = [f32::NAN, 0.0, 0.0];
+            assert_eq!(calculate_breaching_obstacles(&state, &params, bad.as_ptr(), 1, indices.as_mut_ptr(), 4), invalid);
+            assert_eq!(calculate_breaching_obstacles(&state, &params, obstacles.as_ptr(), 3, ptr::null_mut(), 4), invalid);
+        }
+    }
+
+    #[test]
+    fn test_compute_risk_grid() {
+        let params = test_params();
+        // 21x21 unit cells centered on the integers -10..=10
+        let (width, height) = (21, 21);
+        let mut grid = vec![0.0; width * height];
+        let obstacle = [0.0, 0.0, 0.0];
+        unsafe {
+            assert_eq!(
+                compute_risk_grid(&params, obstacle.as_ptr(), 1, -10.5, -10.5, 1.0, width, height, grid.as_mut_ptr()),
+                NavStatus::Ok
+            );
+        }
+        let at = |x: i32, y: i32| grid[(y + 10) as usize * width + (x + 10) as usize];
+
+        // Radial: the margin is the distance from the obstacle minus min_margin
+        for y in -10..=10 {
+            for x in -10..=10 {
+                let expected = ((x * x + y * y) as f32).sqrt() - 1.0;
+                assert!((at(x, y) - expected).abs() < 1e-5, "({x}, {y}): {}", at(x, y));
+                assert_eq!(at(x, y), at(-x, y));
+                assert_eq!(at(x, y), at(y, x));
+            }
+        }
+        assert_eq!(at(0, 0), -1.0);
+        assert!(at(0, 0) < 0.0 && at(1, 0) >= 0.0); // Breach only at the obstacle's own cell
+        assert!((1..=10).all(|r| at(r, 0) > at(r - 1, 0)));
+
+        // Row-major: a non-square grid keeps rows of `width` cells
+        let mut wide = vec![0.0; 4 * 2];
+        let params_l1 = RigorParams { distance_metric: NAV_METRIC_MANHATTAN, ..params };
+        unsafe {
+            compute_risk_grid(&params_l1, obstacle.as_ptr(), 1, 0.0, 0.0, 2.0, 4, 2, wide.as_mut_ptr());
+        }
+        assert_eq!(wide, [1.0, 3.0, 5.0, 7.0, 3.0, 5.0, 7.0, 9.0]);
+
+        // Matches the per-state margin, and no obstacles means clear everywhere
+        let state = State7D { position: [3.0, -4.0, 0.0], velocity: [5.0, 5.0, 0.0], ..test_state() };
+        assert_eq!(unsafe { calculate_min_margin(&state, &params, obstacle.as_ptr(), 1) }, at(3, -4));
+        unsafe {
+            assert_eq!(compute_risk_grid(&params, ptr::null(), 0, 0.0, 0.0, 1.0, 4, 2, wide.as_mut_ptr()), NavStatus::Ok);
+        }
+        assert!(wide.iter().all(|&m| m == c_float::MAX));
+
+        unsafe {
+            let out = wide.as_mut_ptr();
+            assert_eq!(compute_risk_grid(ptr::null(), obstacle.as_ptr(), 1, 0.0, 0.0, 1.0, 4, 2, out), NavStatus::NullPointer);
+            assert_eq!(compute_risk_grid(&params, obstacle.as_ptr(), 1, 0.0, 0.0, 1.0, 4, 2, ptr::null_mut()), NavStatus::NullPointer);
+            assert_eq!(compute_risk_grid(&params, obstacle.as_ptr(), 1, f32::NAN, 0.0, 1.0, 4, 2, out), NavStatus::NonFinite);
+            assert_eq!(compute_risk_grid(&params, obstacle.as_ptr(), 1, 0.0, 0.0, 0.0, 4, 2, out), NavStatus::OutOfRange);
+            assert_eq!(compute_risk_grid(&params, obstacle.as_ptr(), 1, 0.0, 0.0, 1.0, 0, 2, out), NavStatus::OutOfRange);
+            assert_eq!(compute_risk_grid(&params, obstacle.as_ptr(), 1, 0.0, 0.0, 1.0, usize::MAX, 2, out), NavStatus::OutOfRange);
+        }
+        assert!(wide.iter().all(|&m| m == c_float::MAX));
+    }
+
+    #[test]
+    fn test_breach_log_ring_buffer() {
+        let ctx = nav_context_create();
+        let params = test_params();
+        let obstacles = [0.5, 0.0, 0.0];
+        let verify = |timestamp: u64, x: f32| {
+            let state = State7D { position: [x, 0.0, 0.0], timestamp, ..test_state() };
+            scored(ctx, &state, &params, &obstacles).hash
+        };
+
+        unsafe {
+            nav_breach_log_set_capacity(ctx, 3);
+            verify(100, 50.0); // Safe: not logged
+            assert_eq!(nav_breach_log_count(ctx), 0);
+
+            let hashes: Vec<String> = (1..=5).map(|t| verify(t, 0.0)).collect();
+            assert_eq!(nav_breach_log_count(ctx), 3);
+
+            // Oldest two were overwritten; order is oldest first
+            let mut record = std::mem::zeroed::<BreachRecord>();
+            for (index, timestamp) in (3..=5).enumerate() {
+                assert_eq!(nav_breach_log_get(ctx, index, &mut record), NavStatus::Ok);
+                assert_eq!(record.timestamp, timestamp);
+                assert_ne!(record.breach_flags & BREACH_FLAG_VNC_VIOLATION, 0);
+                assert!((record.margin + 0.5).abs() < 1e-6);
+                let hash = CStr::from_ptr(record.evidence_hash.as_ptr()).to_str().unwrap();
+                assert_eq!(hash, hashes[timestamp as usize - 1]);
+            }
+            assert_eq!(nav_breach_log_get(ctx, 3, &mut record), NavStatus::OutOfRange);
+            assert_eq!(nav_breach_log_get(ctx, 0, ptr::null_mut()), NavStatus::NullPointer);
+
+            // Shrinking keeps the newest; capacity 0 stops logging
+            nav_breach_log_set_capacity(ctx, 1);
+            assert_eq!(nav_breach_log_count(ctx), 1);
+            assert_eq!(nav_breach_log_get(ctx, 0, &mut record), NavStatus::Ok);
+            assert_eq!(record.timestamp, 5);
+            nav_breach_log_set_capacity(ctx, 0);
+            verify(6, 0.0);
+            assert_eq!(nav_breach_log_count(ctx), 0);
+
+            nav_context_destroy(ctx);
+        }
+    }
+
+    #[test]
+    fn test_breach_log_to_csv() {
+        let _guard = lock_global_state();
+        // Minimal RFC 4180 reader: quoted fields may hold commas, quotes and CRLF
+        fn parse_csv(text: &str) -> Vec<Vec<String>> {
+            let (mut rows, mut row, mut field) = (Vec::new(), Vec::new(), String::new());
+            let (mut chars, mut quoted) = (text.chars().peekable(), false);
+            while let Some(c) = chars.next() {
+                match (c, quoted) {
+                    ('"', true) if chars.peek() == Some(&'"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    ('"', _) => quoted = !quoted,
+                    (',', false) => row.push(std::mem::take(&mut field)),
+                    ('\r', false) => {}
+                    ('\n', false) => {
+                        row.push(std::mem::take(&mut field));
+                        rows.push(std::mem::take(&mut row));
+                    }
+                    (c, _) => field.push(c),
+                }
+            }
+            rows
+        }
+        let csv_of = |ctx: *const NavContext| unsafe {
+            let ptr = nav_breach_log_to_csv(ctx);
+            let text = CStr::from_ptr(ptr).to_str().unwrap().to_owned();
+            free_c_string(ptr);
+            text
+        };
+
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\"\r\n"), "\"say \"\"hi\"\"\r\n\"");
+        let row = ["1", "a,b", "say \"hi\"\r\nbye"].map(|f| csv_field(f).into_owned()).join(",");
+        assert_eq!(parse_csv(&(row + "\r\n")), vec![vec!["1", "a,b", "say \"hi\"\r\nbye"]]);
+
+        let ctx = nav_context_create();
+        assert_eq!(csv_of(ctx), "timestamp,breach_flags,p_score,margin,sigma,evidence_hash\r\n");
+        let params = test_params();
+        let mut hashes = Vec::new();
+        for timestamp in 1..=3 {
+            let state = State7D { timestamp, ..test_state() };
+            hashes.push(scored(ctx, &state, &params, &[0.5, 0.0, 0.0]).hash);
+        }
+
+        let rows = parse_csv(&csv_of(ctx));
+        assert_eq!(rows[0], ["timestamp", "breach_flags", "p_score", "margin", "sigma", "evidence_hash"]);
+        assert_eq!(rows.len() - 1, unsafe { nav_breach_log_count(ctx) });
+        for (row, (timestamp, hash)) in rows[1..].iter().zip((1..=3).zip(&hashes)) {
+            assert_eq!(row[0], timestamp.to_string());
+            assert_eq!(row[1].parse::<u64>().unwrap(), BREACH_FLAG_VNC_VIOLATION);
+            assert!((row[3].parse::<f32>().unwrap() + 0.5).abs() < 1e-6);
+            assert_eq!(row[4], "0");
+            assert_eq!(&row[5], hash);
+        }
+        unsafe { nav_context_destroy(ctx) };
+
+        // The uncertainty variant logs its sigma. It only has a default
+        // context form, which other tests log to concurrently, so find its
+        // row by hash; the lock keeps a shutdown from clearing the log meanwhile.
+        let state = State7D { timestamp: 9, ..test_state() };
+        let variates = [1.0, 3.0];
+        let mut result = VerificationResult::default();
+        let hash = unsafe {
+            calculate_p_score_with_uncertainty(&state, &params, [0.5, 0.0, 0.0].as_ptr(), 1, variates.as_ptr(), 2, &mut result);
+            let hash = CStr::from_ptr(result.evidence_hash).to_str().unwrap().to_owned();
+            free_verification_result(&mut result);
+            hash
+        };
+        let rows = parse_csv(&csv_of(ptr::null()));
+        let row = rows.iter().find(|row| row[5] == hash).unwrap();
+        assert_eq!((row[0].as_str(), row[4].as_str()), ("9", "1"));
+    }
+
+    #[test]
+    fn test_breach_log_file() {
+        let _guard = lock_global_state();
+        let path = std::env::temp_dir().join(format!("nav_breach_log_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { nav_set_breach_log_file(c_path.as_ptr()) }, NavStatus::Ok);
+
+        // Timestamps tag this test's lines; other tests may breach concurrently
+        const BASE: u64 = 0x5EED_0000;
+        let threads: Vec<_> = (0..4u64)
+            .map(|t| {
+                std::thread::spawn(move || unsafe {
+                    let ctx = nav_context_create();
+                    let params = test_params();
+                    for i in 0..50 {
+                        let state = State7D { timestamp: BASE + t * 100 + i, ..test_state() };
+                        scored(ctx, &state, &params, &[0.5, 0.0, 0.0]);
+                    }
+                    nav_context_destroy(ctx);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(unsafe { nav_set_breach_log_file(ptr::null()) }, NavStatus::Ok);
+
+        // Every line is whole JSON; none are lost or interleaved
+        let text = std::fs::read_to_string(&path).unwrap();
+        let ours: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|line| line["timestamp"].as_u64().is_some_and(|t| (BASE..BASE + 400).contains(&t)))
+            .collect();
+        assert_eq!(ours.len(), 200);
+        let line = &ours[0];
+        assert_eq!(line["breach_flags"], BREACH_FLAG_VNC_VIOLATION);
+        assert!((line["margin"].as_f64().unwrap() + 0.5).abs() < 1e-6);
+        assert!(line["p_score"].as_f64().is_some());
+        assert_eq!(line["evidence_hash"].as_str().unwrap().len(), "sha256:".len() + 64);
+
+        // Disabled: nothing more is appended
+        let len = text.len();
+        let state = State7D { timestamp: BASE, ..test_state() };
+        let params = test_params();
+        assert!(safe::verify(&state, &params, &[[0.5, 0.0, 0.0]]).unwrap().breach_flags != 0);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().len(), len);
+
+        let missing_dir = CString::new("/nonexistent/nav_breach_log/log.jsonl").unwrap();
+        assert_eq!(unsafe { nav_set_breach_log_file(missing_dir.as_ptr()) }, NavStatus::IoError);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_time_period_phase() {
+        let phase_score = |timestamp: u64, time_period: u64| {
+            let state = State7D { timestamp, ..test_state() };
+            // Only the t_phase term contributes to the score
+            let params = RigorParams {
+                weights: [0.0, 1.0, 0.0, 0.0, 0.0],
+                time_period,
+                ..Default::default()
+            };
+            p_score(&state, &params, &[]).p_score
+        };
+
+        // timestamp 0 is the start of a period
+        assert_eq!(phase_score(0, 1000), 0.0);
+
+        // Continuous within the period: every tick adds exactly 1/period
+        let mut previous = phase_score(0, 1000);
+        for t in 1..1000 {
+            let phase = phase_score(t, 1000);
+            assert!((phase - previous - 0.001).abs() < 1e-5, "jump at {t}");
+            previous = phase;
+        }
+        assert!((previous - 0.999).abs() < 1e-6);
+        // ...and wraps to 0 exactly at the period boundary
+        assert_eq!(phase_score(1000, 1000), 0.0);
+        assert!((phase_score(1250, 1000) - 0.25).abs() < 1e-6);
+
+        // A zero period falls back to the default instead of dividing by zero
+        assert_eq!(phase_score(2500, 0), phase_score(2500, DEFAULT_TIME_PERIOD));
+        assert!((phase_score(2500, 0) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_speed_confidence_decay() {
+        let verify = |velocity: [f32; 3], speed_confidence_decay: f32| {
+            let state = State7D { velocity, certainty: 0.6, ..test_state() };
+            // Only the i_intent term contributes to the score
+            let params = RigorParams {
+                weights: [0.0, 0.0, 0.0, 1.0, 0.0],
+                speed_confidence_decay,
+                ..Default::default()
+            };
+            p_score(&state, &params, &[])
+        };
+        let stationary = [0.0, 0.0, 0.0];
+        let fast = [6.0, 8.0, 0.0]; // 10 m/s
+
+        // Zero decay: speed has no effect
+        let (slow, quick) = (verify(stationary, 0.0), verify(fast, 0.0));
+        assert_eq!(slow.p_score, quick.p_score);
+        assert_eq!(quick.breach_flags, 0);
+
+        // With decay the same raw certainty drops below the limit only when fast
+        let slow = verify(stationary, 0.05);
+        assert_eq!(slow.p_score, 0.6);
+        assert_eq!(slow.breach_flags & BREACH_FLAG_LOW_CERTAINTY, 0);
+        let quick = verify(fast, 0.05);
+        assert!((quick.p_score - 0.6 * (-0.5f32).exp()).abs() < 1e-6);
+        assert_ne!(quick.breach_flags & BREACH_FLAG_LOW_CERTAINTY, 0);
+        assert_eq!(quick.is_safe, 0);
+    }
+
+    #[test]
+    fn test_stopping_distance() {
+        let _guard = lock_global_state();
+        assert_eq!(stopping_distance(10.0, 5.0), 10.0);
+        assert_eq!(stopping_distance(10.0, 0.0), 0.0);
+
+        // Obstacle 10 m ahead along +X, min_margin 1: clearance 9 m.
+        // At 4 m/s^2 the stopping distance v^2 / 8 passes 9 m between 8 and 9 m/s
+        let verify = |speed: f32, max_decel: f32| unsafe {
+            let state = State7D { velocity: [speed, 0.0, 0.0], certainty: 0.9, ..test_state() };
+            let params = RigorParams { max_decel, ..test_params() };
+            let obstacles = [10.0, 0.0, 0.0];
+            let Scored { result, reason, .. } = scored(ptr::null(), &state, &params, &obstacles);
+            let mut fixed = VerificationResultFixed::default();
+            assert_eq!(calculate_p_score_fixed(&state, &params, obstacles.as_ptr(), 1, &mut fixed), NavStatus::Ok);
+            assert_eq!(fixed.breach_flags, result.breach_flags, "fixed path at {speed} m/s");
+            assert_eq!(calculate_stopping_distance(&state, &params), stopping_distance(speed, max_decel));
+            (result, reason)
+        };
+
+        for (speed, stoppable) in [(0.0, true), (2.0, true), (5.0, true), (8.0, true), (9.0, false), (12.0, false), (30.0, false)] {
+            let (result, reason) = verify(speed, 4.0);
+            assert_eq!(result.margin, 9.0);
+            assert_eq!(result.is_safe == 1, stoppable, "{speed} m/s");
+            assert_eq!(result.breach_flags & BREACH_FLAG_STOPPING_DISTANCE != 0, !stoppable, "{speed} m/s");
+            assert_eq!(reason, if stoppable { "SAFE" } else { "INSUFFICIENT_STOPPING_DISTANCE" });
+
+            // Zero max_decel keeps the old margin > 0 rule at any speed
+            let (result, reason) = verify(speed, 0.0);
+            assert_eq!((result.is_safe, result.breach_flags, reason.as_str()), (1, 0, "SAFE"));
+        }
+
+        // Inside the margin it is a VNC violation, not a stopping-distance one
+        let state = State7D { position: [9.5, 0.0, 0.0], velocity: [20.0, 0.0, 0.0], certainty: 0.9, ..test_state() };
+        let params = RigorParams { max_decel: 4.0, ..test_params() };
+        let flags = unsafe { verify_flags_only(&state, &params, [10.0, 0.0, 0.0].as_ptr(), 1) };
+        assert_eq!(flags, BREACH_FLAG_VNC_VIOLATION);
+        assert!(unsafe { calculate_stopping_distance(ptr::null(), &params) }.is_nan());
+    }
+
+    #[test]
+    fn test_safe_verify() {
+        let state = test_state();
+        let params = test_params();
+        let obstacles = [[10.0, 0.0, 0.0], [0.5, 0.0, 0.0]];
+
+        // Matches the raw FFI result field for field
+        let report = safe::verify(&state, &params, &obstacles).unwrap();
+        let Scored { result: raw, reason, hash } = scored(ptr::null(), &state, &params, obstacles.as_flattened());
+        assert_eq!(report.breach_reason, reason);
+        assert_eq!(report.evidence_hash, hash);
+        assert_eq!(report.p_score, raw.p_score);
+        assert!(!report.is_safe);
+        assert_eq!(report.breach_reason, "VNC_VIOLATION");
+        assert!(report.evidence_hash.starts_with("sha256:"));
+        assert_eq!(report.closest_obstacle_index, Some(1));
+        assert_eq!(report.breach_flags, raw.breach_flags);
+        assert_eq!(report.sigma, raw.sigma);
+        assert_eq!(report.terms, raw.terms);
+        assert_ne!(report.terms, [0.0; 5]);
+
+        let clear = safe::verify(&state, &params, &[]).unwrap();
+        assert!(clear.is_safe);
+        assert_eq!(clear.closest_obstacle_index, None);
+
+        // Errors surface as the FFI status
+        let nan = State7D { certainty: f32::NAN, ..state };
+        assert_eq!(safe::verify(&nan, &params, &obstacles), Err(NavStatus::NonFinite));
+        let high = State7D { certainty: 1.5, ..state };
+        let err = safe::verify(&high, &params, &obstacles).unwrap_err();
+        assert_eq!(err, NavStatus::OutOfRange);
+        assert_eq!(err.to_string(), "Value out of range");
+    }
+
+    #[test]
+    fn test_safe_verify_batch() {
+        let base = test_state();
+        let states: Vec<State7D> = (0..8).map(|i| State7D { position: [i as f32, 0.0, 0.0], ..base }).collect();
+        let params = test_params();
+        let obstacles = [[5.5, 0.0, 0.0]];
+
+        // One report per state, in order, each matching the single-state call
+        let reports = safe::verify_batch(&states, &params, &obstacles).unwrap();
+        assert_eq!(reports.len(), states.len());
+        for (state, report) in states.iter().zip(&reports) {
+            assert_eq!(*report, safe::verify(state, &params, &obstacles).unwrap());
+        }
+        assert_eq!(reports.iter().filter(|r| !r.is_safe).count(), 2); // x = 5 and 6
+
+        assert_eq!(safe::verify_batch(&[], &params, &obstacles), Err(NavStatus::EmptyInput));
+        let nan = [base, State7D { heading: f32::NAN, ..base }];
+        assert_eq!(safe::verify_batch(&nan, &params, &obstacles), Err(NavStatus::NonFinite));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_verify_batch() {
+        let base = test_state();
+        let states: Vec<State7D> = (0..100).map(|i| State7D { position: [i as f32 * 0.1, 0.0, 0.0], ..base }).collect();
+        let params = test_params();
+        let obstacles = vec![[3.0, 0.0, 0.0]];
+        let expected = safe::verify_batch(&states, &params, &obstacles).unwrap();
+
+        // A current-thread runtime still completes: the work runs on the blocking pool
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let reports = runtime
+            .block_on(async_api::verify_batch(states, params, obstacles.clone()))
+            .unwrap();
+        assert_eq!(reports, expected);
+
+        let empty = runtime.block_on(async_api::verify_batch(Vec::new(), params, obstacles));
+        assert_eq!(empty, Err(NavStatus::EmptyInput));
+    }
+
+    #[test]
+    fn test_fixed_point_matches_float() {
+        let to_real = |v: i32| v as f32 / NAV_FIXED_ONE as f32;
+        let mut rng = SplitMix64(7);
+        let mut uniform = |lo: f32, hi: f32| lo + (hi - lo) * (rng.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+
+        for case in 0..500 {
+            let state = State7D {
+                position: [uniform(-100.0, 100.0), uniform(-100.0, 100.0), uniform(-100.0, 100.0)],
+                velocity: [uniform(-5.0, 5.0), uniform(-5.0, 5.0), uniform(-5.0, 5.0)],
+                heading: uniform(-10.0, 10.0),
+                timestamp: uniform(0.0, 1_000_000.0) as u64,
+                certainty: uniform(0.0, 1.0),
+                fatigue: uniform(0.0, 1.0),
+            };
+            let params = RigorParams {
+                min_margin: uniform(0.0, 3.0),
+                horizon_secs: if case % 2 == 0 { 0.0 } else { uniform(0.0, 4.0) },
+                planar_mode: (case % 3 == 0) as c_int,
+                distance_metric: (case / 2 % 3) as c_int,
+                speed_confidence_decay: if case % 4 == 0 { uniform(0.0, 0.5) } else { 0.0 },
+                ..Default::default()
+            };
+            let mut obstacles = Vec::new();
+            for _ in 0..(case % 8) {
+                obstacles.extend([
+                    state.position[0] + uniform(-20.0, 20.0),
+                    state.position[1] + uniform(-20.0, 20.0),
+                    state.position[2] + uniform(-20.0, 20.0),
+                ]);
+            }
+            let count = obstacles.len() / 3;
+
+            let float = p_score(&state, &params, &obstacles);
+            let mut fixed = VerificationResultFixed::default();
+            unsafe {
+                assert_eq!(calculate_p_score_fixed(&state, &params, obstacles.as_ptr(), count, &mut fixed), NavStatus::Ok);
+            }
+
+            assert!((to_real(fixed.p_score) - float.p_score).abs() <= NAV_FIXED_TOLERANCE, "case {case}: {fixed:?} vs {float:?}");
+            if count == 0 {
+                assert_eq!(fixed.margin, i32::MAX);
+            } else {
+                assert!((to_real(fixed.margin) - float.margin).abs() <= NAV_FIXED_TOLERANCE, "case {case}");
+            }
+            assert_eq!(fixed.closest_obstacle_index, float.closest_obstacle_index, "case {case}");
+            assert_eq!(fixed.is_safe, float.is_safe, "case {case}");
+            if fixed.time_to_collision != i32::MAX {
+                assert!((to_real(fixed.time_to_collision) - float.time_to_collision).abs() <= 0.01, "case {case}");
+            }
+        }
+
+        // Bit-exact, by construction: a known input has a known result
+        let state = State7D { position: [3.0, 4.0, 0.0], timestamp: 2500, certainty: 0.75, fatigue: 0.5, ..test_state() };
+        let params = test_params();
+        let obstacles = [3.0, 7.0, 0.0];
+        let mut fixed = VerificationResultFixed::default();
+        unsafe {
+            assert_eq!(calculate_p_score_fixed(&state, &params, obstacles.as_ptr(), 1, &mut fixed), NavStatus::Ok);
+        }
+        // 5 + 0.25 + 0.4 + 0.75 + 0.5
+        assert_eq!(fixed.p_score, (6.9 * 65536.0f64).round() as i32);
+        assert_eq!(fixed.margin, 2 * NAV_FIXED_ONE);
+        assert_eq!(fixed.is_safe, 1);
+
+        // Out of the Q16.16 range
+        let far = State7D { position: [40_000.0, 0.0, 0.0], ..state };
+        unsafe {
+            assert_eq!(calculate_p_score_fixed(&far, &params, ptr::null(), 0, &mut fixed), NavStatus::OutOfRange);
+        }
+    }
+
+    #[test]
+    fn test_merge_obstacles() {
+        // Two sensors see the same two objects, slightly offset; the lidar also sees a third
+        let lidar = [0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 20.0, 0.0];
+        let camera = [0.2, 0.0, 0.0, 10.0, 0.4, 0.0];
+        let arrays = [lidar.as_ptr(), camera.as_ptr()];
+        let counts = [3usize, 2];
+        let mut out = [0.0f32; 9];
+        unsafe {
+            let merged = nav_merge_obstacles(arrays.as_ptr(), counts.as_ptr(), 2, 0.5, out.as_mut_ptr(), 3);
+            assert_eq!(merged, 3);
+        }
+        // Centroids, in order of first detection
+        let expected = [0.1, 0.0, 0.0, 10.0, 0.2, 0.0, 0.0, 20.0, 0.0];
+        for (got, want) in out.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-6, "{out:?}");
+        }
+
+        unsafe {
+            // Radius 0 keeps every distinct detection
+            let mut wide = [0.0f32; 15];
+            assert_eq!(nav_merge_obstacles(arrays.as_ptr(), counts.as_ptr(), 2, 0.0, wide.as_mut_ptr(), 5), 5);
+
+            // Too small a buffer: nothing written, required count returned negated
+            let mut small = [-1.0f32; 3];
+            assert_eq!(nav_merge_obstacles(arrays.as_ptr(), counts.as_ptr(), 2, 0.5, small.as_mut_ptr(), 1), -3);
+            assert_eq!(small, [-1.0; 3]);
+
+            // Empty input is valid
+            assert_eq!(nav_merge_obstacles(ptr::null(), ptr::null(), 0, 0.5, ptr::null_mut(), 0), 0);
+
+            // Invalid input is distinguishable from both
+            assert_eq!(
+                nav_merge_obstacles(arrays.as_ptr(), counts.as_ptr(), 2, -1.0, out.as_mut_ptr(), 3),
+                NAV_MERGE_INVALID_INPUT
+            );
+            let nan = [f32::NAN, 0.0, 0.0];
+            let bad = [nan.as_ptr()];
+            assert_eq!(
+                nav_merge_obstacles(bad.as_ptr(), [1usize].as_ptr(), 1, 0.5, out.as_mut_ptr(), 3),
+                NAV_MERGE_INVALID_INPUT
+            );
+            let null = [ptr::null::<f32>()];
+            assert_eq!(
+                nav_merge_obstacles(null.as_ptr(), [1usize].as_ptr(), 1, 0.5, out.as_mut_ptr(), 3),
+                NAV_MERGE_INVALID_INPUT
+            );
         }
     }
 }
@@ -1,17 +1,214 @@
 // NAVΛ Dashboard - Rust Asset Server
 // Streams large files in chunks to prevent Unity memory crashes
 
-use tokio::net::TcpListener;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use std::path::Path;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::TlsAcceptor;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
 use std::fs::File;
-use std::io::{self, Read, BufReader};
+use std::io::{Read, BufReader, Seek, SeekFrom, Write};
+use std::pin::Pin;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Serialize, Deserialize};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
 
-const CHUNK_SIZE: usize = 2 * 1024 * 1024; // 2MB chunks
+const CHUNK_SIZE: usize = 2 * 1024 * 1024; // 2MB chunks (default)
+const MIN_CHUNK_SIZE: usize = 64 * 1024; // 64KB
+const MAX_CHUNK_SIZE: usize = 32 * 1024 * 1024; // 32MB
 const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_BIND_ADDR: &str = "0.0.0.0";
+const DEFAULT_ASSET_ROOT: &str = "./Assets";
+const MAX_HEADER_SIZE: usize = 16 * 1024; // 16KB request head cap
+const MAX_UPLOAD_SIZE: u64 = 100 * 1024 * 1024; // 100MB cap on upload bodies and stored (decompressed) assets
+const FRAMED_CONTENT_TYPE: &str = "application/x-navlambda-stream"; // StreamingHeader + file bytes
+const MAX_VERIFY_BODY: usize = 1024 * 1024; // 1MB cap on `POST /verify` JSON (state, params, obstacles) and each `/ws/verify` message
+/// `schema_version` of the `POST /verify` JSON (request and response, as
+/// served by `GET /verify/schema`)
+///
+/// Compatibility policy: within a version, changes are additive only: new
+/// optional request fields (defaulting to the old behavior) and new
+/// response fields. Clients must ignore response fields they don't know.
+/// Removing, renaming or retyping a field, changing its meaning or making a
+/// request field required bumps the version.
+///
+/// Changelog (newest first):
+/// - 2: `evidence_hash` is prefixed with its algorithm (`sha256:<hex>`);
+///   request `params.hash_algo` selects it. Later, additively: response
+///   `sigma` and `terms`, completing the `VerificationResult` mirror.
+/// - 1: Initial schema. Request `state`, `params`, `obstacles`; response
+///   mirrors the core's `VerificationResult`.
+const VERIFY_SCHEMA_VERSION: u32 = 2;
+const DEFAULT_MAX_CONNECTIONS: usize = 128;
+const DEFAULT_LISTEN_BACKLOG: u32 = 1024; // Pending accepts; the kernel caps it (Linux: net.core.somaxconn)
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(120); // Per chunk; generous for slow links
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5); // Idle time between requests
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30); // Drain window on SIGINT/SIGTERM
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500); // Between streaming progress reports
+const WS_PING_INTERVAL: Duration = Duration::from_secs(15); // Keepalive on `/ws/verify`; two silent intervals drop the socket
+const WATCH_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15); // Blank line on an idle `/watch/Assets/` stream
 
-#[derive(Serialize, Deserialize, Debug)]
+// Runtime configuration, read once from the environment at startup
+#[derive(Debug, Clone)]
+struct ServerConfig {
+    asset_roots: Vec<PathBuf>, // Searched in order on reads; never empty
+    chunk_size: usize, // Streaming buffer size in bytes
+    max_connections: usize,
+    overload_policy: OverloadPolicy,
+    max_kbps_per_client: u64, // Per-connection (not global) cap in KB/s; 0 = unlimited
+    verbose: bool,            // Streaming progress and per-connection logs
+    on_progress: Option<ProgressCallback>, // Streaming progress sink (log_progress when verbose)
+    tls_cert: Option<PathBuf>, // PEM certificate chain; TLS is on when both paths are set
+    tls_key: Option<PathBuf>,  // PEM private key
+    auth_token: Option<String>, // Bearer token required for uploads when set
+    require_auth_for_get: bool, // Also require the token for GET/HEAD
+    read_timeout: Duration,     // Deadline for the TLS handshake and first request head
+    write_timeout: Duration,    // Deadline for each streamed chunk to be accepted by the client
+    mime_map: Option<PathBuf>,  // JSON {"ext": "type"} file merged over the built-in content types
+    mime_types: BTreeMap<String, String>, // Overrides loaded from `mime_map` (lowercase ext, no dot)
+    cors_origin: Option<String>, // Access-Control-Allow-Origin value; None disables CORS
+    listen_backlog: u32,        // Accept queue length passed to listen(2)
+    tcp_nodelay: bool,          // Disable Nagle on accepted streams (small header exchanges)
+}
+
+/// What to do with a new connection when `max_connections` are already active
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverloadPolicy {
+    Queue,  // Stop accepting until a slot frees up (excess waits in the listen backlog)
+    Reject, // Answer 503 immediately
+}
+
+impl OverloadPolicy {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "queue" => Some(OverloadPolicy::Queue),
+            "reject" | "503" => Some(OverloadPolicy::Reject),
+            _ => None,
+        }
+    }
+}
+
+impl ServerConfig {
+    fn from_env() -> Self {
+        // ASSET_ROOTS (colon-separated, semicolon on Windows) takes precedence over
+        // the single ASSET_ROOT
+        let mut roots: Vec<PathBuf> = std::env::var_os("ASSET_ROOTS")
+            .map(|value| std::env::split_paths(&value).filter(|p| !p.as_os_str().is_empty()).collect())
+            .unwrap_or_default();
+        if roots.is_empty() {
+            let root = std::env::var("ASSET_ROOT").unwrap_or_else(|_| DEFAULT_ASSET_ROOT.to_string());
+            roots.push(PathBuf::from(root));
+        }
+        // Resolve to absolute paths when the directories exist so logs are unambiguous
+        let asset_roots = roots
+            .into_iter()
+            .map(|root| root.canonicalize().unwrap_or(root))
+            .collect();
+        let chunk_size = parse_chunk_size(std::env::var("CHUNK_SIZE_KB").ok().as_deref());
+        let max_connections = env_parse::<usize>("MAX_CONNECTIONS")
+            .filter(|&n| n > 0)
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        let overload_policy = match std::env::var("OVERLOAD_POLICY") {
+            Ok(value) => OverloadPolicy::parse(&value).unwrap_or_else(|| {
+                eprintln!("[NAVΛ Server] Warning: ignoring OVERLOAD_POLICY={} (expected queue or reject)", value);
+                OverloadPolicy::Queue
+            }),
+            Err(_) => OverloadPolicy::Queue,
+        };
+        let max_kbps_per_client = env_parse::<u64>("MAX_KBPS_PER_CLIENT").unwrap_or(0);
+        let verbose = std::env::var("VERBOSE").is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false"));
+        let tls_cert = std::env::var_os("TLS_CERT").map(PathBuf::from);
+        let tls_key = std::env::var_os("TLS_KEY").map(PathBuf::from);
+        ServerConfig {
+            asset_roots,
+            chunk_size,
+            max_connections,
+            overload_policy,
+            max_kbps_per_client,
+            verbose,
+            on_progress: verbose.then_some(log_progress as ProgressCallback),
+            tls_cert,
+            tls_key,
+            auth_token: std::env::var("AUTH_TOKEN").ok().filter(|t| !t.is_empty()),
+            require_auth_for_get: std::env::var("REQUIRE_AUTH_FOR_GET")
+                .is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false")),
+            read_timeout: env_parse::<u64>("READ_TIMEOUT_SECS")
+                .filter(|&secs| secs > 0)
+                .map_or(DEFAULT_READ_TIMEOUT, Duration::from_secs),
+            write_timeout: env_parse::<u64>("WRITE_TIMEOUT_SECS")
+                .filter(|&secs| secs > 0)
+                .map_or(DEFAULT_WRITE_TIMEOUT, Duration::from_secs),
+            mime_map: std::env::var_os("MIME_MAP").map(PathBuf::from),
+            mime_types: BTreeMap::new(), // Filled by load_mime_map at startup
+            // Unset allows any origin; set-but-empty turns CORS off
+            cors_origin: match std::env::var("CORS_ALLOW_ORIGIN") {
+                Ok(origin) => Some(origin.trim().to_string()).filter(|o| !o.is_empty()),
+                Err(_) => Some("*".to_string()),
+            },
+            listen_backlog: env_parse::<u32>("LISTEN_BACKLOG")
+                .filter(|&backlog| backlog > 0)
+                .unwrap_or(DEFAULT_LISTEN_BACKLOG),
+            // On unless explicitly disabled
+            tcp_nodelay: std::env::var("TCP_NODELAY").map_or(true, |v| !matches!(v.trim(), "0" | "false")),
+        }
+    }
+}
+
+/// Parse an optional environment variable, warning (and returning None) when it is malformed
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    let parsed = value.trim().parse::<T>().ok();
+    if parsed.is_none() {
+        eprintln!("[NAVΛ Server] Warning: ignoring malformed {}={}", name, value);
+    }
+    parsed
+}
+
+/// Parse `CHUNK_SIZE_KB`, falling back to the 2MB default when unset or outside 64KB-32MB
+fn parse_chunk_size(value: Option<&str>) -> usize {
+    let Some(value) = value else {
+        return CHUNK_SIZE;
+    };
+    match value.trim().parse::<usize>().ok().and_then(|kb| kb.checked_mul(1024)) {
+        Some(bytes) if (MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&bytes) => bytes,
+        _ => {
+            eprintln!(
+                "[NAVΛ Server] Warning: ignoring CHUNK_SIZE_KB={} (expected {}-{})",
+                value,
+                MIN_CHUNK_SIZE / 1024,
+                MAX_CHUNK_SIZE / 1024
+            );
+            CHUNK_SIZE
+        }
+    }
+}
+
+/// Binary preamble of a framed asset response (`GET /Assets/<name>?framed=1`)
+///
+/// Byte layout, all integers little-endian on every platform:
+///
+/// | Offset    | Size | Field                                  |
+/// |-----------|------|----------------------------------------|
+/// | 0         | 4    | `name_len`: u32, bytes of `file_name`  |
+/// | 4         | n    | `file_name`: UTF-8, no terminator      |
+/// | 4 + n     | 8    | `file_size`: u64                       |
+/// | 12 + n    | 4    | `chunk_size`: u32                      |
+/// | 16 + n    | 1    | `is_streaming`: u8                     |
+///
+/// The file's bytes follow immediately, at offset `17 + n`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct StreamingHeader {
     file_name: String,
     file_size: u64,
@@ -19,173 +216,3585 @@ struct StreamingHeader {
     is_streaming: u8, // 1 = Streaming, 0 = Standard Transfer
 }
 
+impl StreamingHeader {
+    /// Fixed part of the encoding (everything but the name)
+    const FIXED_LEN: usize = 4 + 8 + 4 + 1;
+
+    fn encoded_len(&self) -> usize {
+        Self::FIXED_LEN + self.file_name.len()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.extend_from_slice(&(self.file_name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.file_name.as_bytes());
+        bytes.extend_from_slice(&self.file_size.to_le_bytes());
+        bytes.extend_from_slice(&self.chunk_size.to_le_bytes());
+        bytes.push(self.is_streaming);
+        bytes
+    }
+
+    /// Decode a header from the start of `bytes`, returning it with the
+    /// number of bytes it occupied (where the file data starts)
+    ///
+    /// None if `bytes` is too short or the name is not UTF-8.
+    #[cfg_attr(not(test), allow(dead_code))] // The decoding side lives in the clients
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let name_len = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let name_end = 4usize.checked_add(name_len)?;
+        let file_name = std::str::from_utf8(bytes.get(4..name_end)?).ok()?.to_string();
+        let fixed = bytes.get(name_end..name_end + 13)?;
+        let header = StreamingHeader {
+            file_name,
+            file_size: u64::from_le_bytes(fixed[..8].try_into().ok()?),
+            chunk_size: u32::from_le_bytes(fixed[8..12].try_into().ok()?),
+            is_streaming: fixed[12],
+        };
+        Some((header, name_end + 13))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct ErrorResponse {
     error: String,
 }
 
+/// Body of `GET /health` and `GET /healthz`
+#[derive(Serialize, Deserialize, Debug)]
+struct HealthResponse {
+    status: String,
+    uptime_secs: u64,
+}
+
+/// Body of `POST /verify`
+///
+/// ```json
+/// {
+///   "state": {"position": [x, y, z], "velocity": [vx, vy, vz], "heading": 0.0,
+///             "timestamp": 0, "certainty": 0.9, "fatigue": 0.9},
+///   "params": {"alpha": 1.0, "min_margin": 0.5, ...},
+///   "obstacles": [[x, y, z], ...]
+/// }
+/// ```
+///
+/// `state.position`, `state.certainty` and `state.fatigue` are required; the
+/// other state fields default to zero. `params` and every field in it are
+/// optional and default to `RigorParams::default()`. Unknown fields are
+/// rejected so typos don't silently fall back to defaults.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct VerifyRequest {
+    state: VerifyState,
+    #[serde(default)]
+    params: VerifyParams,
+    #[serde(default)]
+    obstacles: Vec<[f32; 3]>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct VerifyState {
+    position: [f32; 3],
+    #[serde(default)]
+    velocity: [f32; 3],
+    #[serde(default)]
+    heading: f32,
+    #[serde(default)]
+    timestamp: u64,
+    certainty: f32,
+    fatigue: f32,
+}
+
+/// `RigorParams` with the C int flags as JSON booleans and the distance
+/// metric and hash algorithm by name
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct VerifyParams {
+    alpha: f32,
+    min_margin: f32,
+    weights: [f32; 5],
+    horizon_secs: f32,
+    planar_mode: bool,
+    reference_origin: [f32; 3],
+    clamp_cognitive: bool,
+    time_period: u64,
+    speed_confidence_decay: f32,
+    soft_cognitive_breach: bool,
+    max_decel: f32,
+    distance_metric: DistanceMetric,
+    hash_algo: HashAlgo,
+}
+
+/// `RigorParams::distance_metric` (`NAV_METRIC_*`) as a lowercase name
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+enum DistanceMetric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl From<DistanceMetric> for std::os::raw::c_int {
+    fn from(metric: DistanceMetric) -> Self {
+        match metric {
+            DistanceMetric::Euclidean => nav_lambda_core::NAV_METRIC_EUCLIDEAN,
+            DistanceMetric::Manhattan => nav_lambda_core::NAV_METRIC_MANHATTAN,
+            DistanceMetric::Chebyshev => nav_lambda_core::NAV_METRIC_CHEBYSHEV,
+        }
+    }
+}
+
+/// `RigorParams::hash_algo` (`NAV_HASH_*`) as its evidence-hash prefix
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+enum HashAlgo {
+    #[default]
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl From<HashAlgo> for std::os::raw::c_int {
+    fn from(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha256 => nav_lambda_core::NAV_HASH_SHA256,
+            HashAlgo::Sha512 => nav_lambda_core::NAV_HASH_SHA512,
+            HashAlgo::Blake3 => nav_lambda_core::NAV_HASH_BLAKE3,
+        }
+    }
+}
+
+impl Default for VerifyParams {
+    fn default() -> Self {
+        let params = nav_lambda_core::RigorParams::default();
+        VerifyParams {
+            alpha: params.alpha,
+            min_margin: params.min_margin,
+            weights: params.weights,
+            horizon_secs: params.horizon_secs,
+            planar_mode: params.planar_mode != 0,
+            reference_origin: params.reference_origin,
+            clamp_cognitive: params.clamp_cognitive != 0,
+            time_period: params.time_period,
+            speed_confidence_decay: params.speed_confidence_decay,
+            soft_cognitive_breach: params.soft_cognitive_breach != 0,
+            max_decel: params.max_decel,
+            distance_metric: DistanceMetric::default(),
+            hash_algo: HashAlgo::default(),
+        }
+    }
+}
+
+impl From<VerifyParams> for nav_lambda_core::RigorParams {
+    fn from(params: VerifyParams) -> Self {
+        nav_lambda_core::RigorParams {
+            alpha: params.alpha,
+            min_margin: params.min_margin,
+            weights: params.weights,
+            horizon_secs: params.horizon_secs,
+            planar_mode: params.planar_mode.into(),
+            reference_origin: params.reference_origin,
+            clamp_cognitive: params.clamp_cognitive.into(),
+            time_period: params.time_period,
+            speed_confidence_decay: params.speed_confidence_decay,
+            soft_cognitive_breach: params.soft_cognitive_breach.into(),
+            max_decel: params.max_decel,
+            distance_metric: params.distance_metric.into(),
+            hash_algo: params.hash_algo.into(),
+        }
+    }
+}
+
+/// Response of `POST /verify`: every field of the core's
+/// `VerificationResult`, with `closest_obstacle_index` null when there are
+/// no obstacles
+#[derive(Serialize, Deserialize, Debug)]
+struct VerifyResponse {
+    schema_version: u32, // VERIFY_SCHEMA_VERSION
+    p_score: f32,
+    is_safe: bool,
+    margin: f32,
+    sigma: f32,
+    breach_reason: String,
+    evidence_hash: String,
+    time_to_collision: f32, // Seconds until predicted breach (f32::MAX if none)
+    closest_obstacle_index: Option<usize>,
+    closest_obstacle_pos: [f32; 3],
+    breach_flags: u64,
+    severity: i32,
+    terms: [f32; 5], // Weighted contributions, same order as `params.weights`; sum to p_score
+}
+
+/// JSON Schema (draft 2020-12) of the `POST /verify` request and response,
+/// served by `GET /verify/schema`
+///
+/// Hand-written to match `VerifyRequest` and `VerifyResponse`; defaults come
+/// from `VerifyParams::default()` so they can't drift from the core.
+fn verify_schema() -> serde_json::Value {
+    use serde_json::json;
+    let defaults = VerifyParams::default();
+    let vec3 = json!({"type": "array", "items": {"type": "number"}, "minItems": 3, "maxItems": 3});
+    let number = |default: f32| json!({"type": "number", "default": default});
+    let flag = |default: bool| json!({"type": "boolean", "default": default});
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "schema_version": VERIFY_SCHEMA_VERSION,
+        "request": {
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["state"],
+            "properties": {
+                "state": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["position", "certainty", "fatigue"],
+                    "properties": {
+                        "position": vec3,
+                        "velocity": vec3,
+                        "heading": number(0.0),
+                        "timestamp": {"type": "integer", "minimum": 0, "default": 0},
+                        "certainty": {"type": "number"},
+                        "fatigue": {"type": "number"},
+                    },
+                },
+                "params": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "alpha": number(defaults.alpha),
+                        "min_margin": number(defaults.min_margin),
+                        "weights": {
+                            "type": "array",
+                            "items": {"type": "number"},
+                            "minItems": 5,
+                            "maxItems": 5,
+                            "default": defaults.weights,
+                        },
+                        "horizon_secs": number(defaults.horizon_secs),
+                        "planar_mode": flag(defaults.planar_mode),
+                        "reference_origin": vec3,
+                        "clamp_cognitive": flag(defaults.clamp_cognitive),
+                        "time_period": {"type": "integer", "minimum": 0, "default": defaults.time_period},
+                        "speed_confidence_decay": number(defaults.speed_confidence_decay),
+                        "soft_cognitive_breach": flag(defaults.soft_cognitive_breach),
+                        "max_decel": number(defaults.max_decel),
+                        "distance_metric": {
+                            "enum": ["euclidean", "manhattan", "chebyshev"],
+                            "default": "euclidean",
+                        },
+                        "hash_algo": {"enum": ["sha256", "sha512", "blake3"], "default": "sha256"},
+                    },
+                },
+                "obstacles": {"type": "array", "items": vec3, "default": []},
+            },
+        },
+        "response": {
+            "type": "object",
+            "required": [
+                "schema_version", "p_score", "is_safe", "margin", "sigma", "breach_reason", "evidence_hash",
+                "time_to_collision", "closest_obstacle_index", "closest_obstacle_pos", "breach_flags", "severity",
+                "terms",
+            ],
+            "properties": {
+                "schema_version": {"const": VERIFY_SCHEMA_VERSION},
+                "p_score": {"type": "number"},
+                "is_safe": {"type": "boolean"},
+                "margin": {"type": "number"},
+                "sigma": {"type": "number"},
+                "breach_reason": {"type": "string"},
+                "evidence_hash": {
+                    "type": "string",
+                    "pattern": "^(sha256:[0-9a-f]{64}|sha512:[0-9a-f]{128}|blake3:[0-9a-f]{64})$",
+                },
+                "time_to_collision": {"type": "number"},
+                "closest_obstacle_index": {"type": ["integer", "null"], "minimum": 0},
+                "closest_obstacle_pos": vec3,
+                "breach_flags": {"type": "integer", "minimum": 0},
+                "severity": {"type": "integer"},
+                "terms": {"type": "array", "items": {"type": "number"}, "minItems": 5, "maxItems": 5},
+            },
+        },
+    })
+}
+
+// Process start, pinned at the top of main so uptime covers startup
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+fn uptime() -> Duration {
+    STARTED.get_or_init(Instant::now).elapsed()
+}
+
+/// Core context behind `/verify` and `/ws/verify`, created on first use and
+/// kept for the life of the process
+///
+/// The server's breach log, hysteresis and obstacle cap live here rather
+/// than in the core's default context, which belongs to the global
+/// `calculate_p_score*` API.
+fn verify_context() -> &'static nav_lambda_core::NavContext {
+    static CONTEXT: OnceLock<&'static nav_lambda_core::NavContext> = OnceLock::new();
+    // SAFETY: `nav_context_create` returns a live, initialized context, and
+    // it is never destroyed
+    CONTEXT.get_or_init(|| unsafe { &*nav_lambda_core::nav_context_create() })
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    uptime();
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| DEFAULT_PORT.to_string())
         .parse::<u16>()?;
 
-    let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    println!("[NAVΛ Server] Listening on port {}", port);
+    let mut config = ServerConfig::from_env();
+    // Fail fast on an unreadable MIME_MAP rather than mislabel assets later
+    config.mime_types = load_mime_map(config.mime_map.as_deref())?;
+    let config = Arc::new(config);
+    for root in config.asset_roots.iter().filter(|root| !root.is_dir()) {
+        eprintln!("[NAVΛ Server] Warning: asset root is not a directory: {}", root.display());
+    }
+
+    // Fail fast on a bad cert/key rather than at the first handshake
+    let tls = load_tls_acceptor(&config)?;
+
+    let bind_addr = std::env::var("BIND_ADDR").unwrap_or_else(|_| DEFAULT_BIND_ADDR.to_string());
+    let bind_addr = parse_bind_addr(&bind_addr, port)?;
+    let listener = bind_listener(bind_addr, config.listen_backlog)?;
+    println!(
+        "[NAVΛ Server] Listening on {} ({}, backlog {}, TCP_NODELAY {})",
+        listener.local_addr()?,
+        if tls.is_some() { "TLS" } else { "plain TCP" },
+        config.listen_backlog,
+        if config.tcp_nodelay { "on" } else { "off" }
+    );
+    for (i, root) in config.asset_roots.iter().enumerate() {
+        println!("[NAVΛ Server] Asset root {}: {}", i + 1, root.display());
+    }
+    match writable_root(&config) {
+        Some(root) => println!("[NAVΛ Server] Uploads go to: {}", root.display()),
+        None => eprintln!("[NAVΛ Server] Warning: no writable asset root; uploads and deletes are refused"),
+    }
+    println!("[NAVΛ Server] Chunk size: {} KB", config.chunk_size / 1024);
+    if let Some(path) = &config.mime_map {
+        println!("[NAVΛ Server] Content types: {} override(s) from {}", config.mime_types.len(), path.display());
+    }
+    if config.max_kbps_per_client > 0 {
+        println!("[NAVΛ Server] Bandwidth cap: {} KB/s per connection", config.max_kbps_per_client);
+    }
+    println!(
+        "[NAVΛ Server] Connection limit: {} ({:?} when full)",
+        config.max_connections, config.overload_policy
+    );
     println!("[NAVΛ Server] Ready to stream assets to Unity Dashboard");
 
+    let slots = Arc::new(Semaphore::new(config.max_connections));
+    let mut connections = JoinSet::new();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
     loop {
-        match listener.accept().await {
-            Ok((stream, addr)) => {
-                println!("[NAVΛ Server] New connection from: {}", addr);
-                tokio::spawn(async move {
-                    if let Err(e) = handle_client(stream).await {
-                        eprintln!("[NAVΛ Server] Error handling client: {}", e);
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    if config.verbose {
+                        println!("[NAVΛ Server] New connection from: {}", addr);
                     }
-                });
+                    if let Err(e) = stream.set_nodelay(config.tcp_nodelay) {
+                        eprintln!("[NAVΛ Server] Warning: could not set TCP_NODELAY for {}: {}", addr, e);
+                    }
+                    let permit = match Arc::clone(&slots).try_acquire_owned() {
+                        Ok(permit) => permit,
+                        Err(_) if config.overload_policy == OverloadPolicy::Reject => {
+                            eprintln!(
+                                "[NAVΛ Server] Connection limit ({}) reached, rejecting {}",
+                                config.max_connections, addr
+                            );
+                            let tls = tls.clone();
+                            let read_timeout = config.read_timeout;
+                            connections.spawn(async move {
+                                // Bounded like a normal accept, so stalled handshakes can't pile up under a flood
+                                let reject = async {
+                                    let mut stream = accept_transport(stream, tls.as_ref()).await?;
+                                    write_error_response(&mut stream, "503 Service Unavailable", "Server busy").await
+                                };
+                                let _ = tokio::time::timeout(read_timeout, reject).await;
+                            });
+                            continue;
+                        }
+                        Err(_) => {
+                            eprintln!(
+                                "[NAVΛ Server] Connection limit ({}) reached, queueing {}",
+                                config.max_connections, addr
+                            );
+                            tokio::select! {
+                                permit = Arc::clone(&slots).acquire_owned() => permit?,
+                                _ = &mut shutdown => break,
+                            }
+                        }
+                    };
+                    let config = Arc::clone(&config);
+                    let tls = tls.clone();
+                    connections.spawn(async move {
+                        let _permit = permit; // Released when the connection ends
+                        let stage = if tls.is_some() { "TLS handshake" } else { "Connection setup" };
+                        let handshake = tokio::time::timeout(config.read_timeout, accept_transport(stream, tls.as_ref()));
+                        let stream = match handshake.await {
+                            Ok(Ok(stream)) => stream,
+                            Err(_) => {
+                                eprintln!("[NAVΛ Server] {} with {} timed out", stage, addr);
+                                return;
+                            }
+                            Ok(Err(e)) => {
+                                eprintln!("[NAVΛ Server] {} with {} failed: {}", stage, addr, e);
+                                return;
+                            }
+                        };
+                        if let Err(e) = handle_client(stream, &config, &addr.to_string()).await {
+                            report_connection_error(&config, &addr.to_string(), e.as_ref());
+                        }
+                    });
+                }
+                Err(e) => {
+                    eprintln!("[NAVΛ Server] Accept error: {}", e);
+                }
+            },
+            // Reap finished connections so the set doesn't grow unbounded
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    // Stop accepting, then let in-flight streams finish
+    drop(listener);
+    println!("[NAVΛ Server] Shutting down, draining {} connections", connections.len());
+    let drained = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!(
+            "[NAVΛ Server] Shutdown timeout, aborting {} connections",
+            connections.len()
+        );
+        connections.shutdown().await;
+    }
+    println!("[NAVΛ Server] Shutdown complete");
+    Ok(())
+}
+
+/// Socket address for `BIND_ADDR`: an IPv4 or IPv6 literal, the latter with
+/// or without brackets (`127.0.0.1`, `::1`, `[::]`)
+///
+/// Binding `[::]` is dual-stack where the OS allows it (the Linux default),
+/// accepting IPv4 clients as mapped addresses. Host names are not resolved.
+fn parse_bind_addr(addr: &str, port: u16) -> Result<SocketAddr, String> {
+    let addr = addr.trim();
+    let literal = addr.strip_prefix('[').and_then(|a| a.strip_suffix(']')).unwrap_or(addr);
+    literal
+        .parse::<IpAddr>()
+        .map(|ip| SocketAddr::new(ip, port))
+        .map_err(|_| format!("invalid BIND_ADDR {:?} (expected an IPv4 or IPv6 address)", addr))
+}
+
+/// Bind a non-blocking listener with an explicit accept backlog
+///
+/// `TcpListener::bind` always asks for 1024; a socket2 socket lets
+/// LISTEN_BACKLOG raise it for connection storms (or lower it to shed load
+/// sooner). SO_REUSEADDR is set on Unix as tokio does, so a restart does not
+/// wait out TIME_WAIT.
+fn bind_listener(addr: SocketAddr, backlog: u32) -> std::io::Result<TcpListener> {
+    let socket = socket2::Socket::new(socket2::Domain::for_address(addr), socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(i32::try_from(backlog).unwrap_or(i32::MAX))?;
+    TcpListener::from_std(socket.into())
+}
+
+/// Build the TLS acceptor when `TLS_CERT` and `TLS_KEY` are both set
+///
+/// Returns Ok(None) for plain TCP; setting only one of the two, or paths
+/// that don't hold a usable PEM chain/key, is a startup error.
+fn load_tls_acceptor(config: &ServerConfig) -> Result<Option<TlsAcceptor>, Box<dyn std::error::Error>> {
+    let (cert_path, key_path) = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => return Err("TLS_CERT and TLS_KEY must be set together".into()),
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("failed to load TLS_CERT {}: {}", cert_path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("TLS_CERT {} contains no certificates", cert_path.display()).into());
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("failed to load TLS_KEY {}: {}", key_path.display(), e))?;
+
+    let tls_config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("invalid TLS certificate/key pair: {}", e))?;
+    Ok(Some(TlsAcceptor::from(Arc::new(tls_config))))
+}
+
+/// Methods and request headers a cross-origin dashboard may use
+const CORS_ALLOW_METHODS: &str = "GET, HEAD, POST, DELETE, OPTIONS";
+const CORS_ALLOW_HEADERS: &str = "Authorization, Content-Type, Range, If-None-Match, If-Modified-Since";
+
+/// `Access-Control-*` response headers for asset responses (empty when CORS is off)
+///
+/// Also exposes the validator and checksum headers, which `fetch()` hides
+/// from scripts by default.
+fn cors_headers(config: &ServerConfig) -> String {
+    match config.cors_origin.as_deref() {
+        None => String::new(),
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Expose-Headers: ETag, Last-Modified, Content-Range, X-Content-SHA256\r\n{}",
+            origin,
+            // A specific origin makes the response vary by requester
+            if origin == "*" { "" } else { "Vary: Origin\r\n" }
+        ),
+    }
+}
+
+/// Load the `MIME_MAP` overrides: a JSON object of extension -> content type
+///
+/// Keys are matched case-insensitively, with or without a leading dot
+/// (`"glb"`, `".GLB"`). Returns an empty map when no file is configured.
+fn load_mime_map(path: Option<&Path>) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    let Some(path) = path else {
+        return Ok(BTreeMap::new());
+    };
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read MIME_MAP {}: {}", path.display(), e))?;
+    let map: BTreeMap<String, String> = serde_json::from_str(&text)
+        .map_err(|e| format!("invalid MIME_MAP {} (expected {{\"ext\": \"type\"}}): {}", path.display(), e))?;
+    Ok(map
+        .into_iter()
+        .map(|(ext, content_type)| (ext.trim_start_matches('.').to_lowercase(), content_type))
+        .collect())
+}
+
+/// Plain TCP or TLS behind one type, so handlers stay generic over the transport
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// Run the TLS handshake when enabled
+async fn accept_transport(stream: TcpStream, tls: Option<&TlsAcceptor>) -> std::io::Result<Box<dyn Transport>> {
+    match tls {
+        Some(acceptor) => Ok(Box::new(acceptor.accept(stream).await?)),
+        None => Ok(Box::new(stream)),
+    }
+}
+
+/// Resolves on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("[NAVΛ Server] Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
             }
             Err(e) => {
-                eprintln!("[NAVΛ Server] Accept error: {}", e);
+                eprintln!("[NAVΛ Server] Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
             }
         }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
 }
 
-async fn handle_client(mut stream: tokio::net::TcpStream) -> Result<(), Box<dyn std::error::Error>> {
-    // 1. Read request header (small packet)
-    let mut header_buf = vec![0u8; 512];
-    let bytes_read = stream.read(&mut header_buf).await?;
-    
-    if bytes_read == 0 {
-        return Ok(()); // Connection closed
+async fn handle_client<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    config: &ServerConfig,
+    peer: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _active = ActiveConnection::new();
+    let mut stream = CountingStream::new(stream);
+    let mut pending = Vec::new(); // Bytes read past the previous request's head (pipelining)
+    let mut first_request = true;
+
+    loop {
+        // 1. Read request header (up to the blank line). The first request must
+        // arrive within the read timeout (slowloris guard); between requests an
+        // idle keep-alive connection is closed after KEEP_ALIVE_TIMEOUT
+        let read = read_request_head(&mut stream, std::mem::take(&mut pending));
+        let limit = if first_request { config.read_timeout } else { KEEP_ALIVE_TIMEOUT };
+        let request_head = match tokio::time::timeout(limit, read).await {
+            Ok(result) => result?,
+            Err(_) if first_request => {
+                eprintln!("[NAVΛ Server] Read timeout from {} after {:?}, closing", peer, limit);
+                return Ok(());
+            }
+            Err(_) => return Ok(()), // Idle
+        };
+        first_request = false;
+
+        let started = Instant::now();
+        let sent_before = stream.written;
+        let (head, body) = match request_head {
+            RequestHead::Complete { head, body } => (head, body),
+            RequestHead::Closed => return Ok(()), // Connection closed
+            RequestHead::TooLarge => {
+                write_error_response(&mut stream, "431 Request Header Fields Too Large", "Request headers too large").await?;
+                METRICS.record_response(431, stream.written - sent_before);
+                log_access("-", "-", peer, 431, stream.written - sent_before, started);
+                return Ok(());
+            }
+        };
+
+        // 2. Parse request (simplified - in production use HTTP)
+        let request_str = String::from_utf8_lossy(&head);
+        let request_line = request_str.lines().next().unwrap_or("");
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("-");
+        let path = parts.next().unwrap_or("-");
+
+        let target = is_valid_request_line(request_line).then(|| parse_request_target(path)).flatten();
+        let status = if let Some(target) = target {
+            // Errors become io::Error so no (non-Send) error box is held across the awaits below
+            match handle_request(&mut stream, config, &request_str, method, &target, &body).await.map_err(into_io_error) {
+                Ok(status) => status,
+                // Nothing sent yet: the client gets a 500 instead of a dropped connection
+                // (unless it is the one that went away)
+                Err(e) if stream.written == sent_before && !is_client_disconnect(&e) => {
+                    eprintln!("[NAVΛ Server] {} {} from {} failed: {}", method, path, peer, e);
+                    write_error_response(&mut stream, "500 Internal Server Error", "Internal server error").await?;
+                    METRICS.record_response(500, stream.written - sent_before);
+                    log_access(method, path, peer, 500, stream.written - sent_before, started);
+                    return Ok(());
+                }
+                // Mid-response: the status line is gone, so closing early (short
+                // body or missing last chunk) is the only signal left
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            write_error_response(&mut stream, "400 Bad Request", "Malformed request line").await?;
+            400
+        };
+        METRICS.record_response(status, stream.written - sent_before);
+        log_access(method, path, peer, status, stream.written - sent_before, started);
+
+        // Only bodiless GET/HEAD exchanges can safely continue; an upload body may be
+        // partly unread, after a 400 the stream position is unknown, and a 101 means
+        // the connection was a WebSocket until it closed. A GET/HEAD announcing a body
+        // is closed too, or that body would be parsed as the next request
+        if !matches!(method, "GET" | "HEAD" | "OPTIONS")
+            || matches!(status, 101 | 400)
+            || !is_keep_alive(&request_str)
+            || declares_body(&request_str)
+        {
+            return Ok(());
+        }
+        pending = body;
+    }
+}
+
+/// Convert a request error to `io::Error`, keeping the kind of I/O errors
+fn into_io_error(e: Box<dyn std::error::Error>) -> std::io::Error {
+    match e.downcast::<std::io::Error>() {
+        Ok(e) => *e,
+        Err(e) => std::io::Error::other(e.to_string()),
     }
+}
 
-    // 2. Parse request (simplified - in production use HTTP)
-    let request_str = String::from_utf8_lossy(&header_buf[..bytes_read]);
-    
-    // Simple HTTP-like parsing
-    if request_str.starts_with("GET /Assets/") {
-        // Extract filename
-        let path_start = request_str.find("/Assets/").unwrap() + 7;
-        let path_end = request_str[path_start..].find(" HTTP").unwrap_or(request_str.len() - path_start);
-        let file_name = &request_str[path_start..path_start + path_end];
-        
-        // Handle streaming request
-        handle_streaming_request(stream, file_name).await?;
-    } else if request_str.starts_with("POST /Assets/") {
-        // Handle file upload (small files)
-        handle_file_upload(stream, &request_str).await?;
+/// Whether a connection error just means the client hung up mid-response
+fn is_client_disconnect(e: &(dyn std::error::Error + 'static)) -> bool {
+    e.downcast_ref::<std::io::Error>().is_some_and(|e| {
+        matches!(e.kind(), std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset)
+    })
+}
+
+/// Count and log a connection that ended in an error
+///
+/// A client closing the connection mid-stream is expected (a cancelled
+/// download, a Unity editor leaving play mode): it only bumps
+/// `client_disconnects_total` and is logged when `VERBOSE` is set. Any other
+/// I/O error is a real connection error.
+fn report_connection_error(config: &ServerConfig, peer: &str, e: &(dyn std::error::Error + 'static)) {
+    if is_client_disconnect(e) {
+        METRICS.client_disconnects_total.fetch_add(1, Ordering::Relaxed);
+        if config.verbose {
+            println!("[NAVΛ Server] Client {} disconnected: {}", peer, e);
+        }
     } else {
-        // Send error response
-        let error = ErrorResponse {
-            error: "Invalid request".to_string(),
-        };
-        let error_json = serde_json::to_string(&error)?;
-        let response = format!("HTTP/1.1 400 Bad Request\r\nContent-Length: {}\r\n\r\n{}", error_json.len(), error_json);
+        METRICS.connection_errors_total.fetch_add(1, Ordering::Relaxed);
+        eprintln!("[NAVΛ Server] Error handling client {}: {}", peer, e);
+    }
+}
+
+/// Dispatch a single parsed request, returning the response status
+async fn handle_request<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    config: &ServerConfig,
+    request_str: &str,
+    method: &str,
+    target: &RequestTarget,
+    body: &[u8],
+) -> Result<u16, Box<dyn std::error::Error>> {
+    // Liveness probe: answered before auth and the asset branches, with no file access
+    if method == "GET" && matches!(target.path.as_str(), "/health" | "/healthz") {
+        let body = serde_json::to_string(&HealthResponse { status: "ok".to_string(), uptime_secs: uptime().as_secs() })?;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nCache-Control: no-store\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
         stream.write_all(response.as_bytes()).await?;
+        return Ok(200);
     }
 
-    Ok(())
+    // Uploads and deletes (and optionally reads) need the bearer token when one is configured.
+    // `/verify` writes nothing, so it is treated as a read
+    let is_verify = method == "POST" && target.path == "/verify";
+    let needs_auth = (matches!(method, "POST" | "DELETE") && !is_verify)
+        || (config.require_auth_for_get && (is_verify || matches!(method, "GET" | "HEAD")));
+    if let Some(token) = config.auth_token.as_deref().filter(|_| needs_auth) {
+        if !is_authorized(request_str, token) {
+            let error_json = serde_json::to_string(&ErrorResponse { error: "Unauthorized".to_string() })?;
+            let response = format!(
+                "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                error_json.len(),
+                error_json
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(401);
+        }
+    }
+
+    let asset_name = target.path.strip_prefix("/Assets/");
+    let head_only = method == "HEAD" && asset_name.is_some();
+    let status = if method == "OPTIONS" && config.cors_origin.is_some() {
+        // CORS preflight: no body, no auth (browsers never send credentials here)
+        let response = format!(
+            "HTTP/1.1 204 No Content\r\n{}Access-Control-Allow-Methods: {}\r\nAccess-Control-Allow-Headers: {}\r\nAccess-Control-Max-Age: 600\r\n\r\n",
+            cors_headers(config),
+            CORS_ALLOW_METHODS,
+            CORS_ALLOW_HEADERS
+        );
+        stream.write_all(response.as_bytes()).await?;
+        204
+    } else if method == "GET" && target.path == "/metrics" {
+        let body = METRICS.render();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        200
+    } else if is_verify {
+        handle_verify(stream, config, request_str, body).await?
+    } else if method == "GET" && target.path == "/ws/verify" {
+        handle_ws_verify(stream, config, request_str, body).await?
+    } else if method == "GET" && target.path == "/verify/schema" {
+        let body = serde_json::to_string(&verify_schema())?;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\n{}Content-Type: application/schema+json\r\nContent-Length: {}\r\n\r\n{}",
+            cors_headers(config),
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        200
+    } else if let Some(file_name) = target.path.strip_prefix("/watch/Assets/").filter(|_| method == "GET") {
+        handle_watch(stream, config, file_name).await?
+    } else if let Some(file_name) = asset_name.filter(|_| matches!(method, "GET" | "HEAD")) {
+        if file_name.is_empty() {
+            // Directory listing for `GET /Assets/`
+            handle_listing(stream, config, &target.query, head_only).await?
+        } else {
+            // Handle streaming request (HEAD stops after the response header)
+            let framed = target.query.get("framed").is_some_and(|v| v == "1");
+            handle_streaming_request(stream, config, file_name, request_str, head_only, framed).await?
+        }
+    } else if let Some(file_name) = asset_name.filter(|_| method == "POST") {
+        // Handle file upload (small files)
+        let status = handle_file_upload(stream, config, request_str, file_name, body).await?;
+        if status < 400 {
+            METRICS.uploads_total.fetch_add(1, Ordering::Relaxed);
+        }
+        status
+    } else if let Some(file_name) = asset_name.filter(|_| method == "DELETE") {
+        handle_file_delete(stream, config, file_name).await?
+    } else {
+        write_error_response(stream, "400 Bad Request", "Invalid request").await?;
+        400
+    };
+
+    Ok(status)
+}
+
+/// Request target split at the `?`, both halves percent-decoded
+#[derive(Debug, PartialEq)]
+struct RequestTarget {
+    path: String,
+    query: BTreeMap<String, String>, // First occurrence of each key; `+` decodes to a space
+}
+
+/// Split an origin-form target into its decoded path and query parameters
+///
+/// Handlers look up only the parameters they understand, so unknown ones
+/// (e.g. `?lod=2` on a plain file GET) are ignored. Returns None (-> 400)
+/// for malformed percent escapes or non-UTF-8 results.
+fn parse_request_target(target: &str) -> Option<RequestTarget> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut params = BTreeMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(&key.replace('+', " "))?;
+        let value = percent_decode(&value.replace('+', " "))?;
+        params.entry(key).or_insert(value);
+    }
+    Some(RequestTarget { path: percent_decode(path)?, query: params })
+}
+
+/// `METHOD target HTTP/1.x` with a token method and an origin-form target
+fn is_valid_request_line(line: &str) -> bool {
+    let parts: Vec<&str> = line.split(' ').collect();
+    let [method, target, version] = parts[..] else {
+        return false;
+    };
+    !method.is_empty()
+        && method.bytes().all(|b| b.is_ascii_uppercase())
+        && target.starts_with('/')
+        && matches!(version, "HTTP/1.0" | "HTTP/1.1")
+}
+
+/// HTTP/1.1 connections persist unless the client sends `Connection: close`;
+/// HTTP/1.0 ones only with an explicit `Connection: keep-alive`
+fn is_keep_alive(request: &str) -> bool {
+    let connection = header_value(request, "Connection").unwrap_or("");
+    let has_token = |token: &str| connection.split(',').any(|t| t.trim().eq_ignore_ascii_case(token));
+    if request.lines().next().is_some_and(|line| line.ends_with("HTTP/1.0")) {
+        has_token("keep-alive")
+    } else {
+        !has_token("close")
+    }
+}
+
+/// Whether the request head announces a body (`Content-Length` or
+/// `Transfer-Encoding`, whatever the value)
+fn declares_body(request: &str) -> bool {
+    header_value(request, "Content-Length").is_some() || header_value(request, "Transfer-Encoding").is_some()
+}
+
+/// Check the request's `Authorization: Bearer <token>` against the configured token
+fn is_authorized(request: &str, token: &str) -> bool {
+    let presented = header_value(request, "Authorization")
+        .and_then(|value| {
+            let (scheme, credentials) = value.split_once(' ')?;
+            scheme.eq_ignore_ascii_case("Bearer").then(|| credentials.trim())
+        })
+        .unwrap_or("");
+    constant_time_eq(presented.as_bytes(), token.as_bytes())
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+///
+/// Only the length can leak; the token's content cannot be probed byte by byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Process-wide counters exposed at `GET /metrics` in Prometheus text format
+struct Metrics {
+    requests_total: AtomicU64,
+    bytes_sent_total: AtomicU64,
+    active_connections: AtomicU64,
+    uploads_total: AtomicU64,
+    errors_total: AtomicU64,            // Responses with status >= 400
+    connection_errors_total: AtomicU64, // Connections ending in an I/O error
+    client_disconnects_total: AtomicU64, // Connections the client closed mid-response
+}
+
+static METRICS: Metrics = Metrics {
+    requests_total: AtomicU64::new(0),
+    bytes_sent_total: AtomicU64::new(0),
+    active_connections: AtomicU64::new(0),
+    uploads_total: AtomicU64::new(0),
+    errors_total: AtomicU64::new(0),
+    connection_errors_total: AtomicU64::new(0),
+    client_disconnects_total: AtomicU64::new(0),
+};
+
+impl Metrics {
+    fn record_response(&self, status: u16, bytes_sent: u64) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent_total.fetch_add(bytes_sent, Ordering::Relaxed);
+        if status >= 400 {
+            self.errors_total.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn render(&self) -> String {
+        let metrics = [
+            ("nav_server_requests_total", "counter", "HTTP requests handled", &self.requests_total),
+            ("nav_server_bytes_sent_total", "counter", "Response bytes written, headers included", &self.bytes_sent_total),
+            ("nav_server_active_connections", "gauge", "Connections currently open", &self.active_connections),
+            ("nav_server_uploads_total", "counter", "Accepted uploads", &self.uploads_total),
+            ("nav_server_errors_total", "counter", "Responses with status >= 400", &self.errors_total),
+            ("nav_server_connection_errors_total", "counter", "Connections that ended in an I/O error", &self.connection_errors_total),
+            ("nav_server_client_disconnects_total", "counter", "Connections the client closed mid-response", &self.client_disconnects_total),
+        ];
+        metrics
+            .iter()
+            .map(|(name, kind, help, value)| {
+                format!(
+                    "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {}\n",
+                    value.load(Ordering::Relaxed)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Holds the active-connection gauge up for the lifetime of a connection
+struct ActiveConnection;
+
+impl ActiveConnection {
+    fn new() -> Self {
+        METRICS.active_connections.fetch_add(1, Ordering::Relaxed);
+        ActiveConnection
+    }
+}
+
+impl Drop for ActiveConnection {
+    fn drop(&mut self) {
+        METRICS.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// One access log line per request
+#[derive(Serialize, Debug)]
+struct AccessLog<'a> {
+    method: &'a str,
+    path: &'a str,
+    client: &'a str,
+    status: u16,
+    bytes_sent: u64,
+    duration_ms: u64,
 }
 
-async fn handle_streaming_request(
-    mut stream: tokio::net::TcpStream,
+/// Write an access log entry to stdout as a single JSON line
+fn log_access(method: &str, path: &str, client: &str, status: u16, bytes_sent: u64, started: Instant) {
+    let entry = AccessLog {
+        method,
+        path,
+        client,
+        status,
+        bytes_sent,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    if let Ok(line) = serde_json::to_string(&entry) {
+        println!("{}", line);
+    }
+}
+
+/// Stream wrapper counting the bytes written through it (headers and body)
+struct CountingStream<S> {
+    inner: S,
+    written: u64,
+}
+
+impl<S> CountingStream<S> {
+    fn new(inner: S) -> Self {
+        CountingStream { inner, written: 0 }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountingStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountingStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = result {
+            this.written += n as u64;
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+async fn handle_streaming_request<S: AsyncWrite + Unpin>(
+    mut stream: S,
+    config: &ServerConfig,
     file_name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let file_path = format!("./Assets/{}", file_name);
+    request_str: &str,
+    head_only: bool,
+    framed: bool, // Prepend a StreamingHeader; always the whole file, identity encoded
+) -> Result<u16, Box<dyn std::error::Error>> {
+    let cors = cors_headers(config);
+
+    // Resolve against the asset roots, refusing anything that escapes them
+    let file_path = match locate_asset(&config.asset_roots, file_name) {
+        Some(path) => path,
+        None => {
+            if head_only {
+                stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n").await?;
+            } else {
+                write_error_response(&mut stream, "403 Forbidden", "Forbidden path").await?;
+            }
+            return Ok(403);
+        }
+    };
     
     // Check if file exists
-    if !Path::new(&file_path).exists() {
-        eprintln!("[NAVΛ Server] File not found: {}", file_path);
+    if !file_path.exists() {
+        if head_only {
+            let response = format!("HTTP/1.1 404 Not Found\r\n{}Content-Length: 0\r\n\r\n", cors);
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(404);
+        }
         let error = ErrorResponse {
             error: format!("File not found: {}", file_name),
         };
         let error_json = serde_json::to_string(&error)?;
-        let response = format!("HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\n\r\n{}", error_json.len(), error_json);
+        let response = format!(
+            "HTTP/1.1 404 Not Found\r\n{}Content-Length: {}\r\n\r\n{}",
+            cors,
+            error_json.len(),
+            error_json
+        );
         stream.write_all(response.as_bytes()).await?;
-        return Ok(());
+        return Ok(404);
     }
 
     // Get file size
     let metadata = std::fs::metadata(&file_path)?;
     let file_size = metadata.len();
+    let content_type = get_content_type(file_name, &config.mime_types);
+
+    // Pipes and other special files have no meaningful length (and can only
+    // be read once, so no checksum, validators or ranges): stream them chunked
+    if !metadata.is_file() && !metadata.is_dir() {
+        let response_header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\n{}\r\n",
+            content_type, cors
+        );
+        stream.write_all(response_header.as_bytes()).await?;
+        if !head_only {
+            stream_chunked(&mut stream, File::open(&file_path)?, config).await?;
+        }
+        return Ok(200);
+    }
+
+    // Cache validators: a matching If-None-Match (or, failing that, an
+    // If-Modified-Since no older than the file) short-circuits with 304
+    let etag = file_etag(&metadata);
+    let modified = metadata.modified().ok();
+    let last_modified = modified
+        .map(|m| format!("Last-Modified: {}\r\n", http_date(m)))
+        .unwrap_or_default();
+    if is_not_modified(request_str, &etag, modified) {
+        let response = format!("HTTP/1.1 304 Not Modified\r\nETag: {}\r\n{}{}\r\n", etag, last_modified, cors);
+        stream.write_all(response.as_bytes()).await?;
+        return Ok(304);
+    }
 
-    println!("[NAVΛ Server] Streaming file: {} ({} MB)", file_name, file_size / (1024 * 1024));
+    // Whole-file checksum (identity encoding), so clients can verify any response
+    let sha256 = {
+        let path = file_path.clone();
+        tokio::task::spawn_blocking(move || cached_sha256(&path)).await??
+    };
+    let entity_headers = format!("{}X-Content-SHA256: {}\r\n{}", last_modified, sha256, cors);
+
+    // Resolve the requested byte span (whole file unless a valid Range was sent;
+    // offsets into a framed body would be ambiguous, so framing ignores Range)
+    let range = header_value(request_str, "Range").filter(|_| !framed);
+    let (start, end, is_partial) = match range.map(|r| parse_byte_range(r, file_size)) {
+        Some(ByteRange::Partial(start, end)) => (start, end, true),
+        Some(ByteRange::Unsatisfiable) => {
+            let response = format!(
+                "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\n\r\n",
+                file_size
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(416);
+        }
+        Some(ByteRange::Full) | None => (0, file_size.saturating_sub(1), false),
+    };
+    let content_length = if file_size == 0 { 0 } else { end - start + 1 };
 
     // Open file for reading
     let file = File::open(&file_path)?;
     let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(start))?;
+
+    // Text assets are gzipped for clients that accept it (never for partial content,
+    // whose byte offsets refer to the identity encoding)
+    if !is_partial
+        && !head_only
+        && !framed
+        && is_compressible(file_name)
+        && accepts_gzip(header_value(request_str, "Accept-Encoding"))
+    {
+        let response_header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\nVary: Accept-Encoding\r\nETag: {}\r\n{}\r\n",
+            content_type, gzip_etag(&etag), entity_headers
+        );
+        stream.write_all(response_header.as_bytes()).await?;
+        stream_gzip_chunked(&mut stream, reader, config).await?;
+        return Ok(200);
+    }
 
     // Send HTTP response header
-    let content_type = get_content_type(file_name);
-    let response_header = format!(
-        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\n\r\n",
-        content_type, file_size
-    );
+    let frame = framed.then(|| {
+        StreamingHeader {
+            file_name: file_name.to_string(),
+            file_size,
+            chunk_size: u32::try_from(config.chunk_size).unwrap_or(u32::MAX),
+            is_streaming: u8::from(file_size > config.chunk_size as u64),
+        }
+        .to_bytes()
+    });
+    let response_header = if let Some(frame) = &frame {
+        // X-Content-SHA256 still covers the file alone, not the preamble
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nETag: {}\r\n{}\r\n",
+            FRAMED_CONTENT_TYPE, frame.len() as u64 + content_length, framed_etag(&etag), entity_headers
+        )
+    } else if is_partial {
+        format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Type: {}\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nAccept-Ranges: bytes\r\nETag: {}\r\n{}\r\n",
+            content_type, content_length, start, end, file_size, etag, entity_headers
+        )
+    } else {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nETag: {}\r\n{}\r\n",
+            content_type, content_length, etag, entity_headers
+        )
+    };
+    let status = if is_partial { 206 } else { 200 };
     stream.write_all(response_header.as_bytes()).await?;
+    if head_only {
+        return Ok(status);
+    }
+    if let Some(frame) = &frame {
+        with_write_timeout(config, stream.write_all(frame)).await?;
+    }
 
-    // Stream file in chunks
+    // Stream the span in chunks
     let mut total_sent = 0u64;
-    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut chunk = vec![0u8; config.chunk_size];
+    let mut throttle = Throttle::new(config.max_kbps_per_client);
+    let started = Instant::now();
+    let mut last_report = started;
 
-    loop {
-        // Read chunk from file
-        let bytes_read = reader.read(&mut chunk)?;
+    while total_sent < content_length {
+        // Read chunk from file, never past the end of the span
+        let want = (content_length - total_sent).min(config.chunk_size as u64) as usize;
+        let bytes_read = reader.read(&mut chunk[..want])?;
         if bytes_read == 0 {
             break; // EOF
         }
 
         // Send chunk
-        stream.write_all(&chunk[..bytes_read]).await?;
+        with_write_timeout(config, stream.write_all(&chunk[..bytes_read])).await?;
         total_sent += bytes_read as u64;
+        if let Some(throttle) = throttle.as_mut() {
+            throttle.consume(bytes_read).await;
+        }
 
-        // Log progress (every 10MB)
-        if total_sent % (10 * 1024 * 1024) == 0 || total_sent == file_size {
-            let progress = (total_sent as f64 / file_size as f64) * 100.0;
-            println!(
-                "[NAVΛ Server] Streaming... {:.1}% ({:.2} MB / {:.2} MB)",
-                progress,
-                total_sent as f64 / (1024.0 * 1024.0),
-                file_size as f64 / (1024.0 * 1024.0)
-            );
+        // Periodic progress; the final report is sent after the loop
+        if let Some(callback) = config.on_progress {
+            if total_sent < content_length && last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = Instant::now();
+                callback(&TransferProgress { file_name, sent: total_sent, total: content_length, elapsed: started.elapsed() });
+            }
         }
     }
 
-    println!("[NAVΛ Server] Streaming complete: {} ({:.2} MB)", file_name, file_size as f64 / (1024.0 * 1024.0));
-    Ok(())
+    // Always report completion, including for empty files the loop never entered
+    if let Some(callback) = config.on_progress.filter(|_| total_sent == content_length) {
+        callback(&TransferProgress { file_name, sent: total_sent, total: content_length, elapsed: started.elapsed() });
+    }
+
+    Ok(status)
 }
 
-async fn handle_file_upload(
-    mut stream: tokio::net::TcpStream,
-    request_str: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Handle standard file upload (small files < 100MB)
-    // In production, implement proper multipart/form-data parsing
-    
-    println!("[NAVΛ Server] File upload request received");
-    
-    // For now, just acknowledge
-    let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
-    stream.write_all(response.as_bytes()).await?;
-    
-    Ok(())
+/// One file in the `GET /Assets/` listing
+#[derive(Serialize, Deserialize, Debug)]
+struct AssetEntry {
+    name: String,
+    size: u64,
+    modified: u64, // Unix seconds
 }
 
-fn get_content_type(file_name: &str) -> &str {
-    let ext = Path::new(file_name)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
+/// List the files directly under the asset roots as a JSON array, sorted by name
+///
+/// `?prefix=` filters on the start of the file name. Entries that resolve
+/// outside their root (e.g. escaping symlinks) are omitted. A name present in
+/// several roots is listed once, with the metadata of the copy a GET serves.
+async fn handle_listing<S: AsyncWrite + Unpin>(
+    mut stream: S,
+    config: &ServerConfig,
+    query: &BTreeMap<String, String>,
+    head_only: bool,
+) -> Result<u16, Box<dyn std::error::Error>> {
+    let prefix = query.get("prefix").map_or("", String::as_str);
+
+    let mut entries = Vec::new();
+    let mut names = std::collections::BTreeSet::new();
+    for entry in config.asset_roots.iter().filter_map(|root| std::fs::read_dir(root).ok()).flatten() {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue; // Not addressable by URL
+        };
+        if !name.starts_with(prefix) || !names.insert(name.clone()) {
+            continue; // Filtered out, or shadowed by an earlier root
+        }
+        let Some(path) = locate_asset(&config.asset_roots, &name) else {
+            continue;
+        };
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        entries.push(AssetEntry { name, size: metadata.len(), modified });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let body = serde_json::to_string(&entries)?;
+    let response_header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}\r\n",
+        body.len(),
+        cors_headers(config)
+    );
+    stream.write_all(response_header.as_bytes()).await?;
+    if !head_only {
+        stream.write_all(body.as_bytes()).await?;
+    }
+    Ok(200)
+}
+
+/// One line of the `GET /watch/Assets/<name>` event stream
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct WatchEvent {
+    event: String,         // "watching" (initial state), "changed" or "removed"
+    name: String,
+    size: Option<u64>,     // None once removed
+    modified: Option<u64>, // Unix seconds
+    etag: Option<String>,  // The ETag a GET would send now; differs after every write
+}
+
+impl WatchEvent {
+    fn new(event: &str, name: &str, metadata: Option<&std::fs::Metadata>) -> Self {
+        WatchEvent {
+            event: event.to_string(),
+            name: name.to_string(),
+            size: metadata.map(|m| m.len()),
+            modified: metadata
+                .and_then(|m| m.modified().ok())
+                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            etag: metadata.map(file_etag),
+        }
+    }
+}
+
+/// `GET /watch/Assets/<name>`: hold the connection open and push an event
+/// whenever the asset changes on disk, for live-reloading in the editor
+///
+/// Framing: a chunked `application/x-ndjson` body, one `WatchEvent` JSON
+/// object per line. The first line is a `watching` event with the current
+/// state; after that a `changed` line follows each write that leaves the
+/// file with a new ETag, and a `removed` line when it disappears (a later
+/// `changed` means it was recreated). Blank lines are heartbeats, sent every
+/// `WATCH_HEARTBEAT_INTERVAL` while idle; skip them. Bursts of writes may
+/// collapse into one event, so reload from the asset URL rather than
+/// counting events.
+///
+/// The parent directory is watched rather than the file, so editors that
+/// save by writing a temp file and renaming it over the asset still report
+/// changes. The stream never ends on its own: the watch is dropped (and its
+/// OS watcher released) when the client closes the connection or stops
+/// reading. 404 when the asset doesn't exist, 403 outside the asset roots.
+async fn handle_watch<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    config: &ServerConfig,
+    file_name: &str,
+) -> Result<u16, Box<dyn std::error::Error>> {
+    use notify::Watcher;
+
+    let Some(file_path) = locate_asset(&config.asset_roots, file_name) else {
+        write_error_response(stream, "403 Forbidden", "Forbidden path").await?;
+        return Ok(403);
+    };
+    let (Ok(metadata), Some(dir)) = (std::fs::metadata(&file_path), file_path.parent()) else {
+        write_error_response(stream, "404 Not Found", &format!("File not found: {}", file_name)).await?;
+        return Ok(404);
+    };
+
+    // Subscribe before taking the initial snapshot so no write falls in between
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event); // Receiver gone: the watch is being torn down
+    })?;
+    watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+
+    let response = format!(
+        "HTTP/1.1 200 OK\r\n{}Content-Type: application/x-ndjson\r\nCache-Control: no-store\r\nTransfer-Encoding: chunked\r\n\r\n",
+        cors_headers(config)
+    );
+    stream.write_all(response.as_bytes()).await?;
+    let mut last = WatchEvent::new("watching", file_name, Some(&metadata));
+    let mut line = serde_json::to_string(&last)? + "\n";
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + WATCH_HEARTBEAT_INTERVAL, WATCH_HEARTBEAT_INTERVAL);
+    let mut discard = [0u8; 512];
+
+    loop {
+        with_write_timeout(config, async {
+            write_chunk(stream, line.as_bytes()).await?;
+            stream.flush().await
+        })
+        .await?;
+        heartbeat.reset();
+        line = loop {
+            tokio::select! {
+                // The client never sends anything here; EOF (or a reset) means it went away
+                read = stream.read(&mut discard) => {
+                    if matches!(read, Ok(0) | Err(_)) {
+                        return Ok(200);
+                    }
+                }
+                _ = heartbeat.tick() => break "\n".to_string(),
+                event = rx.recv() => {
+                    let event = event.ok_or("file watcher stopped")??;
+                    if !event.paths.iter().any(|path| path == &file_path) {
+                        continue;
+                    }
+                    let current = match std::fs::metadata(&file_path) {
+                        Ok(metadata) => WatchEvent::new("changed", file_name, Some(&metadata)),
+                        Err(_) => WatchEvent::new("removed", file_name, None),
+                    };
+                    // Same state as already reported (e.g. a read, or one write seen twice)
+                    if current.etag != last.etag {
+                        last = current;
+                        break serde_json::to_string(&last)? + "\n";
+                    }
+                }
+            }
+        };
+    }
+}
+
+/// Stream a reader through a gzip encoder using chunked transfer encoding
+async fn stream_gzip_chunked<S: AsyncWrite + Unpin, R: Read>(
+    stream: &mut S,
+    mut reader: R,
+    config: &ServerConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut chunk = vec![0u8; config.chunk_size];
+    let mut throttle = Throttle::new(config.max_kbps_per_client);
+
+    loop {
+        let bytes_read = reader.read(&mut chunk)?;
+        if bytes_read == 0 {
+            break; // EOF
+        }
+        encoder.write_all(&chunk[..bytes_read])?;
+
+        // Forward whatever the encoder has produced so far
+        let compressed = std::mem::take(encoder.get_mut());
+        with_write_timeout(config, write_chunk(stream, &compressed)).await?;
+        if let Some(throttle) = throttle.as_mut() {
+            throttle.consume(compressed.len()).await;
+        }
+    }
+
+    let trailer = encoder.finish()?;
+    with_write_timeout(config, write_chunk(stream, &trailer)).await?;
+    with_write_timeout(config, write_last_chunk(stream)).await?;
+    Ok(())
+}
+
+/// Stream `reader` to EOF as a chunked body, for content whose length isn't known up front
+async fn stream_chunked<S: AsyncWrite + Unpin, R: Read>(
+    stream: &mut S,
+    mut reader: R,
+    config: &ServerConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut chunk = vec![0u8; config.chunk_size];
+    let mut throttle = Throttle::new(config.max_kbps_per_client);
+
+    loop {
+        let bytes_read = reader.read(&mut chunk)?;
+        if bytes_read == 0 {
+            break; // EOF
+        }
+        with_write_timeout(config, write_chunk(stream, &chunk[..bytes_read])).await?;
+        if let Some(throttle) = throttle.as_mut() {
+            throttle.consume(bytes_read).await;
+        }
+    }
+
+    with_write_timeout(config, write_last_chunk(stream)).await?;
+    Ok(())
+}
+
+/// Snapshot of one streaming response, passed to the progress callback
+struct TransferProgress<'a> {
+    file_name: &'a str,
+    sent: u64,
+    total: u64,
+    elapsed: Duration,
+}
+
+/// Receives periodic (`PROGRESS_INTERVAL`) and final progress for each stream
+type ProgressCallback = fn(&TransferProgress);
+
+impl TransferProgress<'_> {
+    fn is_complete(&self) -> bool {
+        self.sent == self.total
+    }
+
+    fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.sent as f64 / self.total as f64 * 100.0
+        }
+    }
+
+    /// Measured transfer rate in bytes per second
+    fn bytes_per_sec(&self) -> f64 {
+        self.sent as f64 / self.elapsed.as_secs_f64().max(1e-6)
+    }
+
+    /// Time left at the measured rate (None until something was sent)
+    fn eta(&self) -> Option<Duration> {
+        (self.sent > 0).then(|| Duration::from_secs_f64((self.total - self.sent) as f64 / self.bytes_per_sec()))
+    }
+
+    fn log_line(&self) -> String {
+        const MB: f64 = 1024.0 * 1024.0;
+        if self.is_complete() {
+            format!(
+                "[NAVΛ Server] Streamed {}: 100.0% ({:.2} MB in {:.2}s, {:.2} MB/s)",
+                self.file_name,
+                self.total as f64 / MB,
+                self.elapsed.as_secs_f64(),
+                self.bytes_per_sec() / MB
+            )
+        } else {
+            format!(
+                "[NAVΛ Server] Streaming {}... {:.1}% ({:.2} MB / {:.2} MB, {:.2} MB/s, ETA {})",
+                self.file_name,
+                self.percent(),
+                self.sent as f64 / MB,
+                self.total as f64 / MB,
+                self.bytes_per_sec() / MB,
+                self.eta().map_or("unknown".to_string(), |eta| format!("{:.1}s", eta.as_secs_f64()))
+            )
+        }
+    }
+}
+
+/// Default progress sink (`VERBOSE`): one log line per report
+fn log_progress(progress: &TransferProgress) {
+    println!("{}", progress.log_line());
+}
+
+/// Token bucket pacing a single connection to `MAX_KBPS_PER_CLIENT`
+///
+/// The bucket holds at most one second of credit; sending more than the
+/// available credit sleeps until the deficit has been refilled.
+struct Throttle {
+    bytes_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    /// None when the cap is disabled (0)
+    fn new(kbps: u64) -> Option<Self> {
+        if kbps == 0 {
+            return None;
+        }
+        let bytes_per_sec = kbps as f64 * 1024.0;
+        Some(Throttle {
+            bytes_per_sec,
+            tokens: bytes_per_sec,
+            last_refill: Instant::now(),
+        })
+    }
+
+    /// Charge `bytes` against the bucket, sleeping off any deficit
+    async fn consume(&mut self, bytes: usize) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.bytes_per_sec).min(self.bytes_per_sec);
+        self.tokens -= bytes as f64;
+
+        if self.tokens < 0.0 {
+            tokio::time::sleep(Duration::from_secs_f64(-self.tokens / self.bytes_per_sec)).await;
+        }
+    }
+}
+
+/// Bound a streaming write by `WRITE_TIMEOUT_SECS` so a client that stops
+/// reading can't pin the task forever
+async fn with_write_timeout<T>(
+    config: &ServerConfig,
+    write: impl std::future::Future<Output = std::io::Result<T>>,
+) -> std::io::Result<T> {
+    tokio::time::timeout(config.write_timeout, write).await.unwrap_or_else(|_| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("write timed out after {:?}", config.write_timeout),
+        ))
+    })
+}
+
+/// Write one chunked-transfer frame (empty data is skipped; it would end the body)
+async fn write_chunk<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    data: &[u8],
+) -> std::io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    stream.write_all(format!("{:x}\r\n", data.len()).as_bytes()).await?;
+    stream.write_all(data).await?;
+    stream.write_all(b"\r\n").await?;
+    Ok(())
+}
+
+/// Terminate a chunked body (zero-size chunk, no trailers)
+async fn write_last_chunk<S: AsyncWrite + Unpin>(stream: &mut S) -> std::io::Result<()> {
+    stream.write_all(b"0\r\n\r\n").await
+}
+
+async fn handle_file_upload<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    config: &ServerConfig,
+    request_str: &str,
+    file_name: &str,
+    body: &[u8],
+) -> Result<u16, Box<dyn std::error::Error>> {
+    // Handle standard file upload (small files < 100MB): the request body is
+    // the raw file content, written to the writable root under `file_name`
+    let Some(upload_root) = writable_root(config) else {
+        write_error_response(&mut stream, "403 Forbidden", "No writable asset root").await?;
+        return Ok(403);
+    };
+    let file_path = match resolve_asset_path(upload_root, file_name) {
+        Some(path) if !file_name.is_empty() && !path.is_dir() => path,
+        _ => {
+            write_error_response(&mut stream, "403 Forbidden", "Forbidden path").await?;
+            return Ok(403);
+        }
+    };
+
+    // Pre-compressed assets are stored decompressed so Unity can read them
+    let gzip = match header_value(request_str, "Content-Encoding").map(str::to_ascii_lowercase).as_deref() {
+        None | Some("identity") => false,
+        Some("gzip" | "x-gzip") => true,
+        Some(_) => {
+            write_error_response(&mut stream, "415 Unsupported Media Type", "Unsupported Content-Encoding").await?;
+            return Ok(415);
+        }
+    };
+    if header_value(request_str, "Transfer-Encoding").is_some() {
+        write_error_response(&mut stream, "411 Length Required", "Uploads need a Content-Length").await?;
+        return Ok(411);
+    }
+    // No Content-Length means an empty body
+    let content_length = match header_value(request_str, "Content-Length").map(str::parse::<u64>) {
+        None => 0,
+        Some(Ok(length)) => length,
+        Some(Err(_)) => {
+            write_error_response(&mut stream, "400 Bad Request", "Invalid Content-Length").await?;
+            return Ok(400);
+        }
+    };
+    if content_length > MAX_UPLOAD_SIZE {
+        write_error_response(&mut stream, "413 Payload Too Large", "Upload too large").await?;
+        return Ok(413);
+    }
+
+    // Write to a sibling temp file, then rename, so readers never see a partial asset
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent)?;
+        // A symlinked directory on the way must not lead outside the root
+        if !parent.canonicalize()?.starts_with(upload_root.canonicalize()?) {
+            write_error_response(&mut stream, "403 Forbidden", "Forbidden path").await?;
+            return Ok(403);
+        }
+    }
+    let mut temp_name = file_path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".upload");
+    let temp_path = file_path.with_file_name(temp_name);
+
+    // Stream the body (part of it may have arrived with the head) straight to
+    // disk, so memory use doesn't depend on the client's Content-Length
+    let already_read = &body[..body.len().min(content_length as usize)];
+    let reader = AsyncReadExt::chain(already_read, (&mut stream).take(content_length - already_read.len() as u64));
+    let received = tokio::time::timeout(config.read_timeout, receive_upload(reader, content_length, &temp_path, gzip, MAX_UPLOAD_SIZE))
+        .await
+        .unwrap_or_else(|_| Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "upload body read timed out")));
+    let rejected = match received {
+        Ok(()) => None,
+        Err(e) if e.kind() == std::io::ErrorKind::FileTooLarge => Some((413, "413 Payload Too Large", "Decompressed upload too large")),
+        Err(e) if gzip && matches!(e.kind(), std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData) => {
+            Some((400, "400 Bad Request", "Invalid gzip body"))
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+    };
+    if let Some((code, status, message)) = rejected {
+        let _ = std::fs::remove_file(&temp_path);
+        write_error_response(&mut stream, status, message).await?;
+        return Ok(code);
+    }
+    if let Err(e) = std::fs::rename(&temp_path, &file_path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    HASH_CACHE.lock().unwrap_or_else(|e| e.into_inner()).remove(&file_path);
+
+    let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+    stream.write_all(response.as_bytes()).await?;
+
+    Ok(200)
+}
+
+/// `POST /verify`: score one state with the core library (schema on
+/// `VerifyRequest`, machine-readable at `GET /verify/schema`)
+///
+/// Malformed JSON and inputs the core rejects (NaN, out-of-range certainty
+/// or fatigue) both answer 400 with the reason in the error body.
+async fn handle_verify<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    config: &ServerConfig,
+    request_str: &str,
+    body: &[u8],
+) -> Result<u16, Box<dyn std::error::Error>> {
+    let content_length = match header_value(request_str, "Content-Length").map(str::parse::<usize>) {
+        Some(Ok(length)) if length <= MAX_VERIFY_BODY => length,
+        Some(Ok(_)) => {
+            write_error_response(stream, "413 Payload Too Large", "Verify body too large").await?;
+            return Ok(413);
+        }
+        None | Some(Err(_)) => {
+            write_error_response(stream, "411 Length Required", "Verify needs a Content-Length").await?;
+            return Ok(411);
+        }
+    };
+    let mut data = body[..body.len().min(content_length)].to_vec();
+    let already_read = data.len();
+    data.resize(content_length, 0);
+    tokio::time::timeout(config.read_timeout, stream.read_exact(&mut data[already_read..]))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "verify body read timed out"))??;
+
+    let request: VerifyRequest = match serde_json::from_slice(&data) {
+        Ok(request) => request,
+        Err(e) => {
+            write_error_response(stream, "400 Bad Request", &format!("Invalid verify request: {}", e)).await?;
+            return Ok(400);
+        }
+    };
+    let response = match verify_request(request) {
+        Ok(response) => response,
+        Err(status) => {
+            write_error_response(stream, "400 Bad Request", &format!("Verification rejected: {}", status)).await?;
+            return Ok(400);
+        }
+    };
+
+    let body = serde_json::to_string(&response)?;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\n{}Content-Type: application/json\r\nCache-Control: no-store\r\nContent-Length: {}\r\n\r\n{}",
+        cors_headers(config),
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(200)
+}
+
+/// Run one `/verify` request through the core
+fn verify_request(request: VerifyRequest) -> Result<VerifyResponse, nav_lambda_core::NavStatus> {
+    let state = nav_lambda_core::State7D {
+        position: request.state.position,
+        velocity: request.state.velocity,
+        heading: request.state.heading,
+        timestamp: request.state.timestamp,
+        certainty: request.state.certainty,
+        fatigue: request.state.fatigue,
+    };
+    let report = nav_lambda_core::safe::verify_in(verify_context(), &state, &request.params.into(), &request.obstacles)?;
+    Ok(VerifyResponse {
+        schema_version: VERIFY_SCHEMA_VERSION,
+        p_score: report.p_score,
+        is_safe: report.is_safe,
+        margin: report.margin,
+        sigma: report.sigma,
+        breach_reason: report.breach_reason,
+        evidence_hash: report.evidence_hash,
+        time_to_collision: report.time_to_collision,
+        closest_obstacle_index: report.closest_obstacle_index,
+        closest_obstacle_pos: report.closest_obstacle_pos,
+        breach_flags: report.breach_flags,
+        severity: report.severity,
+        terms: report.terms,
+    })
+}
+
+/// `GET /ws/verify`: upgrade to a WebSocket streaming verifications
+///
+/// For high-frequency clients that would otherwise poll `POST /verify`. Each
+/// text message is a `/verify` request body and is answered in order by a
+/// text message with its `VerifyResponse`, or an `ErrorResponse` when the
+/// JSON is invalid or the core rejects it (the socket stays open). Binary
+/// messages close the socket with 1003. Client pings get pongs; the server
+/// pings every `WS_PING_INTERVAL` and drops a socket that stays silent for a
+/// whole interval after a ping. Returns 101 once the socket has closed.
+async fn handle_ws_verify<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    config: &ServerConfig,
+    request_str: &str,
+    pending: &[u8],
+) -> Result<u16, Box<dyn std::error::Error>> {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Role, WebSocketConfig};
+    use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+    let has_token = |name: &str, token: &str| {
+        header_value(request_str, name).is_some_and(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)))
+    };
+    let key = header_value(request_str, "Sec-WebSocket-Key").filter(|k| !k.is_empty());
+    let Some(key) = key.filter(|_| has_token("Upgrade", "websocket") && has_token("Connection", "upgrade")) else {
+        write_error_response(stream, "400 Bad Request", "Expected a WebSocket upgrade").await?;
+        return Ok(400);
+    };
+    if header_value(request_str, "Sec-WebSocket-Version") != Some("13") {
+        let error_json = serde_json::to_string(&ErrorResponse { error: "Unsupported WebSocket version".to_string() })?;
+        let response = format!(
+            "HTTP/1.1 426 Upgrade Required\r\nSec-WebSocket-Version: 13\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            error_json.len(),
+            error_json
+        );
+        stream.write_all(response.as_bytes()).await?;
+        return Ok(426);
+    }
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        tokio_tungstenite::tungstenite::handshake::derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    // Frames the client sent right behind its handshake are already in `pending`
+    let ws_config = WebSocketConfig::default().max_message_size(Some(MAX_VERIFY_BODY));
+    let mut ws = tokio_tungstenite::WebSocketStream::from_partially_read(stream, pending.to_vec(), Role::Server, Some(ws_config)).await;
+    let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + WS_PING_INTERVAL, WS_PING_INTERVAL);
+    let mut awaiting_pong = false;
+
+    loop {
+        let message = tokio::select! {
+            message = ws.next() => message,
+            _ = keepalive.tick() => {
+                if awaiting_pong {
+                    if config.verbose {
+                        println!("[NAVΛ Server] WebSocket peer silent for {:?}, closing", WS_PING_INTERVAL);
+                    }
+                    return Ok(101);
+                }
+                awaiting_pong = true;
+                with_write_timeout(config, async { ws.send(Message::Ping(Default::default())).await.map_err(ws_io_error) }).await?;
+                continue;
+            }
+        };
+        awaiting_pong = false; // Any frame proves the peer is alive
+        let reply = match message {
+            None | Some(Err(WsError::ConnectionClosed | WsError::AlreadyClosed)) => return Ok(101),
+            Some(Err(WsError::Io(e))) => return Err(e.into()),
+            Some(Err(e)) => {
+                // Protocol violation or oversized message: tungstenite has already
+                // queued the close frame where one applies
+                if config.verbose {
+                    println!("[NAVΛ Server] WebSocket closed on error: {}", e);
+                }
+                return Ok(101);
+            }
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<VerifyRequest>(&text) {
+                Ok(request) => match verify_request(request) {
+                    Ok(response) => serde_json::to_string(&response)?,
+                    Err(status) => serde_json::to_string(&ErrorResponse { error: format!("Verification rejected: {}", status) })?,
+                },
+                Err(e) => serde_json::to_string(&ErrorResponse { error: format!("Invalid verify request: {}", e) })?,
+            },
+            Some(Ok(Message::Binary(_))) => {
+                let frame = CloseFrame { code: CloseCode::Unsupported, reason: "Send verify requests as text".into() };
+                with_write_timeout(config, async { ws.close(Some(frame)).await.map_err(ws_io_error) }).await?;
+                continue; // Drain until the client's close reply
+            }
+            // Pongs are queued by tungstenite itself; a close is answered on the next read
+            Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Close(_) | Message::Frame(_))) => continue,
+        };
+        with_write_timeout(config, async { ws.send(Message::text(reply)).await.map_err(ws_io_error) }).await?;
+    }
+}
+
+/// Convert a WebSocket send error to `io::Error`, a closed socket counting as a disconnect
+fn ws_io_error(e: tokio_tungstenite::tungstenite::Error) -> std::io::Error {
+    use tokio_tungstenite::tungstenite::Error as WsError;
+    match e {
+        WsError::Io(e) => e,
+        WsError::ConnectionClosed | WsError::AlreadyClosed => std::io::ErrorKind::BrokenPipe.into(),
+        e => std::io::Error::other(e),
+    }
+}
+
+/// Size of the reads that stream an upload body to disk
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copy a `length`-byte upload body to `path` in `UPLOAD_CHUNK_SIZE`
+/// pieces, gunzipping on the way when `gzip`
+///
+/// At most `limit` bytes are stored: past that the copy stops with a
+/// `FileTooLarge` error (zip-bomb guard: a small body can't inflate into a
+/// huge file). Invalid or truncated gzip fails with `InvalidInput` or
+/// `InvalidData`, a body that ends early with `UnexpectedEof`. On error
+/// `path` may hold a partial file.
+async fn receive_upload<R: AsyncRead + Unpin>(
+    mut body: R,
+    length: u64,
+    path: &Path,
+    gzip: bool,
+    limit: u64,
+) -> std::io::Result<()> {
+    async fn copy_chunks<R: AsyncRead + Unpin>(body: &mut R, length: u64, sink: &mut impl Write) -> std::io::Result<()> {
+        let mut buf = vec![0u8; UPLOAD_CHUNK_SIZE];
+        let mut copied = 0;
+        while copied < length {
+            let n = body.read(&mut buf[..UPLOAD_CHUNK_SIZE.min((length - copied) as usize)]).await?;
+            if n == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            sink.write_all(&buf[..n])?;
+            copied += n as u64;
+        }
+        Ok(())
+    }
+
+    let mut file = CappedWriter { inner: std::io::BufWriter::new(File::create(path)?), remaining: limit };
+    if gzip {
+        let mut decoder = flate2::write::GzDecoder::new(file);
+        copy_chunks(&mut body, length, &mut decoder).await?;
+        file = decoder.finish()?; // Fails on a truncated stream
+    } else {
+        copy_chunks(&mut body, length, &mut file).await?;
+    }
+    file.flush()
+}
+
+/// `Write` adapter that refuses to pass more than `remaining` bytes on
+struct CappedWriter<W> {
+    inner: W,
+    remaining: u64,
+}
+
+impl<W: Write> Write for CappedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() as u64 > self.remaining {
+            return Err(std::io::Error::new(std::io::ErrorKind::FileTooLarge, "upload exceeds the size cap"));
+        }
+        let n = self.inner.write(buf)?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Remove a single asset file from the writable root: 204 when deleted, 404 when missing
+///
+/// Directories (including the root itself) are refused with 403. Files in
+/// read-only roots are never touched, so deleting a user override uncovers
+/// the shipped asset of the same name.
+async fn handle_file_delete<S: AsyncWrite + Unpin>(
+    mut stream: S,
+    config: &ServerConfig,
+    file_name: &str,
+) -> Result<u16, Box<dyn std::error::Error>> {
+    let Some(root) = writable_root(config) else {
+        write_error_response(&mut stream, "403 Forbidden", "No writable asset root").await?;
+        return Ok(403);
+    };
+    let file_path = match resolve_asset_path(root, file_name) {
+        Some(path) if !path.is_dir() => path,
+        _ => {
+            write_error_response(&mut stream, "403 Forbidden", "Forbidden path").await?;
+            return Ok(403);
+        }
+    };
+
+    match std::fs::remove_file(&file_path) {
+        Ok(()) => {
+            HASH_CACHE.lock().unwrap_or_else(|e| e.into_inner()).remove(&file_path);
+            stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await?;
+            Ok(204)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            write_error_response(&mut stream, "404 Not Found", &format!("File not found: {}", file_name)).await?;
+            Ok(404)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Result of reading the request line and headers
+enum RequestHead {
+    /// Head up to and including the terminating blank line, plus any body bytes read past it
+    Complete { head: Vec<u8>, body: Vec<u8> },
+    Closed,   // Peer closed before sending anything
+    TooLarge, // No terminator within MAX_HEADER_SIZE (431)
+}
+
+/// Read until the `\r\n\r\n` header terminator, growing the buffer up to MAX_HEADER_SIZE
+///
+/// `pending` holds bytes already read from the stream (pipelined requests).
+async fn read_request_head<S: AsyncRead + Unpin>(stream: &mut S, pending: Vec<u8>) -> std::io::Result<RequestHead> {
+    let mut buf = pending;
+    let mut chunk = [0u8; 1024];
+    let mut scanned = 0;
+
+    loop {
+        if let Some(pos) = buf[scanned..].windows(4).position(|w| w == b"\r\n\r\n") {
+            let end = scanned + pos + 4;
+            if end > MAX_HEADER_SIZE {
+                return Ok(RequestHead::TooLarge);
+            }
+            let body = buf.split_off(end);
+            return Ok(RequestHead::Complete { head: buf, body });
+        }
+        if buf.len() >= MAX_HEADER_SIZE {
+            return Ok(RequestHead::TooLarge);
+        }
+        // Resume the search a few bytes back in case the terminator straddles reads
+        scanned = buf.len().saturating_sub(3);
+
+        let bytes_read = stream.read(&mut chunk).await?;
+        if bytes_read == 0 {
+            // EOF: parse whatever arrived (a bare request without the blank line)
+            if buf.is_empty() {
+                return Ok(RequestHead::Closed);
+            }
+            return Ok(RequestHead::Complete { head: buf, body: Vec::new() });
+        }
+        buf.extend_from_slice(&chunk[..bytes_read]);
+    }
+}
+
+/// Case-insensitive lookup of a header value in a raw HTTP request
+fn header_value<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request
+        .split("\r\n")
+        .skip(1) // Request line
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+}
+
+/// Whether an `Accept-Encoding` header allows gzip (`gzip;q=0` opts out)
+fn accepts_gzip(accept_encoding: Option<&str>) -> bool {
+    accept_encoding.is_some_and(|header| {
+        header.split(',').any(|item| {
+            let mut parts = item.split(';');
+            let coding = parts.next().unwrap_or("").trim();
+            let refused = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            (coding.eq_ignore_ascii_case("gzip") || coding == "*") && !refused
+        })
+    })
+}
+
+/// Text formats worth compressing; binary formats (fbx, png, jpeg) are already dense
+fn is_compressible(file_name: &str) -> bool {
+    let ext = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    matches!(ext.as_str(), "json" | "txt" | "obj" | "mtl")
+}
+
+/// Strong ETag derived from the file's size and modification time
+fn file_etag(metadata: &std::fs::Metadata) -> String {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("\"{:x}-{:x}\"", metadata.len(), mtime)
+}
+
+/// ETag of the gzip representation (a different byte sequence needs its own tag)
+fn gzip_etag(etag: &str) -> String {
+    format!("{}-gzip\"", etag.trim_end_matches('"'))
+}
+
+/// ETag of the framed representation (StreamingHeader + file)
+fn framed_etag(etag: &str) -> String {
+    format!("{}-framed\"", etag.trim_end_matches('"'))
+}
+
+/// SHA-256 cache entry, valid while the file's size and mtime are unchanged
+struct CachedHash {
+    len: u64,
+    modified: Option<SystemTime>,
+    hex: String,
+}
+
+// Per-file hashes keyed by path, so large assets aren't rehashed on every request
+static HASH_CACHE: Mutex<BTreeMap<PathBuf, CachedHash>> = Mutex::new(BTreeMap::new());
+
+/// Lowercase hex SHA-256 of the whole file, as sent in `X-Content-SHA256`
+fn cached_sha256(path: &Path) -> std::io::Result<String> {
+    let metadata = std::fs::metadata(path)?;
+    let (len, modified) = (metadata.len(), metadata.modified().ok());
+    {
+        let cache = HASH_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = cache.get(path) {
+            if entry.len == len && entry.modified == modified {
+                return Ok(entry.hex.clone());
+            }
+        }
+    }
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+
+    let mut cache = HASH_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.insert(path.to_path_buf(), CachedHash { len, modified, hex: hex.clone() });
+    Ok(hex)
+}
+
+/// Evaluate the conditional request headers against the file's validators
+///
+/// `If-None-Match` takes precedence; `If-Modified-Since` is only consulted
+/// when it is absent (RFC 9110 section 13.2.2).
+fn is_not_modified(request: &str, etag: &str, modified: Option<SystemTime>) -> bool {
+    if let Some(if_none_match) = header_value(request, "If-None-Match") {
+        return if_none_match.split(',').any(|tag| {
+            let tag = tag.trim();
+            let tag = tag.strip_prefix("W/").unwrap_or(tag);
+            tag == "*" || tag == etag || tag == gzip_etag(etag) || tag == framed_etag(etag)
+        });
+    }
+
+    let since = header_value(request, "If-Modified-Since").and_then(parse_http_date);
+    match (since, modified.and_then(|m| m.duration_since(UNIX_EPOCH).ok())) {
+        (Some(since), Some(mtime)) => mtime.as_secs() <= since,
+        _ => false,
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"]; // 1970-01-01 was a Thursday
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Format a timestamp as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn http_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let rem = secs % 86_400;
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        rem / 3600,
+        (rem / 60) % 60,
+        rem % 60
+    )
+}
+
+/// Parse an IMF-fixdate into seconds since the Unix epoch
+fn parse_http_date(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+    let day: u32 = parts[1].parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == parts[2])? as u32 + 1;
+    let year: i64 = parts[3].parse().ok()?;
+    let mut clock = parts[4].split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (clock.next()??, clock.next()??, clock.next()??);
+    if !(1..=31).contains(&day) || h > 23 || m > 59 || s > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + h * 3600 + m * 60 + s)
+}
+
+/// Days since 1970-01-01 -> (year, month, day), proleptic Gregorian
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// (year, month, day) -> days since 1970-01-01, inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Outcome of parsing a `Range` header against a file size
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,               // Missing, malformed or multi-range: send the whole file
+    Partial(u64, u64),  // Inclusive byte span
+    Unsatisfiable,      // 416
+}
+
+/// Parse a single `bytes=` range: `start-end`, open-ended `start-` or suffix `-len`
+fn parse_byte_range(header: &str, file_size: u64) -> ByteRange {
+    let spec = match header.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return ByteRange::Full,
+    };
+    let (first, last) = match spec.split_once('-') {
+        Some(parts) => parts,
+        None => return ByteRange::Full,
+    };
+
+    if first.is_empty() {
+        // Suffix range: the final `len` bytes
+        return match last.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if file_size == 0 => ByteRange::Unsatisfiable,
+            Ok(len) => ByteRange::Partial(file_size.saturating_sub(len), file_size - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+
+    let start = match first.parse::<u64>() {
+        Ok(start) => start,
+        Err(_) => return ByteRange::Full,
+    };
+    let end = if last.is_empty() {
+        file_size.saturating_sub(1)
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end.min(file_size.saturating_sub(1)),
+            _ => return ByteRange::Full,
+        }
+    };
+
+    if start >= file_size {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+/// Write a JSON `ErrorResponse` with the given status line (e.g. "403 Forbidden")
+async fn write_error_response<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    status: &str,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let error = ErrorResponse {
+        error: message.to_string(),
+    };
+    let error_json = serde_json::to_string(&error)?;
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        status,
+        error_json.len(),
+        error_json
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Decode `%XX` escapes in a URL path or query component
+/// Returns None for malformed escapes or non-UTF-8 results
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolve a requested (already percent-decoded) file name to a path inside the asset root
+///
+/// Returns None (-> 403) when the name contains a NUL byte, is absolute,
+/// climbs above the root with `..`, or - once symlinks are resolved by
+/// canonicalization - lands outside the root.
+fn resolve_asset_path(root: &Path, file_name: &str) -> Option<PathBuf> {
+    if file_name.contains('\0') {
+        return None;
+    }
+
+    // Lexical normalization
+    let mut relative = PathBuf::new();
+    for component in Path::new(file_name).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !relative.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    let joined = root.join(relative);
+
+    // Canonical check (catches symlinks pointing outside the root)
+    match (joined.canonicalize(), root.canonicalize()) {
+        (Ok(resolved), Ok(root)) if resolved.starts_with(&root) => Some(resolved),
+        (Ok(_), _) => None,
+        // Nonexistent files can't escape; let the caller report 404
+        (Err(_), _) => Some(joined),
+    }
+}
+
+/// Find a requested file across the asset roots, in `ASSET_ROOTS` order
+///
+/// The first root holding the file wins. Traversal protection applies per
+/// root: if the first match would escape its root (e.g. an escaping symlink)
+/// the lookup fails with None (-> 403) rather than falling through to a later
+/// root. When no root has the file, the path under the first root is
+/// returned so the caller reports 404.
+fn locate_asset(roots: &[PathBuf], file_name: &str) -> Option<PathBuf> {
+    let mut fallback = None;
+    for root in roots {
+        let path = resolve_asset_path(root, file_name)?;
+        if path.exists() {
+            return Some(path);
+        }
+        fallback.get_or_insert(path);
+    }
+    fallback
+}
+
+/// First asset root that is a directory without the read-only flag
+///
+/// Uploads and deletes only ever touch this root, so shipped assets in the
+/// other roots stay untouched.
+fn writable_root(config: &ServerConfig) -> Option<&Path> {
+    config
+        .asset_roots
+        .iter()
+        .find(|root| std::fs::metadata(root).is_ok_and(|m| m.is_dir() && !m.permissions().readonly()))
+        .map(PathBuf::as_path)
+}
+
+/// Content type by extension: `MIME_MAP` overrides first, then the built-ins
+fn get_content_type<'a>(file_name: &str, overrides: &'a BTreeMap<String, String>) -> &'a str {
+    let ext = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
         .to_lowercase();
 
+    if let Some(content_type) = overrides.get(&ext) {
+        return content_type;
+    }
     match ext.as_str() {
         "jpg" | "jpeg" => "image/jpeg",
         "png" => "image/png",
+        "webp" => "image/webp",
+        "ktx2" => "image/ktx2",
         "fbx" => "application/octet-stream",
         "obj" => "application/octet-stream",
+        "glb" => "model/gltf-binary",
+        "gltf" => "model/gltf+json",
+        "wasm" => "application/wasm",
+        "gz" => "application/gzip",
         "mtl" => "text/plain",
         "txt" => "text/plain",
         "json" => "application/json",
         _ => "application/octet-stream",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run one raw request through `handle_client` and return the raw response
+    async fn roundtrip(config: &ServerConfig, request: &str) -> String {
+        String::from_utf8_lossy(&roundtrip_bytes(config, request).await).into_owned()
+    }
+
+    async fn roundtrip_bytes(config: &ServerConfig, request: impl AsRef<[u8]>) -> Vec<u8> {
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        client.write_all(request.as_ref()).await.unwrap();
+        client.shutdown().await.unwrap(); // EOF ends the keep-alive loop
+        handle_client(server, config, "test").await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        response
+    }
+
+    /// Split a raw response into its header block and body
+    fn split_response(response: &[u8]) -> (String, &[u8]) {
+        let end = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        (String::from_utf8_lossy(&response[..end]).into_owned(), &response[end + 4..])
+    }
+
+    /// Reassemble a chunked transfer-encoded body
+    fn dechunk(mut body: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        loop {
+            let line_end = body.windows(2).position(|w| w == b"\r\n").unwrap();
+            let size = usize::from_str_radix(std::str::from_utf8(&body[..line_end]).unwrap(), 16).unwrap();
+            body = &body[line_end + 2..];
+            if size == 0 {
+                return data;
+            }
+            data.extend_from_slice(&body[..size]);
+            assert_eq!(&body[size..size + 2], b"\r\n");
+            body = &body[size + 2..];
+        }
+    }
+
+    fn test_config() -> (tempfile::TempDir, ServerConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.txt"), b"hello").unwrap();
+        let config = ServerConfig {
+            asset_roots: vec![dir.path().to_path_buf()],
+            chunk_size: MIN_CHUNK_SIZE,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            overload_policy: OverloadPolicy::Queue,
+            max_kbps_per_client: 0,
+            verbose: false,
+            on_progress: None,
+            tls_cert: None,
+            tls_key: None,
+            auth_token: None,
+            require_auth_for_get: false,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            mime_map: None,
+            mime_types: BTreeMap::new(),
+            cors_origin: None,
+            listen_backlog: DEFAULT_LISTEN_BACKLOG,
+            tcp_nodelay: true,
+        };
+        (dir, config)
+    }
+
+    #[tokio::test]
+    async fn test_serves_file_inside_root() {
+        let (_dir, config) = test_config();
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("hello"));
+    }
+
+    #[test]
+    fn test_parse_request_target() {
+        let target = parse_request_target("/Assets/my%20model.fbx?lod=2&format=webp&lod=3&flag").unwrap();
+        assert_eq!(target.path, "/Assets/my model.fbx");
+        assert_eq!(target.query.get("lod").map(String::as_str), Some("2"));
+        assert_eq!(target.query.get("format").map(String::as_str), Some("webp"));
+        assert_eq!(target.query.get("flag").map(String::as_str), Some(""));
+
+        let target = parse_request_target("/Assets/?prefix=a+b%2Bc").unwrap();
+        assert_eq!(target.path, "/Assets/");
+        assert_eq!(target.query.get("prefix").map(String::as_str), Some("a b+c"));
+
+        assert!(parse_request_target("/Assets/model.txt").unwrap().query.is_empty());
+        assert_eq!(parse_request_target("/Assets/bad%zz.txt"), None);
+        assert_eq!(parse_request_target("/Assets/model.txt?q=%"), None);
+    }
+
+    #[tokio::test]
+    async fn test_query_string_ignored_for_assets() {
+        let (dir, config) = test_config();
+        std::fs::write(dir.path().join("model.fbx"), b"fbx data").unwrap();
+
+        let response = roundtrip(&config, "GET /Assets/model.fbx?lod=2 HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with("fbx data"));
+        assert_eq!(header_value(&response, "Content-Type"), Some("application/octet-stream"));
+
+        let response = roundtrip(&config, "HEAD /Assets/model.txt?quality=low&format=webp HTTP/1.1\r\n\r\n").await;
+        assert_eq!(header_value(&response, "Content-Length"), Some("5"));
+        assert!(roundtrip(&config, "GET /metrics?debug=1 HTTP/1.1\r\n\r\n").await.starts_with("HTTP/1.1 200 OK"));
+
+        // The path is decoded once: a literal `%` in a file name is sent as %25
+        std::fs::write(dir.path().join("100%.txt"), b"full").unwrap();
+        assert!(roundtrip(&config, "GET /Assets/100%25.txt HTTP/1.1\r\n\r\n").await.ends_with("full"));
+        let response = roundtrip(&config, "GET /Assets/100%.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+
+        let response = roundtrip(&config, "DELETE /Assets/model.fbx?lod=2 HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 204 No Content"));
+        assert!(!dir.path().join("model.fbx").exists());
+    }
+
+    #[tokio::test]
+    async fn test_multiple_asset_roots() {
+        let (user, mut config) = test_config();
+        let shipped = tempfile::tempdir().unwrap();
+        std::fs::write(shipped.path().join("terrain.bin"), b"shipped terrain").unwrap();
+        std::fs::write(shipped.path().join("model.txt"), b"shipped model").unwrap();
+        config.asset_roots.push(shipped.path().to_path_buf());
+
+        // Found only in the second root
+        let response = roundtrip(&config, "GET /Assets/terrain.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("shipped terrain"));
+
+        // Present in both: the earlier root shadows the later one
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with("hello"));
+
+        // Missing everywhere, or escaping any root
+        let response = roundtrip(&config, "GET /Assets/missing.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        let response = roundtrip(&config, "GET /Assets/../terrain.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+
+        // The listing merges both roots, one entry per name
+        let response = roundtrip(&config, "GET /Assets/ HTTP/1.1\r\n\r\n").await;
+        let (_, body) = split_response(response.as_bytes());
+        let entries: Vec<AssetEntry> = serde_json::from_slice(body).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["model.txt", "terrain.bin"]);
+        assert_eq!(entries[0].size, 5);
+
+        // Deletes only touch the first (writable) root, uncovering the shipped copy
+        assert_eq!(writable_root(&config), Some(user.path()));
+        let response = roundtrip(&config, "DELETE /Assets/terrain.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        let response = roundtrip(&config, "DELETE /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 204 No Content"));
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with("shipped model"));
+    }
+
+    #[tokio::test]
+    async fn test_path_traversal_rejected() {
+        let (_dir, config) = test_config();
+
+        for target in [
+            "../../etc/passwd",
+            "../secret.txt",
+            "%2e%2e/secret.txt",
+            "%2E%2E%2Fsecret.txt",
+            "/etc/passwd",
+            "model.txt%00.png",
+        ] {
+            let request = format!("GET /Assets/{} HTTP/1.1\r\n\r\n", target);
+            let response = roundtrip(&config, &request).await;
+            assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{} -> {}", target, response);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_escape_rejected() {
+        let (dir, config) = test_config();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), b"secret").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        let response = roundtrip(&config, "GET /Assets/link/secret.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+    }
+
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000), ByteRange::Partial(0, 99));
+        assert_eq!(parse_byte_range("bytes=500-", 1000), ByteRange::Partial(500, 999));
+        assert_eq!(parse_byte_range("bytes=-500", 1000), ByteRange::Partial(500, 999));
+        assert_eq!(parse_byte_range("bytes=900-5000", 1000), ByteRange::Partial(900, 999));
+        assert_eq!(parse_byte_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_byte_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_byte_range("bytes=0-1,5-6", 1000), ByteRange::Full);
+        assert_eq!(parse_byte_range("items=0-1", 1000), ByteRange::Full);
+    }
+
+    #[tokio::test]
+    async fn test_range_requests() {
+        let (dir, config) = test_config();
+        std::fs::write(dir.path().join("digits.bin"), b"0123456789").unwrap();
+
+        let response = roundtrip(&config, "GET /Assets/digits.bin HTTP/1.1\r\nRange: bytes=2-4\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 206 Partial Content"));
+        assert!(response.contains("Content-Range: bytes 2-4/10\r\n"));
+        assert!(response.contains("Content-Length: 3\r\n"));
+        assert!(response.ends_with("\r\n\r\n234"));
+
+        let response = roundtrip(&config, "GET /Assets/digits.bin HTTP/1.1\r\nrange: bytes=7-\r\n\r\n").await;
+        assert!(response.ends_with("\r\n\r\n789"));
+
+        let response = roundtrip(&config, "GET /Assets/digits.bin HTTP/1.1\r\nRange: bytes=-2\r\n\r\n").await;
+        assert!(response.ends_with("\r\n\r\n89"));
+
+        let response = roundtrip(&config, "GET /Assets/digits.bin HTTP/1.1\r\nRange: bytes=10-\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 416 Range Not Satisfiable"));
+        assert!(response.contains("Content-Range: bytes */10\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_framing_roundtrip() {
+        let (_dir, config) = test_config();
+        // Several full chunks plus a partial one
+        let original: Vec<u8> = (0..(3 * MIN_CHUNK_SIZE + 1234)).map(|i| (i * 31 % 251) as u8).collect();
+
+        let mut framed = Vec::new();
+        stream_chunked(&mut framed, &original[..], &config).await.unwrap();
+        assert!(framed.starts_with(format!("{:x}\r\n", MIN_CHUNK_SIZE).as_bytes()));
+        assert!(framed.ends_with(b"\r\n0\r\n\r\n"));
+        assert_eq!(dechunk(&framed), original);
+
+        // Empty input is just the terminating chunk
+        let mut framed = Vec::new();
+        stream_chunked(&mut framed, &b""[..], &config).await.unwrap();
+        assert_eq!(framed, b"0\r\n\r\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streams_pipe_chunked() {
+        let (dir, config) = test_config();
+        let fifo = dir.path().join("live.txt");
+        let status = std::process::Command::new("mkfifo").arg(&fifo).status().unwrap();
+        assert!(status.success());
+
+        let original = "telemetry line\n".repeat(500);
+        let writer = {
+            let (fifo, original) = (fifo.clone(), original.clone());
+            std::thread::spawn(move || std::fs::write(fifo, original).unwrap())
+        };
+
+        let response = roundtrip_bytes(&config, "GET /Assets/live.txt HTTP/1.1\r\n\r\n").await;
+        writer.join().unwrap();
+        let (headers, body) = split_response(&response);
+        assert!(headers.starts_with("HTTP/1.1 200 OK"));
+        assert!(headers.contains("Transfer-Encoding: chunked"));
+        assert!(!headers.contains("Content-Length"));
+        assert_eq!(dechunk(body), original.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_gzip_text_asset_roundtrip() {
+        let (dir, config) = test_config();
+        let original = "v 0.0 1.0 2.0\n".repeat(3000);
+        std::fs::write(dir.path().join("mesh.obj"), &original).unwrap();
+        std::fs::write(dir.path().join("texture.png"), &original).unwrap();
+
+        let response = roundtrip_bytes(
+            &config,
+            "GET /Assets/mesh.obj HTTP/1.1\r\nAccept-Encoding: deflate, gzip\r\n\r\n",
+        )
+        .await;
+        let (headers, body) = split_response(&response);
+        assert!(headers.contains("Content-Encoding: gzip"));
+        assert!(headers.contains("Transfer-Encoding: chunked"));
+        assert!(!headers.contains("Content-Length"));
+
+        let compressed = dechunk(body);
+        assert!(compressed.len() < original.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&compressed[..]).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, original);
+
+        // Binary formats and clients that refuse gzip get the identity encoding
+        for request in [
+            "GET /Assets/texture.png HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n",
+            "GET /Assets/mesh.obj HTTP/1.1\r\nAccept-Encoding: gzip;q=0\r\n\r\n",
+            "GET /Assets/mesh.obj HTTP/1.1\r\n\r\n",
+        ] {
+            let response = roundtrip_bytes(&config, request).await;
+            let (headers, body) = split_response(&response);
+            assert!(!headers.contains("Content-Encoding"));
+            assert_eq!(body, original.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_request_line() {
+        let (_dir, config) = test_config();
+        for request in [
+            "GET /Assets/model.txt\r\n\r\n",              // No version
+            "GET  /Assets/model.txt HTTP/1.1\r\n\r\n",     // Double space
+            "get /Assets/model.txt HTTP/1.1\r\n\r\n",      // Lowercase method
+            "GET Assets/model.txt HTTP/1.1\r\n\r\n",       // Not origin-form
+            "GET /Assets/model.txt HTTP/2.0\r\n\r\n",      // Unsupported version
+            "\x16\x03\x01garbage\r\n\r\n",                  // TLS hello on the plain port
+        ] {
+            let response = roundtrip(&config, request).await;
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{request:?}");
+            assert!(response.ends_with(r#"{"error":"Malformed request line"}"#), "{request:?}");
+        }
+        assert!(roundtrip(&config, "GET /Assets/model.txt HTTP/1.0\r\n\r\n").await.ends_with("hello"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_io_error_returns_500() {
+        use std::os::unix::fs::PermissionsExt;
+        let (dir, config) = test_config();
+
+        // Reading a directory as a file fails after the existence check
+        std::fs::create_dir(dir.path().join("textures.bin")).unwrap();
+        let response = roundtrip(&config, "GET /Assets/textures.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+        assert!(response.ends_with(r#"{"error":"Internal server error"}"#));
+
+        let locked = dir.path().join("locked.bin");
+        std::fs::write(&locked, b"secret").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        if File::open(&locked).is_ok() {
+            return; // Running as root: permissions aren't enforced
+        }
+        let response = roundtrip(&config, "GET /Assets/locked.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+        assert!(!response.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_large_request_headers() {
+        let (_dir, config) = test_config();
+        let cookie = "c".repeat(2048);
+        let request = format!("GET /Assets/model.txt HTTP/1.1\r\nCookie: {}\r\nRange: bytes=1-2\r\n\r\n", cookie);
+        let response = roundtrip(&config, &request).await;
+        assert!(response.starts_with("HTTP/1.1 206 Partial Content"));
+        assert!(response.ends_with("\r\n\r\nel"));
+
+        let request = format!("GET /Assets/model.txt HTTP/1.1\r\nCookie: {}\r\n\r\n", "c".repeat(MAX_HEADER_SIZE));
+        let response = roundtrip(&config, &request).await;
+        assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+    }
+
+    #[test]
+    fn test_http_date_roundtrip() {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+        assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+        assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_requests() {
+        let (_dir, config) = test_config();
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        let etag = header_value(&response, "ETag").unwrap().to_string();
+        let last_modified = header_value(&response, "Last-Modified").unwrap().to_string();
+
+        let response = roundtrip(&config, &format!("GET /Assets/model.txt HTTP/1.1\r\nIf-None-Match: \"x\", {}\r\n\r\n", etag)).await;
+        assert!(response.starts_with("HTTP/1.1 304 Not Modified"));
+        assert!(response.ends_with("\r\n\r\n"));
+        assert!(!response.contains("hello"));
+
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\nIf-None-Match: \"stale\"\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("hello"));
+
+        let response = roundtrip(&config, &format!("GET /Assets/model.txt HTTP/1.1\r\nIf-Modified-Since: {}\r\n\r\n", last_modified)).await;
+        assert!(response.starts_with("HTTP/1.1 304 Not Modified"));
+
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\nIf-Modified-Since: Thu, 01 Jan 1970 00:00:00 GMT\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[tokio::test]
+    async fn test_head_request() {
+        let (_dir, config) = test_config();
+        let response = roundtrip(&config, "HEAD /Assets/model.txt HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(header_value(&response, "Content-Length"), Some("5"));
+        assert_eq!(header_value(&response, "Content-Type"), Some("text/plain"));
+        assert_eq!(header_value(&response, "Accept-Ranges"), Some("bytes"));
+        assert!(header_value(&response, "ETag").is_some());
+        assert!(response.ends_with("\r\n\r\n")); // Zero body bytes
+
+        let response = roundtrip(&config, "HEAD /Assets/missing.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(response.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_parse_chunk_size() {
+        assert_eq!(parse_chunk_size(None), CHUNK_SIZE);
+        assert_eq!(parse_chunk_size(Some("64")), 64 * 1024);
+        assert_eq!(parse_chunk_size(Some("32768")), 32 * 1024 * 1024);
+        assert_eq!(parse_chunk_size(Some("32")), CHUNK_SIZE);
+        assert_eq!(parse_chunk_size(Some("65536")), CHUNK_SIZE);
+        assert_eq!(parse_chunk_size(Some("lots")), CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_parse_bind_addr() {
+        assert_eq!(parse_bind_addr("0.0.0.0", 8080).unwrap().to_string(), "0.0.0.0:8080");
+        assert_eq!(parse_bind_addr(" 127.0.0.1 ", 80).unwrap().to_string(), "127.0.0.1:80");
+        assert_eq!(parse_bind_addr("[::]", 8080).unwrap().to_string(), "[::]:8080");
+        assert_eq!(parse_bind_addr("::1", 8443).unwrap().to_string(), "[::1]:8443");
+        assert!(parse_bind_addr("localhost", 8080).unwrap_err().contains("invalid BIND_ADDR"));
+        assert!(parse_bind_addr("[127.0.0.1", 8080).is_err());
+        assert!(parse_bind_addr("0.0.0.0:9090", 8080).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bind_listener_accepts_with_nodelay() {
+        let listener = bind_listener(parse_bind_addr("127.0.0.1", 0).unwrap(), 4).unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+
+        // Connections queue in the backlog until accepted
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            clients.push(TcpStream::connect(addr).await.unwrap());
+        }
+        for _ in &clients {
+            let (stream, _) = listener.accept().await.unwrap();
+            stream.set_nodelay(true).unwrap();
+            assert!(stream.nodelay().unwrap());
+        }
+
+        // A second listener on the same port is refused (no SO_REUSEPORT)
+        assert!(bind_listener(addr, 4).is_err());
+    }
+
+    #[test]
+    fn test_parse_overload_policy() {
+        assert_eq!(OverloadPolicy::parse("queue"), Some(OverloadPolicy::Queue));
+        assert_eq!(OverloadPolicy::parse(" Reject "), Some(OverloadPolicy::Reject));
+        assert_eq!(OverloadPolicy::parse("503"), Some(OverloadPolicy::Reject));
+        assert_eq!(OverloadPolicy::parse("drop"), None);
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_completion() {
+        static REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        fn record(progress: &TransferProgress) {
+            if progress.file_name.starts_with("progress_") {
+                REPORTS.lock().unwrap().push(progress.log_line());
+            }
+        }
+
+        let (dir, mut config) = test_config();
+        config.on_progress = Some(record);
+        // Neither size is a multiple of the old 10MB reporting boundary
+        std::fs::write(dir.path().join("progress_small.bin"), vec![7u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("progress_empty.bin"), b"").unwrap();
+        roundtrip(&config, "GET /Assets/progress_small.bin HTTP/1.1\r\n\r\n").await;
+        roundtrip(&config, "GET /Assets/progress_empty.bin HTTP/1.1\r\n\r\n").await;
+
+        let reports = REPORTS.lock().unwrap();
+        assert!(reports.iter().any(|line| line.starts_with("[NAVΛ Server] Streamed progress_small.bin: 100.0%")), "{reports:?}");
+        assert!(reports.iter().any(|line| line.starts_with("[NAVΛ Server] Streamed progress_empty.bin: 100.0%")), "{reports:?}");
+    }
+
+    #[test]
+    fn test_progress_eta() {
+        let progress = TransferProgress {
+            file_name: "big.bin",
+            sent: 25 * 1024 * 1024,
+            total: 100 * 1024 * 1024,
+            elapsed: Duration::from_secs(5),
+        };
+        assert_eq!(progress.percent(), 25.0);
+        assert_eq!(progress.bytes_per_sec(), 5.0 * 1024.0 * 1024.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(15)));
+        assert_eq!(
+            progress.log_line(),
+            "[NAVΛ Server] Streaming big.bin... 25.0% (25.00 MB / 100.00 MB, 5.00 MB/s, ETA 15.0s)"
+        );
+        assert_eq!(TransferProgress { sent: 0, ..progress }.eta(), None);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_paces_to_rate() {
+        assert!(Throttle::new(0).is_none());
+
+        // 100 KB/s with one second of burst: 150 KB needs at least another half second
+        let mut throttle = Throttle::new(100).unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            throttle.consume(50 * 1024).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[test]
+    fn test_tls_config_errors() {
+        let (dir, mut config) = test_config();
+        assert!(load_tls_acceptor(&config).unwrap().is_none());
+
+        config.tls_cert = Some(dir.path().join("cert.pem"));
+        let err = load_tls_acceptor(&config).err().unwrap().to_string();
+        assert!(err.contains("must be set together"));
+
+        config.tls_key = Some(dir.path().join("key.pem"));
+        let err = load_tls_acceptor(&config).err().unwrap().to_string();
+        assert!(err.contains("failed to load TLS_CERT"));
+
+        config.tls_cert = Some(dir.path().join("model.txt"));
+        let err = load_tls_acceptor(&config).err().unwrap().to_string();
+        assert!(err.contains("contains no certificates"));
+    }
+
+    #[test]
+    fn test_content_types() {
+        let defaults = BTreeMap::new();
+        assert_eq!(get_content_type("ship.GLB", &defaults), "model/gltf-binary");
+        assert_eq!(get_content_type("scene.gltf", &defaults), "model/gltf+json");
+        assert_eq!(get_content_type("viewer.wasm", &defaults), "application/wasm");
+        assert_eq!(get_content_type("albedo.webp", &defaults), "image/webp");
+        assert_eq!(get_content_type("albedo.ktx2", &defaults), "image/ktx2");
+        assert_eq!(get_content_type("mystery.xyz", &defaults), "application/octet-stream");
+        assert_eq!(get_content_type("no_extension", &defaults), "application/octet-stream");
+
+        let (dir, _config) = test_config();
+        let path = dir.path().join("mime.json");
+        std::fs::write(&path, r#"{".XYZ": "application/x-xyz", "json": "application/geo+json"}"#).unwrap();
+        let overrides = load_mime_map(Some(&path)).unwrap();
+        assert_eq!(get_content_type("mystery.xyz", &overrides), "application/x-xyz");
+        assert_eq!(get_content_type("map.json", &overrides), "application/geo+json");
+        assert_eq!(get_content_type("ship.glb", &overrides), "model/gltf-binary");
+
+        assert!(load_mime_map(None).unwrap().is_empty());
+        std::fs::write(&path, "[1, 2]").unwrap();
+        assert!(load_mime_map(Some(&path)).err().unwrap().to_string().contains("invalid MIME_MAP"));
+        let missing = dir.path().join("missing.json");
+        assert!(load_mime_map(Some(&missing)).err().unwrap().to_string().contains("failed to read MIME_MAP"));
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_and_headers() {
+        let (_dir, mut config) = test_config();
+        config.auth_token = Some("s3cret".to_string());
+        let preflight = "OPTIONS /Assets/model.txt HTTP/1.1\r\nOrigin: http://dash.local\r\nAccess-Control-Request-Method: GET\r\nAccess-Control-Request-Headers: range\r\n\r\n";
+
+        // Disabled: OPTIONS is not handled and GETs carry no CORS headers
+        let response = roundtrip(&config, preflight).await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(!response.contains("Access-Control-"));
+
+        config.cors_origin = Some("*".to_string());
+        let response = roundtrip(&config, preflight).await;
+        let (headers, body) = split_response(response.as_bytes());
+        assert!(headers.starts_with("HTTP/1.1 204 No Content"));
+        assert_eq!(header_value(&headers, "Access-Control-Allow-Origin"), Some("*"));
+        assert!(header_value(&headers, "Access-Control-Allow-Methods").unwrap().contains("GET"));
+        assert!(header_value(&headers, "Access-Control-Allow-Headers").unwrap().contains("Range"));
+        assert!(body.is_empty());
+
+        for request in [
+            "GET /Assets/model.txt HTTP/1.1\r\n\r\n",
+            "HEAD /Assets/model.txt HTTP/1.1\r\n\r\n",
+            "GET /Assets/ HTTP/1.1\r\n\r\n",
+            "GET /Assets/missing.txt HTTP/1.1\r\n\r\n",
+        ] {
+            let response = roundtrip(&config, request).await;
+            assert_eq!(header_value(&response, "Access-Control-Allow-Origin"), Some("*"), "{request}");
+        }
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(header_value(&response, "Access-Control-Expose-Headers").unwrap().contains("X-Content-SHA256"));
+        assert!(!response.contains("Vary: Origin"));
+
+        config.cors_origin = Some("http://dash.local".to_string());
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert_eq!(header_value(&response, "Access-Control-Allow-Origin"), Some("http://dash.local"));
+        assert!(response.contains("Vary: Origin\r\n"));
+    }
+
+    #[test]
+    fn test_streaming_header_round_trip() {
+        let header = StreamingHeader {
+            file_name: "terrain/ß.bin".to_string(),
+            file_size: 0x0102_0304_0506_0708,
+            chunk_size: 2 * 1024 * 1024,
+            is_streaming: 1,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), header.encoded_len());
+        // Layout is pinned: little-endian regardless of the host
+        assert_eq!(&bytes[..4], &14u32.to_le_bytes());
+        assert_eq!(&bytes[4..18], "terrain/ß.bin".as_bytes());
+        assert_eq!(&bytes[18..26], &[8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(&bytes[26..30], &[0, 0, 0x20, 0]);
+        assert_eq!(bytes[30], 1);
+
+        let mut framed = bytes.clone();
+        framed.extend_from_slice(b"file data");
+        assert_eq!(StreamingHeader::from_bytes(&framed), Some((header, bytes.len())));
+        for len in 0..bytes.len() {
+            assert_eq!(StreamingHeader::from_bytes(&bytes[..len]), None, "truncated to {len}");
+        }
+        let mut bad_name = bytes.clone();
+        bad_name[4] = 0xff;
+        assert_eq!(StreamingHeader::from_bytes(&bad_name), None);
+    }
+
+    #[tokio::test]
+    async fn test_framed_asset_response() {
+        let (_dir, config) = test_config();
+        let response = roundtrip_bytes(&config, "GET /Assets/model.txt?framed=1 HTTP/1.1\r\nRange: bytes=1-2\r\nAccept-Encoding: gzip\r\n\r\n").await;
+        let (headers, body) = split_response(&response);
+        assert!(headers.starts_with("HTTP/1.1 200 OK"), "{headers}");
+        assert_eq!(header_value(&headers, "Content-Type"), Some(FRAMED_CONTENT_TYPE));
+        assert_eq!(header_value(&headers, "Content-Length"), Some(body.len().to_string().as_str()));
+        assert!(header_value(&headers, "ETag").unwrap().ends_with("-framed\""));
+
+        let (header, offset) = StreamingHeader::from_bytes(body).unwrap();
+        assert_eq!(header.file_name, "model.txt");
+        assert_eq!(header.file_size, 5);
+        assert_eq!(header.chunk_size as usize, MIN_CHUNK_SIZE);
+        assert_eq!(header.is_streaming, 0);
+        assert_eq!(&body[offset..], b"hello");
+
+        // Unframed responses are unchanged
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with("\r\n\r\nhello"));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_upload_is_stored_decompressed() {
+        let (dir, config) = test_config();
+        let original: Vec<u8> = (0..20_000u32).flat_map(|i| (i % 251).to_le_bytes()).collect();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&original).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(compressed.len() < original.len());
+
+        let mut request = format!(
+            "POST /Assets/meshes/terrain.bin HTTP/1.1\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+            compressed.len()
+        )
+        .into_bytes();
+        request.extend_from_slice(&compressed);
+        let response = roundtrip_bytes(&config, &request).await;
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+        assert_eq!(std::fs::read(dir.path().join("meshes/terrain.bin")).unwrap(), original);
+
+        // Uncompressed bodies are stored verbatim
+        let response = roundtrip(&config, "POST /Assets/plain.txt HTTP/1.1\r\nContent-Length: 5\r\n\r\nworld").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let response = roundtrip(&config, "GET /Assets/plain.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with("world"));
+
+        // Corrupt gzip, unknown encodings and traversal are refused without writing
+        let response = roundtrip(&config, "POST /Assets/bad.bin HTTP/1.1\r\nContent-Encoding: gzip\r\nContent-Length: 4\r\n\r\nnope").await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        let response = roundtrip(&config, "POST /Assets/bad.bin HTTP/1.1\r\nContent-Encoding: br\r\nContent-Length: 4\r\n\r\nnope").await;
+        assert!(response.starts_with("HTTP/1.1 415 Unsupported Media Type"));
+        assert!(!dir.path().join("bad.bin").exists());
+        let response = roundtrip(&config, "POST /Assets/../escape.bin HTTP/1.1\r\nContent-Length: 4\r\n\r\nnope").await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+
+        // Bodies that would inflate past the cap are rejected before they are fully decompressed
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0u8; 64 * 1024]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        let path = dir.path().join("bomb.bin");
+        let inflate = |limit| receive_upload(&bomb[..], bomb.len() as u64, &path, true, limit);
+        assert_eq!(inflate(1024).await.unwrap_err().kind(), std::io::ErrorKind::FileTooLarge);
+        inflate(64 * 1024).await.unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 64 * 1024);
+
+        // A truncated gzip stream is invalid, a body shorter than its Content-Length is an error
+        let response = roundtrip_bytes(
+            &config,
+            [format!("POST /Assets/cut.bin HTTP/1.1\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", bomb.len() - 4).as_bytes(), &bomb[..bomb.len() - 4]].concat(),
+        )
+        .await;
+        assert!(response.starts_with(b"HTTP/1.1 400 Bad Request"));
+        assert!(!dir.path().join("cut.bin").exists() && !dir.path().join("cut.bin.upload").exists());
+        let short = receive_upload(&b"abc"[..], 10, &path, false, MAX_UPLOAD_SIZE).await;
+        assert_eq!(short.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_auth() {
+        let (_dir, mut config) = test_config();
+        config.auth_token = Some("s3cret".to_string());
+
+        let response = roundtrip(&config, "POST /Assets/upload.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(response.contains("WWW-Authenticate: Bearer\r\n"));
+
+        let response = roundtrip(&config, "POST /Assets/upload.bin HTTP/1.1\r\nAuthorization: Bearer s3cre7\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+
+        let response = roundtrip(&config, "POST /Assets/upload.bin HTTP/1.1\r\nAuthorization: Bearer s3cret\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // Reads stay open unless REQUIRE_AUTH_FOR_GET is set
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        config.require_auth_for_get = true;
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\nauthorization: bearer s3cret\r\n\r\n").await;
+        assert!(response.ends_with("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_asset() {
+        let (dir, mut config) = test_config();
+        config.auth_token = Some("s3cret".to_string());
+        std::fs::write(dir.path().join("stale.bin"), b"old").unwrap();
+        let auth = "Authorization: Bearer s3cret\r\n";
+
+        let response = roundtrip(&config, "DELETE /Assets/stale.bin HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(dir.path().join("stale.bin").exists());
+
+        let response = roundtrip(&config, &format!("DELETE /Assets/stale.bin HTTP/1.1\r\n{auth}\r\n")).await;
+        assert!(response.starts_with("HTTP/1.1 204 No Content"));
+        assert!(!dir.path().join("stale.bin").exists());
+
+        let response = roundtrip(&config, &format!("DELETE /Assets/stale.bin HTTP/1.1\r\n{auth}\r\n")).await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+
+        // Traversal and directories are refused
+        let outside = dir.path().parent().unwrap().join("nav_delete_outside.txt");
+        std::fs::write(&outside, b"keep").unwrap();
+        let response = roundtrip(&config, &format!("DELETE /Assets/../nav_delete_outside.txt HTTP/1.1\r\n{auth}\r\n")).await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+        assert!(outside.exists());
+        std::fs::remove_file(&outside).unwrap();
+        let response = roundtrip(&config, &format!("DELETE /Assets/ HTTP/1.1\r\n{auth}\r\n")).await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+        assert!(!constant_time_eq(b"", b"token"));
+    }
+
+    #[tokio::test]
+    async fn test_directory_listing() {
+        let (dir, config) = test_config();
+        std::fs::write(dir.path().join("mesh.obj"), b"v 0 0 0").unwrap();
+        std::fs::write(dir.path().join("model.json"), b"{}").unwrap();
+        std::fs::create_dir(dir.path().join("textures")).unwrap();
+
+        let response = roundtrip(&config, "GET /Assets/ HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: application/json\r\n"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let entries: Vec<AssetEntry> = serde_json::from_str(body).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["mesh.obj", "model.json", "model.txt"]);
+        assert_eq!(entries[2].size, 5);
+        assert!(entries[2].modified > 0);
+
+        let response = roundtrip(&config, "GET /Assets/?prefix=mod HTTP/1.1\r\n\r\n").await;
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let entries: Vec<AssetEntry> = serde_json::from_str(body).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.name.starts_with("mod")));
+    }
+
+    #[tokio::test]
+    async fn test_content_sha256_header() {
+        let (dir, config) = test_config();
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        let advertised = header_value(&response, "X-Content-SHA256").unwrap().to_string();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let expected: String = Sha256::digest(body.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(advertised, expected);
+        assert_eq!(advertised, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+
+        // Partial responses still advertise the whole-file hash
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\nRange: bytes=0-1\r\n\r\n").await;
+        assert_eq!(header_value(&response, "X-Content-SHA256"), Some(expected.as_str()));
+
+        // A rewritten file (new size) is rehashed rather than served from the cache
+        std::fs::write(dir.path().join("model.txt"), b"hello world").unwrap();
+        let response = roundtrip(&config, "GET /Assets/model.txt HTTP/1.1\r\n\r\n").await;
+        assert_eq!(
+            header_value(&response, "X-Content-SHA256"),
+            Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_reuses_connection() {
+        let (_dir, config) = test_config();
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        let handle = tokio::spawn(async move { handle_client(server, &config, "test").await.unwrap() });
+
+        /// Read until the buffer ends with `suffix`
+        async fn read_until(client: &mut tokio::io::DuplexStream, suffix: &[u8]) -> Vec<u8> {
+            let mut response = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !response.ends_with(suffix) {
+                let n = client.read(&mut buf).await.unwrap();
+                assert!(n > 0, "connection closed early");
+                response.extend_from_slice(&buf[..n]);
+            }
+            response
+        }
+
+        client.write_all(b"GET /Assets/model.txt HTTP/1.1\r\n\r\n").await.unwrap();
+        let first = read_until(&mut client, b"hello").await;
+        assert!(first.starts_with(b"HTTP/1.1 200 OK"));
+
+        client.write_all(b"HEAD /Assets/model.txt HTTP/1.1\r\n\r\n").await.unwrap();
+        let second = read_until(&mut client, b"\r\n\r\n").await;
+        assert!(second.starts_with(b"HTTP/1.1 200 OK"));
+
+        // `Connection: close` ends the loop after the response
+        client.write_all(b"GET /Assets/model.txt HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.ends_with(b"hello"));
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_requests() {
+        let (_dir, config) = test_config();
+        let response = roundtrip(
+            &config,
+            "HEAD /Assets/model.txt HTTP/1.1\r\n\r\nGET /Assets/model.txt HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 2);
+        assert!(response.ends_with("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_body_closes_connection() {
+        let (_dir, config) = test_config();
+        // The "body" is a smuggled request; it must not be answered
+        let smuggled = "GET /Assets/model.txt HTTP/1.1\r\n\r\n";
+        for framing in [format!("Content-Length: {}", smuggled.len()), "Transfer-Encoding: chunked".to_string()] {
+            for method in ["GET", "HEAD"] {
+                let request = format!("{method} /health HTTP/1.1\r\n{framing}\r\n\r\n{smuggled}");
+                let response = roundtrip(&config, &request).await;
+                assert_eq!(response.matches("HTTP/1.1 ").count(), 1, "{request:?}: {response}");
+                assert!(!response.ends_with("hello"));
+            }
+        }
+
+        // Without a body the same pipeline is answered twice
+        let response = roundtrip(&config, &format!("GET /health HTTP/1.1\r\n\r\n{smuggled}")).await;
+        assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 2);
+    }
+
+    #[test]
+    fn test_is_keep_alive() {
+        assert!(is_keep_alive("GET / HTTP/1.1\r\n\r\n"));
+        assert!(!is_keep_alive("GET / HTTP/1.1\r\nConnection: close\r\n\r\n"));
+        assert!(!is_keep_alive("GET / HTTP/1.0\r\n\r\n"));
+        assert!(is_keep_alive("GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let (_dir, mut config) = test_config();
+        // Probes carry no token and must not touch the (missing) asset roots
+        config.auth_token = Some("s3cret".to_string());
+        config.require_auth_for_get = true;
+        config.asset_roots = vec![PathBuf::from("/nonexistent/nav_assets")];
+
+        for request in ["GET /health HTTP/1.1\r\n\r\n", "GET /healthz?probe=k8s HTTP/1.1\r\n\r\n"] {
+            let response = roundtrip(&config, request).await;
+            let (headers, body) = split_response(response.as_bytes());
+            assert!(headers.starts_with("HTTP/1.1 200 OK"), "{request}");
+            assert_eq!(header_value(&headers, "Content-Type"), Some("application/json"));
+            let health: HealthResponse = serde_json::from_slice(body).unwrap();
+            assert_eq!(health.status, "ok");
+            assert!(health.uptime_secs < 3600);
+        }
+
+        let response = roundtrip(&config, "GET /healthcheck HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_endpoint() {
+        let (_dir, mut config) = test_config();
+        // Upload token alone: verify writes nothing, so it stays open
+        config.auth_token = Some("s3cret".to_string());
+        let post = |body: &str| format!("POST /verify HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+
+        // pos_norm 5 + t_phase 0 + gradient 0 (y = 0) + certainty 0.9 + fatigue 0.8
+        let body = r#"{"state": {"position": [3, 0, 4], "certainty": 0.9, "fatigue": 0.8},
+                       "params": {"min_margin": 0.5}, "obstacles": [[10, 0, 4]]}"#;
+        let response = roundtrip(&config, &post(body)).await;
+        let (headers, body) = split_response(response.as_bytes());
+        assert!(headers.starts_with("HTTP/1.1 200 OK"), "{headers}");
+        assert_eq!(header_value(&headers, "Content-Type"), Some("application/json"));
+        let result: VerifyResponse = serde_json::from_slice(body).unwrap();
+        assert!((result.p_score - 6.7).abs() < 1e-5, "p_score {}", result.p_score);
+        assert!(result.is_safe);
+        assert!((result.margin - 6.5).abs() < 1e-5); // Distance 7 minus min_margin
+        assert_eq!(result.closest_obstacle_index, Some(0));
+        assert_eq!(result.evidence_hash.len(), "sha256:".len() + 64);
+        assert_eq!(result.terms, [5.0, 0.0, 0.0, 0.9, 0.8]);
+        assert!((result.terms.iter().sum::<f32>() - result.p_score).abs() < 1e-5);
+        assert!(result.sigma.is_finite());
+
+        // Breaches land in the server's context, not the core's default one
+        let body = r#"{"state": {"position": [0, 0, 0], "certainty": 0.9, "fatigue": 0.8},
+                       "params": {"min_margin": 0.5}, "obstacles": [[0.1, 0, 0]]}"#;
+        let response = roundtrip(&config, &post(body)).await;
+        let (_, body) = split_response(response.as_bytes());
+        let result: VerifyResponse = serde_json::from_slice(body).unwrap();
+        assert!(!result.is_safe);
+        unsafe {
+            assert!(nav_lambda_core::nav_breach_log_count(verify_context()) > 0);
+            assert_eq!(nav_lambda_core::nav_breach_log_count(std::ptr::null()), 0);
+        }
+
+        // Same request under L1: pos_norm 3 + 4
+        let body = r#"{"state": {"position": [3, 0, 4], "certainty": 0.9, "fatigue": 0.8},
+                       "params": {"min_margin": 0.5, "distance_metric": "manhattan"}, "obstacles": [[10, 0, 4]]}"#;
+        let response = roundtrip(&config, &post(body)).await;
+        let (headers, body) = split_response(response.as_bytes());
+        assert!(headers.starts_with("HTTP/1.1 200 OK"), "{headers}");
+        let result: VerifyResponse = serde_json::from_slice(body).unwrap();
+        assert!((result.p_score - 8.7).abs() < 1e-5, "p_score {}", result.p_score);
+        assert!((result.margin - 6.5).abs() < 1e-5);
+
+        // Malformed JSON, a missing required field, an unknown field or metric, a core rejection
+        for body in [
+            "{not json",
+            r#"{"state": {"position": [0, 0, 0], "certainty": 0.9}}"#,
+            r#"{"state": {"position": [0, 0, 0], "certainty": 0.9, "fatigue": 0.9}, "obstacle": []}"#,
+            r#"{"state": {"position": [0, 0, 0], "certainty": 0.9, "fatigue": 0.9}, "params": {"distance_metric": "taxicab"}}"#,
+            r#"{"state": {"position": [0, 0, 0], "certainty": 1.5, "fatigue": 0.9}}"#,
+        ] {
+            let response = roundtrip(&config, &post(body)).await;
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{body}: {response}");
+        }
+
+        let response = roundtrip(&config, "POST /verify HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 411 Length Required"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_schema_version() {
+        let (_dir, config) = test_config();
+        let body = r#"{"state": {"position": [3, 0, 4], "certainty": 0.9, "fatigue": 0.8}, "obstacles": [[10, 0, 4]]}"#;
+        let request = format!("POST /verify HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let response = roundtrip(&config, &request).await;
+        let (_, body) = split_response(response.as_bytes());
+        let result: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(result["schema_version"], VERIFY_SCHEMA_VERSION);
+
+        let response = roundtrip(&config, "GET /verify/schema HTTP/1.1\r\n\r\n").await;
+        let (headers, body) = split_response(response.as_bytes());
+        assert!(headers.starts_with("HTTP/1.1 200 OK"), "{headers}");
+        assert_eq!(header_value(&headers, "Content-Type"), Some("application/schema+json"));
+        let schema: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(schema["schema_version"], VERIFY_SCHEMA_VERSION);
+
+        // The documented response fields are exactly the ones served
+        let documented = schema["response"]["properties"].as_object().unwrap();
+        let served = result.as_object().unwrap();
+        assert_eq!(documented.keys().collect::<Vec<_>>(), served.keys().collect::<Vec<_>>());
+        // ... and every one of them is always present
+        let mut required: Vec<_> = schema["response"]["required"].as_array().unwrap().iter().map(|key| key.as_str().unwrap()).collect();
+        required.sort_unstable();
+        assert_eq!(required, served.keys().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(result["terms"].as_array().unwrap().len(), 5);
+        let hash = result["evidence_hash"].as_str().unwrap().strip_prefix("sha256:").unwrap();
+        assert!(hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')));
+
+        // Every documented param default is accepted by the request parser
+        let defaults: serde_json::Map<_, _> = schema["request"]["properties"]["params"]["properties"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .filter_map(|(key, field)| Some((key.clone(), field.get("default")?.clone())))
+            .collect();
+        assert!(defaults.len() >= 10);
+        let params: VerifyParams = serde_json::from_value(defaults.into()).unwrap();
+        assert_eq!(format!("{:?}", params), format!("{:?}", VerifyParams::default()));
+    }
+
+    #[tokio::test]
+    async fn test_ws_verify() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let (_dir, config) = test_config();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move { handle_client(server, &config, "test").await.map_err(|e| e.to_string()) });
+        let (mut ws, response) = tokio_tungstenite::client_async("ws://localhost/ws/verify", client).await.unwrap();
+        assert_eq!(response.status(), 101);
+
+        // Same request as test_verify_endpoint, answered with the same result
+        let body = r#"{"state": {"position": [3, 0, 4], "certainty": 0.9, "fatigue": 0.8},
+                       "params": {"min_margin": 0.5}, "obstacles": [[10, 0, 4]]}"#;
+        ws.send(Message::text(body)).await.unwrap();
+        let Some(Ok(Message::Text(reply))) = ws.next().await else { panic!("expected a text reply") };
+        let result: VerifyResponse = serde_json::from_str(&reply).unwrap();
+        assert_eq!(result.schema_version, VERIFY_SCHEMA_VERSION);
+        assert!((result.p_score - 6.7).abs() < 1e-5, "p_score {}", result.p_score);
+        assert!((result.margin - 6.5).abs() < 1e-5);
+
+        // A bad request is answered with an error and the socket stays usable
+        ws.send(Message::text("{not json")).await.unwrap();
+        let Some(Ok(Message::Text(reply))) = ws.next().await else { panic!("expected a text reply") };
+        let error: ErrorResponse = serde_json::from_str(&reply).unwrap();
+        assert!(error.error.starts_with("Invalid verify request"), "{}", error.error);
+
+        ws.send(Message::Ping("keepalive".into())).await.unwrap();
+        let Some(Ok(Message::Pong(payload))) = ws.next().await else { panic!("expected a pong") };
+        assert_eq!(&payload[..], b"keepalive");
+
+        // Clean close: the server echoes the close frame and ends the connection
+        ws.close(None).await.unwrap();
+        while let Some(Ok(_)) = ws.next().await {}
+        server.await.unwrap().unwrap();
+
+        // A plain GET without the upgrade headers is refused
+        let (_dir, config) = test_config();
+        let response = roundtrip(&config, "GET /ws/verify HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{response}");
+        let request = "GET /ws/verify HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 8\r\n\r\n";
+        let response = roundtrip(&config, request).await;
+        assert!(response.starts_with("HTTP/1.1 426 Upgrade Required"), "{response}");
+        assert_eq!(header_value(&response, "Sec-WebSocket-Version"), Some("13"));
+    }
+
+    #[tokio::test]
+    async fn test_watch_asset() {
+        let (dir, config) = test_config();
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move { handle_client(server, &config, "test").await.map_err(|e| e.to_string()) });
+        client.write_all(b"GET /watch/Assets/model.txt HTTP/1.1\r\n\r\n").await.unwrap();
+
+        // Events are JSON lines inside chunks; `next_event` waits for the next complete one
+        let mut received = Vec::new();
+        let mut seen = 0;
+        let mut next_event = async || -> WatchEvent {
+            loop {
+                let text = String::from_utf8_lossy(&received);
+                let body = text.split_once("\r\n\r\n").map_or("", |(_, body)| body);
+                let events: Vec<WatchEvent> = body.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+                if let Some(event) = events.into_iter().nth(seen) {
+                    seen += 1;
+                    return event;
+                }
+                let mut buf = [0u8; 1024];
+                let n = tokio::time::timeout(Duration::from_secs(10), client.read(&mut buf)).await.unwrap().unwrap();
+                assert!(n > 0, "stream ended");
+                received.extend_from_slice(&buf[..n]);
+            }
+        };
+
+        let watching = next_event().await;
+        assert_eq!(watching.event, "watching");
+        assert_eq!(watching.name, "model.txt");
+        assert_eq!(watching.size, Some(5));
+
+        std::fs::write(dir.path().join("model.txt"), b"hello, world").unwrap();
+        let changed = next_event().await;
+        assert_eq!(changed.event, "changed");
+        assert_eq!(changed.size, Some(12));
+        assert_ne!(changed.etag, watching.etag);
+        let text = String::from_utf8_lossy(&received).into_owned();
+        assert!(text.starts_with("HTTP/1.1 200 OK"), "{text}");
+        assert_eq!(header_value(&text, "Content-Type"), Some("application/x-ndjson"));
+
+        // Disconnecting ends the watch
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(10), server).await.unwrap().unwrap().unwrap();
+
+        let (_dir, config) = test_config();
+        let response = roundtrip(&config, "GET /watch/Assets/missing.txt HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"), "{response}");
+        let response = roundtrip(&config, "GET /watch/Assets/../secret HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let (_dir, config) = test_config();
+        roundtrip(&config, "GET /Assets/missing.txt HTTP/1.1\r\n\r\n").await;
+        roundtrip(&config, "POST /Assets/upload.bin HTTP/1.1\r\n\r\n").await;
+
+        let response = roundtrip(&config, "GET /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+        assert!(response.contains("# TYPE nav_server_requests_total counter\n"));
+        assert!(response.contains("# TYPE nav_server_active_connections gauge\n"));
+
+        // Counters are process-wide and other tests run concurrently, so only check lower bounds
+        let value = |name: &str| -> u64 {
+            response
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')?.parse().ok())
+                .unwrap()
+        };
+        assert!(value("nav_server_requests_total") >= 2);
+        assert!(value("nav_server_errors_total") >= 1);
+        assert!(value("nav_server_uploads_total") >= 1);
+        assert!(value("nav_server_bytes_sent_total") > 0);
+        assert!(value("nav_server_active_connections") >= 1); // This one
+    }
+
+    #[tokio::test]
+    async fn test_stalled_reader_times_out() {
+        let (dir, mut config) = test_config();
+        config.write_timeout = Duration::from_millis(100);
+        std::fs::write(dir.path().join("large.bin"), vec![0u8; 256 * 1024]).unwrap();
+
+        // The client sends a request and then never reads, so the small pipe fills up
+        let (mut client, server) = tokio::io::duplex(4 * 1024);
+        client.write_all(b"GET /Assets/large.bin HTTP/1.1\r\n\r\n").await.unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), handle_client(server, &config, "test"))
+            .await
+            .expect("handle_client should give up on its own");
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("write timed out"));
+        drop(client);
+    }
+
+    #[tokio::test]
+    async fn test_client_disconnect_mid_stream() {
+        let (dir, config) = test_config();
+        std::fs::write(dir.path().join("large.bin"), vec![0u8; 256 * 1024]).unwrap();
+
+        // The client reads the first few KB of the body, then hangs up
+        let (mut client, server) = tokio::io::duplex(4 * 1024);
+        client.write_all(b"GET /Assets/large.bin HTTP/1.1\r\n\r\n").await.unwrap();
+        let serve = tokio::spawn(async move {
+            let result = handle_client(server, &config, "test").await;
+            result.map_err(|e| (is_client_disconnect(e.as_ref()), e.to_string()))
+        });
+        let mut partial = vec![0u8; 8 * 1024];
+        client.read_exact(&mut partial).await.unwrap();
+        assert!(partial.starts_with(b"HTTP/1.1 200 OK"));
+        drop(client);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), serve)
+            .await
+            .expect("handle_client should stop on its own")
+            .unwrap();
+        let (disconnect, message) = result.unwrap_err();
+        assert!(disconnect, "unexpected error kind: {}", message);
+
+        // Counted as a disconnect, not a connection error
+        let (_dir, config) = test_config();
+        let before = METRICS.client_disconnects_total.load(Ordering::Relaxed);
+        let broken = std::io::Error::from(std::io::ErrorKind::BrokenPipe);
+        report_connection_error(&config, "test", &broken);
+        assert!(METRICS.client_disconnects_total.load(Ordering::Relaxed) > before);
+        assert!(is_client_disconnect(&std::io::Error::from(std::io::ErrorKind::ConnectionReset)));
+        assert!(!is_client_disconnect(&std::io::Error::from(std::io::ErrorKind::TimedOut)));
+        assert!(!is_client_disconnect(&into_io_error("bad request".into())));
+        assert_eq!(into_io_error(Box::new(broken)).kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_head_times_out() {
+        let (_dir, mut config) = test_config();
+        config.read_timeout = Duration::from_millis(100);
+
+        // Half a request line, then silence
+        let (mut client, server) = tokio::io::duplex(4 * 1024);
+        client.write_all(b"GET /Assets/mod").await.unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), handle_client(server, &config, "test"))
+            .await
+            .expect("handle_client should give up on its own");
+        assert!(result.is_ok());
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert!(response.is_empty());
+    }
+}
@@ -287,7 +287,7 @@ public class CertificationCompiler : MonoBehaviour
         RustCoreBridge.State7D state = new RustCoreBridge.State7D();
         state.position = new float[3] { pos.x, pos.y, pos.z };
         state.velocity = new float[3] { vel.x, vel.y, vel.z };
-        state.heading = realRobot != null ? realRobot.transform.eulerAngles.y : transform.eulerAngles.y;
+        state.heading = (realRobot != null ? realRobot.transform.eulerAngles.y : transform.eulerAngles.y) * Mathf.Deg2Rad;
         state.timestamp = (ulong)(Time.time * 1000);
         state.certainty = GetCertainty();
         state.fatigue = GetFatigue();
@@ -296,7 +296,9 @@ public class CertificationCompiler : MonoBehaviour
         RustCoreBridge.RigorParams parameters = new RustCoreBridge.RigorParams
         {
             alpha = vncVerifier != null ? vncVerifier.alpha : 5.0f,
-            min_margin = GetMinMargin()
+            min_margin = GetMinMargin(),
+            weights = new float[5] { 1f, 1f, 1f, 1f, 1f },
+            reference_origin = new float[3]
         };
 
         // Gather Obstacles (Flatten to float array for Rust)
@@ -312,15 +314,15 @@ public class CertificationCompiler : MonoBehaviour
 
         // Call Rust (P/Invoke)
         RustCoreBridge.VerificationResult result;
-        int success = RustCoreBridge.calculate_p_score(
+        RustCoreBridge.NavStatus status = RustCoreBridge.calculate_p_score(
             ref state,
             ref parameters,
             obsArray,
-            obsList.Length,
+            (UIntPtr)obsList.Length,
             out result
         );
 
-        if (success != 0)
+        if (status == RustCoreBridge.NavStatus.Ok)
         {
             // Convert to C# format
             RustCoreBridge.VerificationResultCSharp resultCSharp = RustCoreBridge.ConvertResult(result);
@@ -328,7 +330,7 @@ public class CertificationCompiler : MonoBehaviour
         }
         else
         {
-            Debug.LogError("[RustCore] Verification call failed. Falling back to C#.");
+            Debug.LogError($"[RustCore] Verification call failed ({status}). Falling back to C#.");
             if (enableFallback)
             {
                 CompileCertificateCSharp();
@@ -464,7 +466,7 @@ public class CertificationCompiler : MonoBehaviour
         if (sigma == 0.0f && controlVariates.Count > 0)
         {
             float[] variates = controlVariates.ToArray();
-            RustCoreBridge.calculate_sim2val_uncertainty(variates, variates.Length, out sigma);
+            RustCoreBridge.calculate_sim2val_uncertainty(variates, (UIntPtr)variates.Length, out sigma);
         }
         
         // Generate Certificate
@@ -31,12 +31,32 @@ public static class RustCoreBridge
         [MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
         public float[] velocity; // vx, vy, vz
         
-        public float heading;    // Theta
+        public float heading;    // Theta (radians)
         public ulong timestamp;
         public float certainty;  // 'i' (Model Confidence)
         public float fatigue;    // 'c' (Consciousness/Fatigue)
     }
 
+    /// <summary>
+    /// State7D with a quaternion orientation [w, x, y, z] (Y-up world, body forward = +X) instead of heading
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public struct State7DQuat
+    {
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+        public float[] position;
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+        public float[] velocity;
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 4)]
+        public float[] orientation; // w, x, y, z (scalar first; Unity's Quaternion is x, y, z, w)
+
+        public ulong timestamp;
+        public float certainty;
+        public float fatigue;
+    }
+
     [StructLayout(LayoutKind.Sequential)]
     public struct VerificationResult
     {
@@ -45,7 +65,49 @@ public static class RustCoreBridge
         public float margin;
         public float sigma;      // Uncertainty (from SIM2VAL)
         public IntPtr breach_reason; // String pointer
-        public IntPtr evidence_hash; // SHA-256 hash string
+        public IntPtr evidence_hash; // "<algo>:<hex digest>", e.g. "sha256:..."
+        public float time_to_collision; // Seconds until predicted breach (float.MaxValue if none)
+        public int closest_obstacle_index; // Obstacle that produced margin (-1 if none)
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+        public float[] closest_obstacle_pos;
+
+        public ulong breach_flags; // Every active violation (BREACH_FLAG_* bits, 0 = none; see soft_cognitive_breach)
+        public int severity;       // 0 = SAFE, 1 = CAUTION, 2 = WARNING, 3 = CRITICAL
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 5)]
+        public float[] terms;      // Weighted [pos_norm, t_phase, g_gradient, i_intent, c_consciousness], sum to p_score
+    }
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Ansi)]
+    public struct BreachRecord
+    {
+        public ulong timestamp;
+        public ulong breach_flags; // BREACH_FLAG_* bits
+        public float p_score;
+        public float margin;
+
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 136)]
+        public string evidence_hash; // "<algo>:<hex digest>" (up to 135 chars)
+        public float sigma; // 0 unless logged by calculate_p_score_with_uncertainty
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct VerificationResultFixed
+    {
+        public int p_score;                // Q16.16 (divide by 65536f)
+        public int is_safe;
+        public int margin;                 // Q16.16, int.MaxValue if no obstacles
+        public int time_to_collision;      // Q16.16 seconds, int.MaxValue if none
+        public int closest_obstacle_index; // -1 if none
+        public ulong breach_flags;         // BREACH_FLAG_* bits
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Obstacle
+    {
+        public float x, y, z; // Center (Rust: pos[3])
+        public float radius;  // 0 for a point obstacle
     }
 
     [StructLayout(LayoutKind.Sequential)]
@@ -53,6 +115,51 @@ public static class RustCoreBridge
     {
         public float alpha;      // Class-K (Rigorousness)
         public float min_margin;
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 5)]
+        public float[] weights;  // [pos_norm, t_phase, g_gradient, i_intent, c_consciousness]
+        public float horizon_secs; // Collision lookahead (0 = static check only)
+        public int planar_mode;    // Non-zero: ignore Z in all distances, zero the gradient
+
+        [MarshalAs(UnmanagedType.ByValArray, SizeConst = 3)]
+        public float[] reference_origin; // Point pos_norm is measured from (world origin by default)
+        public int clamp_cognitive;      // Non-zero: saturate certainty/fatigue to [0, 1]; zero: reject with OutOfRange
+        public ulong time_period;        // t_phase period in timestamp ticks (0 = 10000)
+        public float speed_confidence_decay; // Certainty discount per m/s of speed (0 = none)
+        public int soft_cognitive_breach;    // Non-zero: fatigue/certainty breaches are flagged but keep is_safe = 1
+        public float max_decel;              // Braking deceleration (m/s^2); clearance must cover the stopping distance (0 = off)
+        public int distance_metric;          // Obstacle clearance and pos_norm: 0 = Euclidean, 1 = Manhattan, 2 = Chebyshev
+        public int hash_algo;                // Evidence hash: 0 = SHA-256, 1 = SHA-512, 2 = BLAKE3
+    }
+
+    /// <summary>
+    /// Status codes returned by the fallible FFI functions (0 = Ok)
+    /// </summary>
+    public enum NavStatus
+    {
+        Ok = 0,
+        NullPointer = 1,
+        EmptyInput = 2,
+        NonFinite = 3,
+        Uninitialized = 4,
+        InsufficientSamples = 5,
+        OutOfRange = 6,
+        UnknownPointer = 7,
+        SizeMismatch = 8,
+        InvalidAlpha = 9,
+        InvalidMinMargin = 10,
+        InvalidWeights = 11,
+        InvalidHorizon = 12,
+        InvalidReferenceOrigin = 13,
+        InvalidSpeedDecay = 14,
+        InvalidMaxDecel = 15,
+        IoError = 16,
+        InvalidDistanceMetric = 17,
+        InvalidHashAlgo = 18,
+        TooManyObstacles = 19,
+        StateSizeMismatch = 20,
+        ResultSizeMismatch = 21,
+        ParamsSizeMismatch = 22
     }
 
     // --- FFI Function Declarations ---
@@ -60,31 +167,214 @@ public static class RustCoreBridge
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
     public static extern int rust_core_init();
 
+    // Init that fails when these mirrored structs' sizes differ from the library's
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus rust_core_init_checked(UIntPtr state_size, UIntPtr result_size, UIntPtr params_size);
+
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
     public static extern int check_system_robustness();
 
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int validate_unity_alloc(IntPtr ptr, ulong size);
+    public static extern void rust_core_shutdown(); // Resets global state; rust_core_init may be called again
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_register_alloc(IntPtr ptr, UIntPtr size); // Record before validate_unity_alloc can return Ok
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_unregister_alloc(IntPtr ptr); // UnknownPointer if never registered
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus validate_unity_alloc(IntPtr ptr, UIntPtr size);
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus validate_rigor_params(ref RigorParams parameters); // Call at config load
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus calculate_p_score(
+        ref State7D state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count,
+        out VerificationResult result
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus calculate_p_score_obstacles( // Typed [x, y, z, radius] records
+        ref State7D state,
+        ref RigorParams parameters,
+        [In] Obstacle[] obstacles,
+        UIntPtr obstacle_count,
+        out VerificationResult result
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern float calculate_min_margin( // < 0 = breach, float.MaxValue = no obstacles, NaN = invalid input
+        ref State7D state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr calculate_breaching_obstacles( // Breaching count; negative = required capacity; long.MinValue = invalid input
+        ref State7D state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count,
+        [Out] int[] indices,
+        UIntPtr out_cap
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus compute_risk_grid( // Margin per cell center, row-major out[row * width + col]
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count,
+        float origin_x,
+        float origin_y,
+        float cell_size,
+        UIntPtr width,
+        UIntPtr height,
+        [Out] float[] out_margins
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern float calculate_stopping_distance(ref State7D state, ref RigorParams parameters); // v^2 / (2 * max_decel), NaN = invalid input
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus state7d_lerp(ref State7D a, ref State7D b, float t, out State7D result); // t clamped to [0, 1]; heading takes the short way round
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern ulong verify_flags_only( // BREACH_FLAG_* bits as calculate_p_score reports them (soft cognitive bits can be set on a safe verdict), ulong.MaxValue = invalid input
+        ref State7D state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr nav_merge_obstacles( // Merged count; negative = required capacity; long.MinValue = invalid input
+        IntPtr[] arrays,   // Pinned float[] per sensor
+        UIntPtr[] counts,  // Obstacles per array
+        UIntPtr array_count,
+        float merge_radius,
+        [Out] float[] merged,
+        UIntPtr out_cap
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus calculate_p_score_fixed( // Integer-only, bit-identical across platforms
+        ref State7D state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count,
+        out VerificationResultFixed result
+    );
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus calculate_p_score_quat(
+        ref State7DQuat state,
+        ref RigorParams parameters,
+        [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
+        UIntPtr obstacle_count,
+        out VerificationResult result
+    );
 
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int calculate_p_score(
+    public static extern NavStatus calculate_p_score_masked(
         ref State7D state,
         ref RigorParams parameters,
         [MarshalAs(UnmanagedType.LPArray)] float[] obstacles,
-        int obstacle_count,
+        UIntPtr obstacle_count,
+        [MarshalAs(UnmanagedType.LPArray)] byte[] valid_mask, // One byte per slot, non-zero = live
         out VerificationResult result
     );
 
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int calculate_sim2val_uncertainty(
+    public static extern NavStatus calculate_sim2val_uncertainty(
         [MarshalAs(UnmanagedType.LPArray)] float[] control_variates,
-        int variate_count,
+        UIntPtr variate_count,
         out float result_sigma
     );
 
+    // Streaming sigma (one handle per stream; free with sim2val_accum_destroy)
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr sim2val_accum_create();
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void sim2val_accum_destroy(IntPtr acc);
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus sim2val_accum_push(IntPtr acc, float value);
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern float sim2val_accum_sigma(IntPtr acc); // NaN when empty
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void sim2val_accum_reset(IntPtr acc);
+
     [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
     public static extern void free_c_string(IntPtr ptr);
 
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void free_verification_result(ref VerificationResult result); // Frees both strings, nulls the pointers
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int verification_result_equal( // 1 = equivalent (scores within tol, same verdict and reason)
+        ref VerificationResult a,
+        ref VerificationResult b,
+        float tol
+    );
+
+    // Obstacle count cap, checked before the array is read (IntPtr.Zero = default context, 0 is rejected)
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_context_set_max_obstacles(IntPtr ctx, UIntPtr max_obstacles);
+
+    // Verdict hysteresis: a margin breach holds until margin >= band (IntPtr.Zero = default context, 0 = off)
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_context_set_hysteresis(IntPtr ctx, float band);
+
+    // Terrain for the gradient term (IntPtr.Zero = default context; null data clears)
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_set_heightmap(
+        IntPtr ctx,
+        [MarshalAs(UnmanagedType.LPArray)] float[] data, // Row-major, rows along Z
+        UIntPtr width,
+        UIntPtr height,
+        float cell_size,
+        float origin_x,
+        float origin_z
+    );
+
+    // Breach audit log (IntPtr.Zero = default context used by the global functions)
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern UIntPtr nav_breach_log_count(IntPtr ctx);
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern NavStatus nav_breach_log_get(IntPtr ctx, UIntPtr index, out BreachRecord record); // 0 = oldest
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void nav_breach_log_set_capacity(IntPtr ctx, UIntPtr capacity);
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr nav_breach_log_to_csv(IntPtr ctx); // free with free_c_string
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
+    public static extern NavStatus nav_set_breach_log_file(string path); // JSON lines from every context; null disables
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr nav_status_message(int code); // Static string, do not free
+
+    // Last failure on this thread ("function: message"); do not free, copy it before the next call
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr nav_last_error();
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern uint nav_core_version(); // (major << 16) | (minor << 8) | patch
+
+    [DllImport(RUST_LIB_NAME, CallingConvention = CallingConvention.Cdecl)]
+    public static extern ulong nav_core_abi_hash();
+
     // --- Helper Methods ---
     
     /// <summary>
@@ -98,15 +388,15 @@ public static class RustCoreBridge
         if (result.breach_reason != IntPtr.Zero)
         {
             breachReason = Marshal.PtrToStringAnsi(result.breach_reason);
-            free_c_string(result.breach_reason);
         }
 
         if (result.evidence_hash != IntPtr.Zero)
         {
             evidenceHash = Marshal.PtrToStringAnsi(result.evidence_hash);
-            free_c_string(result.evidence_hash);
         }
 
+        free_verification_result(ref result);
+
         return new VerificationResultCSharp
         {
             p_score = result.p_score,
@@ -158,8 +448,16 @@ public static class RustCoreBridge
     {
         try
         {
-            int result = rust_core_init();
-            return result != 0;
+            NavStatus status = rust_core_init_checked(
+                (UIntPtr)Marshal.SizeOf<State7D>(),
+                (UIntPtr)Marshal.SizeOf<VerificationResult>(),
+                (UIntPtr)Marshal.SizeOf<RigorParams>());
+            if (status != NavStatus.Ok)
+            {
+                Debug.LogError($"[RustCore] Struct layout does not match the native library ({status}); rebuild the bindings");
+                return false;
+            }
+            return true;
         }
         catch (DllNotFoundException)
         {